@@ -1,31 +1,153 @@
+use numpy::{PyArray1, PyArray2, PyArray3};
 use pyo3::prelude::*;
+use pyo3::exceptions::{PyIndexError, PyKeyError, PyValueError};
 
 use std::path::PathBuf;
-use grid::block::{Block, BlockIO};
+use common::vector3::Vector3;
+use grid::block::{BlockCollection, GridBlock};
+use grid::{Block, Interface, Vertex};
 
-/// Python facing wrapper for a Block
+use crate::vector3::PyVector3;
+
+/// Python facing wrapper for a GridBlock
 #[pyclass(name="Block")]
+#[derive(Clone)]
 pub struct PyBlock {
-    pub inner: Block,
+    pub inner: GridBlock,
 }
 
-#[pyclass(name="BlockIO")]
-pub struct PyBlockIO {
-    pub block_io: BlockIO,
-    pub blocks: Vec<PyBlock>,
+#[pymethods]
+impl PyBlock {
+    fn cell_count(&self) -> usize {
+        self.inner.cells().len()
+    }
+
+    fn vertex_count(&self) -> usize {
+        self.inner.vertices().len()
+    }
+
+    fn interface_count(&self) -> usize {
+        self.inner.interfaces().len()
+    }
+
+    /// The tags of the boundaries of the block, in alphabetical order
+    fn boundary_tags(&self) -> Vec<String> {
+        self.inner.boundary_tags().into_iter().map(String::from).collect()
+    }
+
+    /// The number of interfaces on the boundary with the given tag
+    fn boundary_interface_count(&self, tag: &str) -> PyResult<usize> {
+        match self.inner.boundary_interfaces(tag) {
+            Some(interfaces) => Ok(interfaces.len()),
+            None => Err(PyKeyError::new_err(format!("No boundary with tag '{}'", tag))),
+        }
+    }
+
+    /// The (min, max) corners of the smallest axis aligned
+    /// box containing every vertex of the block
+    fn bounding_box(&self) -> (PyVector3, PyVector3) {
+        let (min, max) = self.inner.bounding_box();
+        (min.into(), max.into())
+    }
+
+    /// The average position of the vertices
+    fn centroid(&self) -> PyVector3 {
+        self.inner.centroid().into()
+    }
+
+    /// The length of the diagonal of the bounding box
+    fn characteristic_length(&self) -> f64 {
+        self.inner.characteristic_length()
+    }
+
+    /// The (x, y, z) position of each vertex, as an N x 3 array
+    fn vertex_positions(&self, py: Python) -> PyResult<Py<PyArray2<f64>>> {
+        let positions = self.inner.vertices().iter().map(|vertex| vertex.pos());
+        vector3s_to_array(py, positions)
+    }
+
+    /// The (x, y, z) position of the centre of each cell, as an N x 3 array
+    fn cell_centres(&self, py: Python) -> PyResult<Py<PyArray2<f64>>> {
+        let centres = self.inner.cells().iter().map(|cell| cell.centre());
+        vector3s_to_array(py, centres)
+    }
+
+    /// The volume of each cell
+    fn cell_volumes(&self, py: Python) -> PyResult<Py<PyArray1<f64>>> {
+        let volumes: Vec<f64> = self.inner.cells().iter().map(|cell| cell.volume()).collect();
+        Ok(PyArray1::from_vec(py, volumes).into())
+    }
+
+    /// The (x, y, z) position of each vertex of each interface on the
+    /// boundary with the given tag, as an array of shape
+    /// (number of interfaces, vertices per interface, 3)
+    fn boundary_interface_positions(&self, tag: &str, py: Python) -> PyResult<Py<PyArray3<f64>>> {
+        let interface_ids = match self.inner.boundaries().get(tag) {
+            Some(interface_ids) => interface_ids,
+            None => return Err(PyKeyError::new_err(format!("No boundary with tag '{}'", tag))),
+        };
+        let interfaces = self.inner.interfaces();
+        let vertices = self.inner.vertices();
+        let n_vertices = interface_ids
+            .first()
+            .map_or(2, |id| interfaces[*id].vertex_ids().len());
+
+        let mut components = Vec::with_capacity(interface_ids.len() * n_vertices * 3);
+        for id in interface_ids.iter() {
+            let vertex_ids = interfaces[*id].vertex_ids();
+            if vertex_ids.len() != n_vertices {
+                return Err(PyValueError::new_err(
+                    format!("Interfaces on boundary '{}' have different numbers of vertices", tag)
+                ));
+            }
+            for vertex_id in vertex_ids.iter() {
+                let pos = vertices[*vertex_id].pos();
+                components.extend([pos.x, pos.y, pos.z]);
+            }
+        }
+        let shape = [interface_ids.len(), n_vertices, 3];
+        Ok(PyArray1::from_vec(py, components).reshape(shape)?.into())
+    }
+}
+
+/// Pack vectors into an N x 3 numpy array
+fn vector3s_to_array<'a>(py: Python, vectors: impl ExactSizeIterator<Item = &'a Vector3>) -> PyResult<Py<PyArray2<f64>>> {
+    let n = vectors.len();
+    let mut components = Vec::with_capacity(3 * n);
+    for vector in vectors {
+        components.extend([vector.x, vector.y, vector.z]);
+    }
+    Ok(PyArray1::from_vec(py, components).reshape([n, 3])?.into())
+}
+
+/// Python facing wrapper for a BlockCollection
+#[pyclass(name="BlockCollection")]
+pub struct PyBlockCollection {
+    pub inner: BlockCollection,
 }
 
 #[pymethods]
-impl PyBlockIO {
+impl PyBlockCollection {
     #[new]
-    fn new() -> PyBlockIO {
-        PyBlockIO{ block_io: BlockIO::new(), blocks: Vec::new() }
+    fn new() -> PyBlockCollection {
+        PyBlockCollection{ inner: BlockCollection::new() }
+    }
+
+    fn add_block(&mut self, file_path: &str) -> PyResult<()> {
+        self.inner
+            .add_block(&PathBuf::from(file_path))
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// A copy of the block with the given id
+    fn block(&self, id: usize) -> PyResult<PyBlock> {
+        match self.inner.find_block(id) {
+            Some(block) => Ok(PyBlock{ inner: block.clone() }),
+            None => Err(PyIndexError::new_err(format!("No block with id {}", id))),
+        }
     }
 
-    fn add_block(&mut self, file_path: &str) {
-        let block = self.block_io
-            .create_block(&PathBuf::from(file_path))
-            .unwrap();
-        self.blocks.push( PyBlock{ inner: block } );
+    fn __len__(&self) -> usize {
+        self.inner.blocks().len()
     }
 }
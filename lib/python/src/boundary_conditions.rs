@@ -0,0 +1,53 @@
+use pyo3::prelude::*;
+
+use common::number::Real;
+use finite_volume::boundary_conditions::spec::BoundaryConditionSpec;
+
+use crate::flow_state::PyFlowState;
+
+/// Python facing wrapper of a BoundaryConditionSpec. These are
+/// created with the `slip_wall`, `supersonic_inflow`,
+/// `supersonic_outflow` and `pressure_outflow` functions.
+///
+/// >>> from aeolus_py import slip_wall
+/// >>> slip_wall("wall").tag
+/// 'wall'
+#[pyclass(name="BoundaryConditionSpec")]
+#[derive(Clone)]
+pub struct PyBoundaryConditionSpec {
+    pub inner: BoundaryConditionSpec,
+}
+
+#[pymethods]
+impl PyBoundaryConditionSpec {
+    fn __repr__(&self) -> String {
+        format!("BoundaryConditionSpec(tag={}, kind={:?})", self.inner.tag(), self.inner.kind())
+    }
+
+    /// The tag of the boundary this applies to
+    #[getter]
+    fn tag(&self) -> String {
+        self.inner.tag().to_string()
+    }
+}
+
+#[pyfunction]
+pub fn slip_wall(tag: &str) -> PyBoundaryConditionSpec {
+    PyBoundaryConditionSpec{inner: BoundaryConditionSpec::slip_wall(tag)}
+}
+
+#[pyfunction]
+pub fn supersonic_inflow(tag: &str, flow_state: &PyFlowState) -> PyBoundaryConditionSpec {
+    PyBoundaryConditionSpec{inner: BoundaryConditionSpec::supersonic_inflow(tag, flow_state.inner)}
+}
+
+#[pyfunction]
+pub fn supersonic_outflow(tag: &str) -> PyBoundaryConditionSpec {
+    PyBoundaryConditionSpec{inner: BoundaryConditionSpec::supersonic_outflow(tag)}
+}
+
+/// A subsonic outflow, with the pressure held at `p_back`
+#[pyfunction]
+pub fn pressure_outflow(tag: &str, p_back: Real) -> PyBoundaryConditionSpec {
+    PyBoundaryConditionSpec{inner: BoundaryConditionSpec::pressure_outflow(tag, p_back)}
+}
@@ -0,0 +1,109 @@
+use pyo3::prelude::*;
+
+use common::number::Real;
+use common::vector3::Vector3;
+use gas::flow_state::FlowState;
+use gas::gas_state::GasState;
+
+/// The flow state at a point: the state of the gas, and its velocity.
+/// The density, energy and sound speed are filled in by the gas model.
+///
+/// >>> from aeolus_py import FlowState, IdealGas
+/// >>> fs = FlowState(101325.0, 300.0, 0.0, 0.0, 0.0)
+/// >>> IdealGas(287.0, 1.4).update_from_pT(fs)
+/// >>> round(fs.rho, 3)
+/// 1.177
+#[pyclass(name="FlowState")]
+pub struct PyFlowState {
+    pub inner: FlowState<Real>,
+}
+
+#[allow(non_snake_case)]
+#[pymethods]
+impl PyFlowState {
+    #[new]
+    fn new(p: Real, T: Real, vx: Real, vy: Real, vz: Real) -> PyFlowState {
+        let gas_state = GasState{p, T, ..GasState::default()};
+        let velocity = Vector3{x: vx, y: vy, z: vz};
+        PyFlowState{inner: FlowState::new(gas_state, velocity)}
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    fn __repr__(&self) -> String {
+        let gs = self.inner.gas_state();
+        let vel = self.inner.velocity();
+        format!("FlowState(p={}, T={}, rho={}, u={}, a={}, vx={}, vy={}, vz={})",
+                gs.p, gs.T, gs.rho, gs.u, gs.a, vel.x, vel.y, vel.z)
+    }
+
+    #[getter]
+    fn get_p(&self) -> Real {
+        self.inner.gas_state().p
+    }
+
+    #[setter]
+    fn set_p(&mut self, val: Real) {
+        self.inner.gas_state_mut().p = val;
+    }
+
+    #[getter]
+    fn get_T(&self) -> Real {
+        self.inner.gas_state().T
+    }
+
+    #[setter]
+    fn set_T(&mut self, val: Real) {
+        self.inner.gas_state_mut().T = val;
+    }
+
+    #[getter]
+    fn get_vx(&self) -> Real {
+        self.inner.velocity().x
+    }
+
+    #[setter]
+    fn set_vx(&mut self, val: Real) {
+        self.inner.velocity_mut().x = val;
+    }
+
+    #[getter]
+    fn get_vy(&self) -> Real {
+        self.inner.velocity().y
+    }
+
+    #[setter]
+    fn set_vy(&mut self, val: Real) {
+        self.inner.velocity_mut().y = val;
+    }
+
+    #[getter]
+    fn get_vz(&self) -> Real {
+        self.inner.velocity().z
+    }
+
+    #[setter]
+    fn set_vz(&mut self, val: Real) {
+        self.inner.velocity_mut().z = val;
+    }
+
+    /// The density, set by the gas model
+    #[getter]
+    fn get_rho(&self) -> Real {
+        self.inner.gas_state().rho
+    }
+
+    /// The specific energy, set by the gas model
+    #[getter]
+    fn get_u(&self) -> Real {
+        self.inner.gas_state().u
+    }
+
+    /// The sound speed, set by the gas model
+    #[getter]
+    fn get_a(&self) -> Real {
+        self.inner.gas_state().a
+    }
+}
@@ -1,34 +1,64 @@
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyRuntimeError, PyTypeError};
+
+use common::number::Real;
+use gas::gas_model::GasModelError;
+use gas::gas_state::GasState;
+
+use crate::flow_state::PyFlowState;
+use crate::gas_state::PyGasState;
+
+/// Apply `update` to the gas state of a python GasState or FlowState
+pub(crate) fn update_gas_state<F>(state: &PyAny, update: F) -> PyResult<()>
+    where F: FnOnce(&mut GasState<Real>) -> Result<(), GasModelError>
+{
+    let result = if let Ok(mut gs) = state.extract::<PyRefMut<PyGasState>>() {
+        update(&mut gs.inner)
+    }
+    else if let Ok(mut fs) = state.extract::<PyRefMut<PyFlowState>>() {
+        update(fs.inner.gas_state_mut())
+    }
+    else {
+        return Err(PyTypeError::new_err("Expected a GasState or a FlowState"));
+    };
+    result.map_err(|err| PyRuntimeError::new_err(err.to_string()))
+}
 
 /// Generate a python interface to the GasModel trait for
 /// a concrete type implementing the trait
 macro_rules! create_gas_model_python_interface {
     ($inner_name: ident, $wrapper_name: ident, $python_name: literal) => {
-        use pyo3::{pyclass, pymethods};
+        use pyo3::{pyclass, pymethods, PyAny, PyResult};
+        use crate::gas_model::update_gas_state;
         use crate::gas_state::PyGasState;
         use gas::gas_model::GasModel;
 
-        #[pyclass]
+        #[pyclass(name = $python_name)]
         pub struct $wrapper_name {
-            inner: $inner_name<Real>
+            pub inner: $inner_name<Real>
         }
 
         #[allow(non_snake_case)]
         #[pymethods]
         impl $wrapper_name {
-            fn update_from_pT(&self, gs: &mut PyGasState) {
-                self.inner.update_from_pT(&mut gs.inner);
+            /// Update a GasState or FlowState from its pressure and temperature
+            fn update_from_pT(&self, state: &PyAny) -> PyResult<()> {
+                update_gas_state(state, |gs| self.inner.update_from_pT(gs))
             }
 
-            fn update_from_rhoT(&self, gs: &mut PyGasState) {
-                self.inner.update_from_rhoT(&mut gs.inner);
+            /// Update a GasState or FlowState from its density and temperature
+            fn update_from_rhoT(&self, state: &PyAny) -> PyResult<()> {
+                update_gas_state(state, |gs| self.inner.update_from_rhoT(gs))
             }
             
-            fn update_from_rhou(&self, gs: &mut PyGasState) {
-                self.inner.update_from_rhou(&mut gs.inner);
+            /// Update a GasState or FlowState from its density and energy
+            fn update_from_rhou(&self, state: &PyAny) -> PyResult<()> {
+                update_gas_state(state, |gs| self.inner.update_from_rhou(gs))
             }
 
-            fn update_from_rhop(&self, gs: &mut PyGasState) {
-                self.inner.update_from_rhop(&mut gs.inner);
+            /// Update a GasState or FlowState from its density and pressure
+            fn update_from_rhop(&self, state: &PyAny) -> PyResult<()> {
+                update_gas_state(state, |gs| self.inner.update_from_rhop(gs))
             }
 
             fn Cv(&self, gs: &PyGasState) -> Real {
@@ -39,7 +69,9 @@ macro_rules! create_gas_model_python_interface {
                 self.inner.Cp(&gs.inner)
             }
 
-            fn R(&self, gs: &PyGasState) -> Real {
+            // not called `R`, so gas models with a constant gas
+            // constant can have an `R` property
+            fn gas_constant(&self, gs: &PyGasState) -> Real {
                 self.inner.R(&gs.inner)
             }
         }
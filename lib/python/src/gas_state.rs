@@ -23,7 +23,8 @@ impl PyGasState {
     }
 
     fn __repr__(&self) -> String {
-        self.inner.to_string()
+        let gs = &self.inner;
+        format!("GasState(p={}, T={}, rho={}, u={}, a={})", gs.p, gs.T, gs.rho, gs.u, gs.a)
     }
     
     #[getter]
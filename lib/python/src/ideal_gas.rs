@@ -1,14 +1,61 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::types::PyType;
 use gas::ideal_gas::IdealGas;
 use crate::gas_model::create_gas_model_python_interface;
 use common::number::Real;
 
 create_gas_model_python_interface!(IdealGas, PyIdealGas, "IdealGas");
 
+/// The universal gas constant (J / mol / K)
+const R_UNIVERSAL: Real = 8.314462618;
+
 #[allow(non_snake_case)]
 #[pymethods]
 impl PyIdealGas {
     #[new]
-    fn new(R: Real, gamma: Real) -> PyIdealGas {
-        PyIdealGas{inner: IdealGas::new(R, gamma)}
+    fn new(R: Real, gamma: Real) -> PyResult<PyIdealGas> {
+        if R <= 0.0 {
+            return Err(PyValueError::new_err(
+                format!("The gas constant must be positive, but R = {}", R)
+            ));
+        }
+        if gamma <= 1.0 {
+            return Err(PyValueError::new_err(
+                format!("The ratio of specific heats must be greater than one, but gamma = {}", gamma)
+            ));
+        }
+        Ok(PyIdealGas{inner: IdealGas::new(R, gamma)})
+    }
+
+    /// Create an ideal gas from its molecular weight (kg / mol)
+    #[staticmethod]
+    fn from_molecular_weight(M: Real, gamma: Real) -> PyResult<PyIdealGas> {
+        if M <= 0.0 {
+            return Err(PyValueError::new_err(
+                format!("The molecular weight must be positive, but M = {}", M)
+            ));
+        }
+        PyIdealGas::new(R_UNIVERSAL / M, gamma)
+    }
+
+    /// Create an ideal gas with the properties of a common gas:
+    /// "air", "nitrogen", "oxygen", "argon", "helium" or "co2"
+    #[classmethod]
+    fn from_species_name(_cls: &PyType, species: &str) -> PyResult<PyIdealGas> {
+        IdealGas::from_species_name(species)
+            .map(|inner| PyIdealGas{inner})
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// The specific gas constant (J / kg / K)
+    #[getter]
+    fn get_R(&self) -> Real {
+        self.inner.gas_constant()
+    }
+
+    /// The ratio of specific heats
+    #[getter]
+    fn get_gamma(&self) -> Real {
+        self.inner.gamma()
     }
 }
@@ -1,20 +1,42 @@
 pub mod gas_state;
+pub mod flow_state;
 pub mod gas_model;
 pub mod ideal_gas;
 pub mod block;
+pub mod vector3;
+pub mod ref_dim;
+pub mod boundary_conditions;
+pub mod sim_settings;
 
 use pyo3::prelude::*;
 
-use crate::block::{PyBlock, PyBlockIO};
+use crate::block::{PyBlock, PyBlockCollection};
 use crate::gas_state::PyGasState;
+use crate::flow_state::PyFlowState;
 use crate::ideal_gas::PyIdealGas;
+use crate::vector3::PyVector3;
+use crate::ref_dim::PyRefDim;
+use crate::boundary_conditions::{
+    PyBoundaryConditionSpec, slip_wall, supersonic_inflow, supersonic_outflow, pressure_outflow
+};
+use crate::sim_settings::{PySimSettings, PySolverConfig};
 
 // python module
 #[pymodule]
 pub fn aeolus_py(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<PyGasState>()?;
+    m.add_class::<PyFlowState>()?;
     m.add_class::<PyIdealGas>()?;
     m.add_class::<PyBlock>()?;
-    m.add_class::<PyBlockIO>()?;
+    m.add_class::<PyBlockCollection>()?;
+    m.add_class::<PyVector3>()?;
+    m.add_class::<PyRefDim>()?;
+    m.add_class::<PyBoundaryConditionSpec>()?;
+    m.add_function(wrap_pyfunction!(slip_wall, m)?)?;
+    m.add_function(wrap_pyfunction!(supersonic_inflow, m)?)?;
+    m.add_function(wrap_pyfunction!(supersonic_outflow, m)?)?;
+    m.add_function(wrap_pyfunction!(pressure_outflow, m)?)?;
+    m.add_class::<PySimSettings>()?;
+    m.add_class::<PySolverConfig>()?;
     Ok(())
 }
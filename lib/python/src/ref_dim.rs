@@ -0,0 +1,112 @@
+use std::str::FromStr;
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+
+use common::number::Real;
+use common::unit::{RefDim, Unit, UnitNum};
+
+use crate::gas_state::PyGasState;
+
+/// The reference dimensions used to non-dimensionalise the simulation,
+/// computed from a list of (value, unit) pairs
+///
+/// >>> from aeolus_py import GasState, RefDim
+/// >>> ref_dim = RefDim([(6.0, 'm'), (1.0, 'm/s'), (1.2, 'kg/m^3')])
+/// >>> round(ref_dim.mass, 6), round(ref_dim.time, 6)
+/// (259.2, 6.0)
+/// >>> gs = GasState()
+/// >>> gs.p = 3.0
+/// >>> ref_dim.non_dimensionalise_gas_state(gs)
+/// >>> round(gs.p, 6)
+/// 2.5
+/// >>> ref_dim.dimensionalise_gas_state(gs)
+/// >>> round(gs.p, 6)
+/// 3.0
+#[pyclass(name="RefDim")]
+pub struct PyRefDim {
+    pub inner: RefDim,
+}
+
+#[pymethods]
+impl PyRefDim {
+    #[new]
+    fn new(reference_values: Vec<(Real, String)>) -> PyResult<PyRefDim> {
+        let mut unit_nums = Vec::with_capacity(reference_values.len());
+        for (value, unit) in reference_values.iter() {
+            if Unit::from_str(unit).is_err() {
+                return Err(PyValueError::new_err(format!("Unknown unit '{}'", unit)));
+            }
+            unit_nums.push(UnitNum::new(*value, unit));
+        }
+        Ok(PyRefDim{inner: RefDim::new(unit_nums)})
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RefDim(mass={}, length={}, time={}, temp={})",
+                self.inner.mass(), self.inner.length(), self.inner.time(), self.inner.temp())
+    }
+
+    #[getter]
+    fn mass(&self) -> Real {
+        self.inner.mass()
+    }
+
+    #[getter]
+    fn length(&self) -> Real {
+        self.inner.length()
+    }
+
+    #[getter]
+    fn time(&self) -> Real {
+        self.inner.time()
+    }
+
+    /// The reference temperature, which is zero if no reference
+    /// value with a temperature unit was given
+    #[getter]
+    fn temp(&self) -> Real {
+        self.inner.temp()
+    }
+
+    #[getter]
+    fn velocity(&self) -> Real {
+        self.inner.velocity()
+    }
+
+    #[getter]
+    fn density(&self) -> Real {
+        self.inner.density()
+    }
+
+    #[getter]
+    fn pressure(&self) -> Real {
+        self.inner.pressure()
+    }
+
+    /// Convert a gas state to non-dimensional values. The temperature
+    /// is left unchanged if there is no reference temperature.
+    fn non_dimensionalise_gas_state(&self, gs: &mut PyGasState) {
+        let gs = &mut gs.inner;
+        gs.p /= self.inner.pressure();
+        gs.rho /= self.inner.density();
+        gs.u /= self.inner.velocity().powi(2);
+        gs.a /= self.inner.velocity();
+        if self.inner.temp() > 0.0 {
+            gs.T /= self.inner.temp();
+        }
+    }
+
+    /// Convert a non-dimensional gas state back to dimensional values.
+    /// The temperature is left unchanged if there is no reference temperature.
+    fn dimensionalise_gas_state(&self, gs: &mut PyGasState) {
+        let gs = &mut gs.inner;
+        gs.p *= self.inner.pressure();
+        gs.rho *= self.inner.density();
+        gs.u *= self.inner.velocity().powi(2);
+        gs.a *= self.inner.velocity();
+        if self.inner.temp() > 0.0 {
+            gs.T *= self.inner.temp();
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyIOError, PyValueError};
+
+use aeolus::settings::{FileStructure, FluxScheme, ReconstructionOrder, SimSettings, SolverConfig, TimeIntegrator};
+use common::number::Real;
+use grid::block::GridFileType;
+
+use crate::boundary_conditions::PyBoundaryConditionSpec;
+use crate::ideal_gas::PyIdealGas;
+use crate::ref_dim::PyRefDim;
+
+/// Python facing wrapper of the simulation configuration
+///
+/// >>> from aeolus_py import SimSettings, slip_wall
+/// >>> settings = SimSettings()
+/// >>> settings.set_boundary_condition(slip_wall("wall"))
+/// >>> [bc.tag for bc in settings.boundary_conditions()]
+/// ['wall']
+#[pyclass(name="SimSettings", unsendable)]
+pub struct PySimSettings {
+    pub inner: SimSettings,
+}
+
+#[pymethods]
+impl PySimSettings {
+    #[new]
+    fn new() -> PySimSettings {
+        PySimSettings{inner: SimSettings::default()}
+    }
+
+    /// Set the boundary condition for a boundary, replacing any
+    /// boundary condition already set for the same tag
+    fn set_boundary_condition(&mut self, bc: &PyBoundaryConditionSpec) {
+        self.inner.set_boundary_condition(bc.inner.clone());
+    }
+
+    fn boundary_conditions(&self) -> Vec<PyBoundaryConditionSpec> {
+        self.inner
+            .boundary_conditions()
+            .iter()
+            .map(|bc| PyBoundaryConditionSpec{inner: bc.clone()})
+            .collect()
+    }
+
+    fn set_gas_model(&mut self, model: &PyIdealGas) {
+        self.inner.set_gas_model("ideal_gas", Box::new(model.inner.clone()));
+    }
+
+    fn set_reference_values(&mut self, ref_dim: &PyRefDim) {
+        self.inner.set_reference_dimensions(ref_dim.inner.clone());
+    }
+
+    /// Read a grid, and add it to the simulation
+    fn add_block(&mut self, path: &str) -> PyResult<()> {
+        self.inner
+            .add_block(&PathBuf::from(path))
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    fn set_solver_config(&mut self, config: &PySolverConfig) -> PyResult<()> {
+        config.inner
+            .validate()
+            .map_err(|_| PyValueError::new_err("Invalid solver configuration"))?;
+        self.inner.set_solver(config.inner.clone());
+        Ok(())
+    }
+
+    /// Write the configuration, gas model and grids to `output_dir`,
+    /// in the default layout of the simulation files
+    fn write(&self, output_dir: &str) -> PyResult<()> {
+        let file_structure = FileStructure::in_directory(Path::new(output_dir));
+        file_structure.create_directories();
+        self.inner
+            .write_config(&file_structure, GridFileType::Su2)
+            .map_err(|err| PyIOError::new_err(err.to_string()))
+    }
+}
+
+/// Python facing wrapper of the solver configuration. Anything
+/// not set takes the default value.
+///
+/// >>> from aeolus_py import SolverConfig
+/// >>> config = SolverConfig()
+/// >>> config.flux_scheme = "hanel"
+/// >>> config.cfl = 0.8
+/// >>> config.flux_scheme, config.cfl
+/// ('hanel', 0.8)
+#[pyclass(name="SolverConfig")]
+pub struct PySolverConfig {
+    pub inner: SolverConfig,
+}
+
+#[pymethods]
+impl PySolverConfig {
+    #[new]
+    fn new() -> PySolverConfig {
+        PySolverConfig{inner: SolverConfig::default()}
+    }
+
+    /// "forward_euler" or "runge_kutta3"
+    #[getter]
+    fn get_time_integrator(&self) -> &str {
+        match self.inner.time_integrator() {
+            TimeIntegrator::ForwardEuler => "forward_euler",
+            TimeIntegrator::RungeKutta3 => "runge_kutta3",
+        }
+    }
+
+    #[setter]
+    fn set_time_integrator(&mut self, time_integrator: &str) -> PyResult<()> {
+        let time_integrator = TimeIntegrator::from_str(time_integrator)
+            .map_err(|_| PyValueError::new_err(format!("Unknown time integrator '{}'", time_integrator)))?;
+        self.inner.set_time_integrator(time_integrator);
+        Ok(())
+    }
+
+    /// "hanel" or "ausmdv"
+    #[getter]
+    fn get_flux_scheme(&self) -> &str {
+        match self.inner.flux_scheme() {
+            FluxScheme::Hanel => "hanel",
+            FluxScheme::Ausmdv => "ausmdv",
+        }
+    }
+
+    #[setter]
+    fn set_flux_scheme(&mut self, flux_scheme: &str) -> PyResult<()> {
+        let flux_scheme = FluxScheme::from_str(flux_scheme)
+            .map_err(|_| PyValueError::new_err(format!("Unknown flux scheme '{}'", flux_scheme)))?;
+        self.inner.set_flux_scheme(flux_scheme);
+        Ok(())
+    }
+
+    /// "first" or "second"
+    #[getter]
+    fn get_reconstruction_order(&self) -> &str {
+        match self.inner.reconstruction_order() {
+            ReconstructionOrder::First => "first",
+            ReconstructionOrder::Second => "second",
+        }
+    }
+
+    #[setter]
+    fn set_reconstruction_order(&mut self, order: &str) -> PyResult<()> {
+        let order = ReconstructionOrder::from_str(order)
+            .map_err(|_| PyValueError::new_err(format!("Unknown reconstruction order '{}'", order)))?;
+        self.inner.set_reconstruction_order(order);
+        Ok(())
+    }
+
+    #[getter]
+    fn get_cfl(&self) -> Real {
+        self.inner.cfl()
+    }
+
+    #[setter]
+    fn set_cfl(&mut self, cfl: Real) {
+        self.inner.set_cfl(cfl);
+    }
+
+    #[getter]
+    fn get_max_iterations(&self) -> usize {
+        self.inner.max_iterations()
+    }
+
+    #[setter]
+    fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.inner.set_max_iterations(max_iterations);
+    }
+
+    #[getter]
+    fn get_convergence_tolerance(&self) -> Real {
+        self.inner.convergence_tolerance()
+    }
+
+    #[setter]
+    fn set_convergence_tolerance(&mut self, tolerance: Real) {
+        self.inner.set_convergence_tolerance(tolerance);
+    }
+
+    #[getter]
+    fn get_output_interval(&self) -> usize {
+        self.inner.output_interval()
+    }
+
+    #[setter]
+    fn set_output_interval(&mut self, output_interval: usize) {
+        self.inner.set_output_interval(output_interval);
+    }
+
+    /// The physical time to stop the simulation at, or None
+    /// to only stop after the maximum number of iterations
+    #[getter]
+    fn get_max_physical_time(&self) -> Option<Real> {
+        self.inner.max_physical_time()
+    }
+
+    #[setter]
+    fn set_max_physical_time(&mut self, max_physical_time: Real) {
+        self.inner.set_max_physical_time(Some(max_physical_time));
+    }
+
+    #[getter]
+    fn get_axisymmetric(&self) -> bool {
+        self.inner.is_axisymmetric()
+    }
+
+    #[setter]
+    fn set_axisymmetric(&mut self, axisymmetric: bool) {
+        self.inner.set_axisymmetric(axisymmetric);
+    }
+}
@@ -0,0 +1,103 @@
+use pyo3::prelude::*;
+
+use common::number::Real;
+use common::vector3::Vector3;
+
+/// Python facing wrapper of a Vector3
+#[pyclass(name="Vector3")]
+#[derive(Clone, Copy)]
+pub struct PyVector3 {
+    pub inner: Vector3,
+}
+
+impl From<Vector3> for PyVector3 {
+    fn from(inner: Vector3) -> PyVector3 {
+        PyVector3{inner}
+    }
+}
+
+#[pymethods]
+impl PyVector3 {
+    #[new]
+    fn new(x: Real, y: Real, z: Real) -> PyVector3 {
+        PyVector3{inner: Vector3{x, y, z}}
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Vector3({}, {}, {})", self.inner.x, self.inner.y, self.inner.z)
+    }
+
+    #[getter]
+    fn get_x(&self) -> Real {
+        self.inner.x
+    }
+
+    #[setter]
+    fn set_x(&mut self, val: Real) {
+        self.inner.x = val;
+    }
+
+    #[getter]
+    fn get_y(&self) -> Real {
+        self.inner.y
+    }
+
+    #[setter]
+    fn set_y(&mut self, val: Real) {
+        self.inner.y = val;
+    }
+
+    #[getter]
+    fn get_z(&self) -> Real {
+        self.inner.z
+    }
+
+    #[setter]
+    fn set_z(&mut self, val: Real) {
+        self.inner.z = val;
+    }
+
+    fn __add__(&self, other: &PyVector3) -> PyVector3 {
+        (&self.inner + &other.inner).into()
+    }
+
+    fn __sub__(&self, other: &PyVector3) -> PyVector3 {
+        (&self.inner - &other.inner).into()
+    }
+
+    /// Multiply by a scalar
+    fn __mul__(&self, factor: Real) -> PyVector3 {
+        let mut result = self.inner;
+        result.scale_in_place(factor);
+        result.into()
+    }
+
+    fn __rmul__(&self, factor: Real) -> PyVector3 {
+        self.__mul__(factor)
+    }
+
+    fn __neg__(&self) -> PyVector3 {
+        self.__mul__(-1.0)
+    }
+
+    fn length(&self) -> Real {
+        self.inner.length()
+    }
+
+    /// A unit vector in the same direction
+    fn normalised(&self) -> PyVector3 {
+        self.inner.normalised().into()
+    }
+
+    fn dot(&self, other: &PyVector3) -> Real {
+        self.inner.dot(&other.inner)
+    }
+
+    fn cross(&self, other: &PyVector3) -> PyVector3 {
+        self.inner.cross(&other.inner).into()
+    }
+
+    fn dist_to(&self, other: &PyVector3) -> Real {
+        self.inner.dist_to(&other.inner)
+    }
+}
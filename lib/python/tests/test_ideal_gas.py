@@ -0,0 +1,36 @@
+import unittest
+
+from aeolus_py import GasState, IdealGas
+
+
+class TestIdealGas(unittest.TestCase):
+    def test_properties(self):
+        gas = IdealGas(287.0, 1.4)
+        self.assertEqual(gas.R, 287.0)
+        self.assertIs(type(gas.R), float)
+        self.assertEqual(gas.gamma, 1.4)
+
+    def test_non_positive_gas_constant(self):
+        with self.assertRaisesRegex(ValueError, "gas constant"):
+            IdealGas(0.0, 1.4)
+
+    def test_gamma_not_greater_than_one(self):
+        with self.assertRaisesRegex(ValueError, "gamma"):
+            IdealGas(287.0, 1.0)
+
+    def test_from_molecular_weight(self):
+        air = IdealGas.from_molecular_weight(0.0289647, 1.4)
+        self.assertAlmostEqual(air.R, 287.055, places=3)
+        self.assertEqual(air.gamma, 1.4)
+
+    def test_from_non_positive_molecular_weight(self):
+        with self.assertRaisesRegex(ValueError, "molecular weight"):
+            IdealGas.from_molecular_weight(-1.0, 1.4)
+
+    def test_gas_constant_of_state(self):
+        gas = IdealGas(287.0, 1.4)
+        self.assertEqual(gas.gas_constant(GasState()), 287.0)
+
+
+if __name__ == "__main__":
+    unittest.main()
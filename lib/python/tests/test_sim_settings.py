@@ -0,0 +1,60 @@
+import os
+import tempfile
+import unittest
+
+try:
+    import tomllib
+except ModuleNotFoundError:
+    import tomli as tomllib
+
+from aeolus_py import IdealGas, RefDim, SimSettings, SolverConfig
+
+SQUARE = os.path.join(os.path.dirname(__file__), "../../../src/grid/tests/data/square.su2")
+
+
+class TestSimSettings(unittest.TestCase):
+    def test_write(self):
+        settings = SimSettings()
+        settings.set_gas_model(IdealGas(296.8, 1.4))
+        settings.set_reference_values(RefDim([(2.0, "m"), (10.0, "m/s"), (1.0, "kg/m^3")]))
+        settings.add_block(SQUARE)
+        config = SolverConfig()
+        config.flux_scheme = "hanel"
+        config.max_iterations = 500
+        settings.set_solver_config(config)
+
+        with tempfile.TemporaryDirectory() as output_dir:
+            settings.write(output_dir)
+
+            with open(os.path.join(output_dir, "config", "config.toml"), "rb") as f:
+                written = tomllib.load(f)
+            self.assertEqual(written["gas_model_type"], "ideal_gas")
+            self.assertEqual(written["solver"]["flux_scheme"], "hanel")
+            self.assertEqual(written["solver"]["max_iterations"], 500)
+            self.assertAlmostEqual(written["reference_dimensions"]["ref_length"], 2.0)
+
+            with open(os.path.join(output_dir, "config", "gas_model.toml"), "rb") as f:
+                gas_model = tomllib.load(f)
+            self.assertEqual(gas_model["R"], 296.8)
+
+            grid = os.path.join(output_dir, "solution", "grid", "t0000", "block_0000.su2")
+            self.assertTrue(os.path.isfile(grid))
+
+    def test_missing_block(self):
+        with self.assertRaises(ValueError):
+            SimSettings().add_block("missing.su2")
+
+    def test_invalid_solver_config(self):
+        config = SolverConfig()
+        config.cfl = 10.0
+        with self.assertRaisesRegex(ValueError, "solver configuration"):
+            SimSettings().set_solver_config(config)
+
+    def test_unknown_flux_scheme(self):
+        config = SolverConfig()
+        with self.assertRaisesRegex(ValueError, "flux scheme"):
+            config.flux_scheme = "roe"
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,134 @@
+use std::path::Path;
+
+use grid::Block;
+
+use crate::logging::{Logger, UserLogger};
+use crate::prep::read_sim_settings;
+use crate::settings::{AeolusSettings, SimSettings};
+
+/// The result of checking a simulation, ordered from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckOutcome {
+    Valid, Warnings, Errors,
+}
+
+impl CheckOutcome {
+    pub fn exit_code(&self) -> i32 {
+        match &self {
+            CheckOutcome::Valid => 0,
+            CheckOutcome::Warnings => 1,
+            CheckOutcome::Errors => 2,
+        }
+    }
+}
+
+/// Check the program settings, the simulation configuration and
+/// the grids, reporting any problems found. If there are no errors,
+/// a summary of the simulation is printed.
+pub fn check_sim(prep_file: &Path, settings: &AeolusSettings, log: &UserLogger) -> CheckOutcome {
+    let mut outcome = CheckOutcome::Valid;
+
+    if let Err(errors) = settings.validate() {
+        for error in errors.iter() {
+            log.error(&error.to_string());
+        }
+        outcome = CheckOutcome::Errors;
+    }
+
+    let sim_settings = match read_sim_settings(prep_file) {
+        Ok(sim_settings) => sim_settings,
+        Err(err) => {
+            log.error(&err.to_string());
+            return CheckOutcome::Errors;
+        }
+    };
+
+    for block in sim_settings.grids().blocks().iter() {
+        if let Err(errors) = block.validate() {
+            for error in errors.iter() {
+                let message = format!("block {}: {}", block.id(), error);
+                if error.is_warning() {
+                    log.warning(&message);
+                    outcome = outcome.max(CheckOutcome::Warnings);
+                }
+                else {
+                    log.error(&message);
+                    outcome = CheckOutcome::Errors;
+                }
+            }
+        }
+    }
+
+    if outcome != CheckOutcome::Errors {
+        print_summary(&sim_settings);
+    }
+    outcome
+}
+
+fn print_summary(sim_settings: &SimSettings) {
+    println!("Gas model: {}", sim_settings.gas_model_type());
+    let ref_dim = sim_settings.reference_dimensions();
+    println!("Reference dimensions: mass = {} kg, length = {} m, time = {} s, temperature = {} K",
+             ref_dim.mass(), ref_dim.length(), ref_dim.time(), ref_dim.temp());
+    for block in sim_settings.grids().blocks().iter() {
+        let tags = block.boundary_tags();
+        println!("Block {}: {} cells, boundaries: {}", block.id(), block.cells().len(), tags.join(", "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::*;
+    use crate::settings::Verbosity;
+
+    fn check_prep_file(name: &str, contents: &str) -> CheckOutcome {
+        let settings = AeolusSettings::from_toml_file(
+            Path::new("../../resources/defaults/aeolus_defaults.toml")
+        ).unwrap();
+        let log = UserLogger::with_verbosity(&Verbosity::Error);
+        let prep_file = env::temp_dir().join(name);
+        fs::write(&prep_file, contents).unwrap();
+        let outcome = check_sim(&prep_file, &settings, &log);
+        fs::remove_file(&prep_file).unwrap();
+        outcome
+    }
+
+    fn prep_file_contents(grid: &str) -> String {
+        format!(r#"
+            config.reference_values = RefDim(
+                UnitNum(1000., "m/s"),
+                UnitNum(1., "kg/m^3"),
+                UnitNum(1., "m")
+            )
+            blks = blocks()
+            blks:add_block("{}/../grid/tests/data/{}")
+            config.blocks = blks
+            config.gas_model_type = "ideal_gas"
+            config.gas_model = IdealGas(287, 1.4)
+        "#, env!("CARGO_MANIFEST_DIR"), grid)
+    }
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(CheckOutcome::Valid.exit_code(), 0);
+        assert_eq!(CheckOutcome::Warnings.exit_code(), 1);
+        assert_eq!(CheckOutcome::Errors.exit_code(), 2);
+    }
+
+    #[test]
+    fn check_valid() {
+        let outcome = check_prep_file("check_valid.aeolus", &prep_file_contents("square.su2"));
+
+        assert_eq!(outcome, CheckOutcome::Valid);
+    }
+
+    #[test]
+    fn check_missing_grid() {
+        let outcome = check_prep_file("check_missing_grid.aeolus", &prep_file_contents("missing.su2"));
+
+        assert_eq!(outcome, CheckOutcome::Errors);
+    }
+}
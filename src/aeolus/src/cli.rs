@@ -23,6 +23,25 @@ pub enum Commands {
         prep_file: PathBuf
     },
 
+    /// Check the simulation configuration and grids, without
+    /// writing any files
+    #[command(arg_required_else_help = true)]
+    Check {
+        /// The file defining the simulation
+        prep_file: PathBuf
+    },
+
+    /// Convert a grid to a different format. The formats are
+    /// determined from the file extensions
+    #[command(arg_required_else_help = true)]
+    Convert {
+        /// The grid to convert
+        input: PathBuf,
+
+        /// Where to write the converted grid
+        output: PathBuf,
+    },
+
     /// Run a simulation
     Run {
         start_time_index: Option<usize>
@@ -31,6 +50,24 @@ pub enum Commands {
     /// Post process a simulation
     Post,
 
+    /// Report the progress of a simulation, which may still be running
+    Status,
+
     /// Clean the simulation files
     Clean,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_verbosity() {
+        for (arg, verbosity) in [("error", Verbosity::Error), ("warning", Verbosity::Warning),
+                                 ("info", Verbosity::Info), ("debug", Verbosity::Debug)] {
+            let cli = Cli::try_parse_from(["aeolus", "--verbosity", arg, "post"]).unwrap();
+            assert_eq!(cli.verbosity, Some(verbosity));
+        }
+        assert!(Cli::try_parse_from(["aeolus", "--verbosity", "loud", "post"]).is_err());
+    }
+}
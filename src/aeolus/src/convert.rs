@@ -0,0 +1,18 @@
+use std::path::Path;
+
+use common::DynamicResult;
+use grid::block::{BlockCollection, GridFileType, write_block};
+
+/// Convert a grid from one format to another. The formats
+/// are determined from the file extensions.
+pub fn convert_grid(input: &Path, output: &Path) -> DynamicResult<()> {
+    // check both formats before doing any work, so we
+    // don't read a large grid only to fail at the end
+    GridFileType::from_file_name(input)?;
+    GridFileType::from_file_name(output)?;
+
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(input)?;
+    write_block(block_collection.get_block(0), output)?;
+    Ok(())
+}
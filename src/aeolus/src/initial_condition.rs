@@ -0,0 +1,167 @@
+use rlua::{Function, Table, Value};
+
+use common::DynamicResult;
+use common::number::Real;
+use common::vector3::Vector3;
+use gas::flow_state::FlowState;
+use gas::gas_model::GasModel;
+use gas::gas_state::GasState;
+use finite_volume::flow::FlowStates;
+use finite_volume::fluid_block::FluidBlock;
+
+use crate::settings::InvalidConfig;
+
+/// How the flow states in the cells are set before the first iteration
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitialConditionSpec {
+    /// The same flow state in every cell
+    Uniform(FlowState<Real>),
+
+    /// The body of a lua function of the cell centre `x`, `y` and `z`,
+    /// returning a table of `p`, `T` and optionally `vel_x`, `vel_y`
+    /// and `vel_z`, e.g. `return {p = 101325, T = 300 + 10 * x}`
+    Lua(String),
+}
+
+impl InitialConditionSpec {
+    /// Read the initial condition from a lua table. A `type = "uniform"`
+    /// table has the fields of the flow state alongside the type, and a
+    /// `type = "lua"` table has the body of the function as `source`.
+    pub fn from_lua_table(table: Table) -> Result<InitialConditionSpec, InvalidConfig> {
+        let ic_type = table.get::<_, String>("type").map_err(|_| InvalidConfig)?;
+        let allowable_names: &[&str] = match ic_type.as_str() {
+            "uniform" => &["type", "p", "T", "vel_x", "vel_y", "vel_z"],
+            "lua" => &["type", "source"],
+            _ => return Err(InvalidConfig),
+        };
+        for pair in table.clone().pairs::<String, Value>() {
+            let (key, _) = pair.map_err(|_| InvalidConfig)?;
+            if !allowable_names.contains(&key.as_str()) {
+                return Err(InvalidConfig);
+            }
+        }
+
+        match ic_type.as_str() {
+            "uniform" => Ok(InitialConditionSpec::Uniform(flow_state_from_lua_table(&table)?)),
+            _ => {
+                let source = table.get::<_, String>("source").map_err(|_| InvalidConfig)?;
+                Ok(InitialConditionSpec::Lua(source))
+            }
+        }
+    }
+
+    /// Set the flow state in each cell of `fluid_block`. The gas model fills
+    /// in the thermodynamic properties of the gas from the pressure and
+    /// temperature. The conserved variables need to be encoded again afterwards.
+    pub fn apply(&self, fluid_block: &mut FluidBlock, gm: &dyn GasModel<Real>) -> DynamicResult<()> {
+        match self {
+            InitialConditionSpec::Uniform(flow_state) => {
+                fluid_block.set_initial_condition(|_, _, _| *flow_state, gm)?;
+            }
+            InitialConditionSpec::Lua(source) => {
+                // each block gets a fresh lua state, so the function
+                // can't depend on anything set by the prep script
+                let lua = rlua::Lua::new();
+                let flow_states = lua.context(|lua_ctx| -> DynamicResult<FlowStates> {
+                    let ic: Function = lua_ctx
+                        .load(&format!("return function(x, y, z)\n{}\nend", source))
+                        .eval()
+                        .map_err(|err| format!("Invalid initial condition function: {}", err))?;
+                    let centres = fluid_block.cells().centres();
+                    let mut flow_states = FlowStates::with_capacity(fluid_block.cells().len());
+                    for i in 0 .. fluid_block.cells().len() {
+                        let (x, y, z) = (centres.x[i], centres.y[i], centres.z[i]);
+                        let table: Table = ic
+                            .call((x, y, z))
+                            .map_err(|err| format!("Failed to evaluate the initial condition at ({}, {}, {}): {}", x, y, z, err))?;
+                        let mut flow_state = flow_state_from_lua_table(&table)?;
+                        flow_state.gas_state().validate()?;
+                        gm.update_from_pT(flow_state.gas_state_mut())?;
+                        flow_states.push(&flow_state);
+                    }
+                    Ok(flow_states)
+                })?;
+                fluid_block.set_flow_states(flow_states);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A flow state from the pressure, temperature and velocity
+/// components in a lua table. The velocity defaults to zero.
+fn flow_state_from_lua_table(table: &Table) -> Result<FlowState<Real>, InvalidConfig> {
+    let get = |key: &str| table.get::<_, Real>(key).map_err(|_| InvalidConfig);
+    let get_or_zero = |key: &str| table.get::<_, Option<Real>>(key)
+        .map(|value| value.unwrap_or(0.0))
+        .map_err(|_| InvalidConfig);
+    let gas_state = GasState{p: get("p")?, T: get("T")?, ..GasState::default()};
+    let velocity = Vector3{x: get_or_zero("vel_x")?, y: get_or_zero("vel_y")?, z: get_or_zero("vel_z")?};
+    Ok(FlowState::new(gas_state, velocity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use gas::ideal_gas::IdealGas;
+    use grid::block::BlockCollection;
+
+    fn read_initial_condition(script: &str) -> Result<InitialConditionSpec, InvalidConfig> {
+        let lua = rlua::Lua::new();
+        lua.context(|lua_ctx| {
+            let table = lua_ctx.load(script).eval::<Table>().unwrap();
+            InitialConditionSpec::from_lua_table(table)
+        })
+    }
+
+    fn square_fluid_block() -> FluidBlock {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(Path::new("../grid/tests/data/square.su2")).unwrap();
+        FluidBlock::from_grid_block(&block_collection.blocks()[0], &[], &IdealGas::new(287.0, 1.4)).unwrap()
+    }
+
+    #[test]
+    fn uniform_from_lua() {
+        let ic = read_initial_condition("{type = 'uniform', p = 101325, T = 300, vel_x = 500}").unwrap();
+        let gas_state = GasState{p: 101325.0, T: 300.0, ..GasState::default()};
+        let velocity = Vector3{x: 500.0, y: 0.0, z: 0.0};
+
+        assert_eq!(ic, InitialConditionSpec::Uniform(FlowState::new(gas_state, velocity)));
+    }
+
+    #[test]
+    fn invalid_from_lua() {
+        assert_eq!(read_initial_condition("{type = 'uniform', T = 300}"), Err(InvalidConfig));
+        assert_eq!(read_initial_condition("{type = 'uniform', p = 1e5, T = 300, rho = 1}"), Err(InvalidConfig));
+        assert_eq!(read_initial_condition("{type = 'lua'}"), Err(InvalidConfig));
+        assert_eq!(read_initial_condition("{type = 'file', source = 'flow.csv'}"), Err(InvalidConfig));
+    }
+
+    #[test]
+    fn apply_lua() {
+        let gm = IdealGas::new(287.0, 1.4);
+        let mut fluid_block = square_fluid_block();
+        let ic = read_initial_condition("{type = 'lua', source = 'return {p = 1e5, T = 300 + x, vel_y = y}'}").unwrap();
+        ic.apply(&mut fluid_block, &gm).unwrap();
+
+        let centres = fluid_block.cells().centres();
+        let flow_states = fluid_block.cells().flow_states();
+        for i in 0 .. fluid_block.cells().len() {
+            assert_eq!(flow_states.p[i], 1e5);
+            assert!((flow_states.t[i] - (300.0 + centres.x[i])).abs() < 1e-12);
+            assert!((flow_states.vel_y[i] - centres.y[i]).abs() < 1e-12);
+            assert!((flow_states.rho[i] - 1e5 / (287.0 * flow_states.t[i])).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn apply_invalid_lua() {
+        let gm = IdealGas::new(287.0, 1.4);
+        let mut fluid_block = square_fluid_block();
+
+        assert!(InitialConditionSpec::Lua("return {p = -1, T = 300}".to_string()).apply(&mut fluid_block, &gm).is_err());
+        assert!(InitialConditionSpec::Lua("return 1".to_string()).apply(&mut fluid_block, &gm).is_err());
+        assert!(InitialConditionSpec::Lua("return {p = ".to_string()).apply(&mut fluid_block, &gm).is_err());
+    }
+}
@@ -1,6 +1,14 @@
 
 pub mod cli;
 pub mod settings;
+pub mod initial_condition;
 pub mod prep;
+pub mod check;
+pub mod convert;
+pub mod post;
+pub mod status;
+pub mod solver;
 pub mod lua;
 pub mod logging;
+pub mod progress;
+pub mod timing;
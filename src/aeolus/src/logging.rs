@@ -1,36 +1,92 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use common::DynamicResult;
+
 use crate::settings::Verbosity;
 
 pub trait Logger{
     fn error(&self, message: &str);
     fn warning(&self, message: &str);
+    fn info(&self, message: &str);
     fn debug(&self, message: &str);
+
+    /// The same as [`Logger::warning`]
+    fn warn(&self, message: &str) {
+        self.warning(message);
+    }
 }
 
 pub struct UserLogger {
     verbosity: Verbosity,
+
+    /// A file every message is also written to
+    file: Option<Mutex<BufWriter<File>>>,
 }
 
 impl UserLogger {
     pub fn with_verbosity(verbosity: &Verbosity) -> UserLogger {
-        UserLogger { verbosity: verbosity.clone() }
+        UserLogger { verbosity: verbosity.clone(), file: None }
+    }
+
+    /// Log to the terminal, and also to the file at `path`. The file is
+    /// appended to, so a restarted simulation extends the same log.
+    pub fn with_file(verbosity: &Verbosity, path: &Path) -> DynamicResult<UserLogger> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|err| format!("Unable to open log file {}: {}", path.display(), err))?;
+        Ok(UserLogger { verbosity: verbosity.clone(), file: Some(Mutex::new(BufWriter::new(file))) })
+    }
+
+    /// Make sure everything logged so far has been written to the log file
+    pub fn flush(&self) -> std::io::Result<()> {
+        match &self.file {
+            Some(file) => file.lock().unwrap().flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn write_to_file(&self, message: &str) {
+        if let Some(file) = &self.file {
+            // failing to write the log isn't worth stopping the simulation for
+            let _ = writeln!(file.lock().unwrap(), "{}", message);
+        }
     }
 }
 
 impl Logger for UserLogger {
     fn error(&self, message: &str) {
         match self.verbosity {
-            Verbosity::Error | Verbosity::Warning | Verbosity::Debug => {
+            Verbosity::Error | Verbosity::Warning | Verbosity::Info | Verbosity::Debug => {
                 eprint!("Error: ");
                 eprintln!("{}", message);
+                self.write_to_file(&format!("Error: {}", message));
             }
         }
     }
 
     fn warning(&self, message: &str) {
         match self.verbosity {
-            Verbosity::Warning | Verbosity::Debug => {
+            Verbosity::Warning | Verbosity::Info | Verbosity::Debug => {
                 print!("Warning: ");
                 println!("{}", message);
+                self.write_to_file(&format!("Warning: {}", message));
+            }
+            Verbosity::Error => {}
+        }
+    }
+
+    /// Progress of the simulation, which is printed at `Warning` verbosity
+    /// and above, without the detail of `Debug`
+    fn info(&self, message: &str) {
+        match self.verbosity {
+            Verbosity::Warning | Verbosity::Info | Verbosity::Debug => {
+                println!("{}", message);
+                self.write_to_file(message);
             }
             Verbosity::Error => {}
         }
@@ -40,8 +96,53 @@ impl Logger for UserLogger {
         match self.verbosity {
             Verbosity::Debug => {
                 println!("{}", message);
+                self.write_to_file(message);
             }
-            Verbosity::Warning | Verbosity::Error => {}
+            Verbosity::Info | Verbosity::Warning | Verbosity::Error => {}
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn log_to_file() {
+        let path = std::env::temp_dir().join("aeolus_log_to_file.log");
+        let _ = fs::remove_file(&path);
+
+        let log = UserLogger::with_file(&Verbosity::Info, &path).unwrap();
+        log.info("first run");
+        log.debug("not shown at info");
+        log.flush().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first run\n");
+
+        // a restart appends to the same log
+        let log = UserLogger::with_file(&Verbosity::Warning, &path).unwrap();
+        log.warning("restarted");
+        log.info("shown at warning");
+        log.debug("not shown at warning");
+        log.error("failed");
+        log.flush().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "first run\nWarning: restarted\nshown at warning\nError: failed\n"
+        );
+
+        // but not at error
+        let log = UserLogger::with_file(&Verbosity::Error, &path).unwrap();
+        log.info("not shown at error");
+        log.flush().unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("not shown"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn log_file_in_missing_directory() {
+        let path = std::env::temp_dir().join("aeolus_missing_log_dir").join("aeolus.log");
+
+        assert!(UserLogger::with_file(&Verbosity::Info, &path).is_err());
+    }
+}
@@ -4,6 +4,8 @@ use common::number::Real;
 use grid::block::BlockCollection;
 use gas::ideal_gas::IdealGas;
 
+use crate::settings::SolverConfig;
+
 pub fn create_lua_state() -> Lua {
     let lua = Lua::new();
     lua.context(|lua_ctx| {
@@ -30,6 +32,12 @@ pub fn create_lua_state() -> Lua {
         }).unwrap();
         globals.set("IdealGas", ideal_gas).unwrap();
 
+        // solver settings, starting from the defaults
+        let solver_config = lua_ctx.create_function(|_, ()| {
+            Ok(SolverConfig::default())
+        }).unwrap();
+        globals.set("SolverConfig", solver_config).unwrap();
+
         // Block input
         let block_collection = lua_ctx.create_function(|_,()| {
             Ok(BlockCollection::new())
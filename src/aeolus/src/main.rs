@@ -1,10 +1,15 @@
 
 use aeolus::cli::{Cli,Commands};
-use aeolus::logging::UserLogger;
+use aeolus::logging::{Logger, UserLogger};
 use clap::Parser;
 
 use aeolus::settings::AeolusSettings;
 use aeolus::prep::prep_sim;
+use aeolus::check::check_sim;
+use aeolus::convert::convert_grid;
+use aeolus::post::post_process;
+use aeolus::status::SimulationStatus;
+use aeolus::solver::run_sim;
 use common::DynamicResult;
 
 fn main() -> DynamicResult<()> {
@@ -18,12 +23,39 @@ fn main() -> DynamicResult<()> {
     // perform the sub-command requested by the user
     match args.command {
         Commands::Prep{mut prep_file} => {
+            if let Err(errors) = settings.validate() {
+                for error in errors.iter() {
+                    log.error(&error.to_string());
+                }
+                return Err("Invalid configuration".into());
+            }
             prep_sim(&mut prep_file, &settings)?;
         }
+        Commands::Check{prep_file} => {
+            let outcome = check_sim(&prep_file, &settings, &log);
+            std::process::exit(outcome.exit_code());
+        }
+        Commands::Convert{input, output} => {
+            convert_grid(&input, &output)?;
+        }
         Commands::Run{start_time_index: _} => {
-            println!("Running the simulation");
+            std::fs::create_dir_all(settings.file_structure().fluid())?;
+            let log = UserLogger::with_file(settings.verbosity(), &settings.file_structure().log())?;
+            log.info("Running the simulation");
+            let result = run_sim(&settings, &log);
+            if let Err(err) = &result {
+                log.error(&err.to_string());
+            }
+            log.flush()?;
+            result?;
+        }
+        Commands::Post => {
+            let time_index = post_process(settings.file_structure())?;
+            log.debug(&format!("post processed {}", settings.file_structure().time_directory(time_index).display()));
+        }
+        Commands::Status => {
+            SimulationStatus::read(settings.file_structure())?.print();
         }
-        Commands::Post => {}
         Commands::Clean => { settings.file_structure().clean(&log)?; }
     }
     Ok(())
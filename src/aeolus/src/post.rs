@@ -0,0 +1,105 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use common::DynamicResult;
+use common::number::Real;
+use finite_volume::flow::FlowStates;
+use gas::gas_model::GasModel;
+use gas::gas_state::GasState;
+use grid::block::{block_file_name, BlockCollection, GridFileType};
+use grid::Block;
+
+use crate::settings::{FileStructure, SimSettings};
+
+/// Names of the quantities computed for each cell
+const QUANTITIES: [&str; 5] = ["p", "rho", "T", "|v|", "M"];
+
+/// Write a CSV file of cell quantities for each block at the latest
+/// time, along with a summary of each quantity in each block.
+/// The files are written to the time directory. Returns the time index
+/// that was post-processed.
+pub fn post_process(file_structure: &FileStructure) -> DynamicResult<usize> {
+    let sim_settings = SimSettings::read_config(file_structure)?;
+    let time_index = file_structure.latest_time_index()?;
+    let time_dir = file_structure.time_directory(time_index);
+
+    let summary_file = File::create(time_dir.join("summary.csv"))?;
+    let mut summary = BufWriter::new(summary_file);
+    writeln!(summary, "block,quantity,min,max,mean")?;
+
+    let mut block_id = 0;
+    loop {
+        let grid_path = time_dir.join(block_file_name(block_id, GridFileType::Native.extension()));
+        if !grid_path.exists() {
+            break;
+        }
+        let csv_path = time_dir.join(block_file_name(block_id, "csv"));
+        let quantities = post_process_block(&grid_path, &csv_path, sim_settings.gas_model())?;
+        for (name, values) in QUANTITIES.iter().zip(quantities.iter()) {
+            let (min, max, mean) = statistics(values);
+            writeln!(summary, "{},{},{},{},{}", block_id, name, min, max, mean)?;
+        }
+        block_id += 1;
+    }
+    summary.flush()?;
+
+    if block_id == 0 {
+        return Err(format!("No fluid blocks in {}", time_dir.display()).into());
+    }
+    Ok(time_index)
+}
+
+/// Write the cell quantities for a single block, returning the
+/// value of each quantity (in the order of [`QUANTITIES`]) in each cell
+fn post_process_block(grid_path: &Path, csv_path: &Path, gas_model: &dyn GasModel<Real>) -> DynamicResult<Vec<Vec<Real>>> {
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(grid_path)?;
+    let block = block_collection.get_block(0);
+    let n_cells = block.cells().len();
+
+    let flow_file = File::open(grid_path.with_extension("flow"))?;
+    let flow_states = FlowStates::read_binary(&mut BufReader::new(flow_file), n_cells)?;
+
+    let mut csv = BufWriter::new(File::create(csv_path)?);
+    writeln!(csv, "cell_id,x,y,z,{}", QUANTITIES.join(","))?;
+    let mut quantities = vec![Vec::with_capacity(n_cells); QUANTITIES.len()];
+    for (i, (cell, flow_state)) in block.cells().iter().zip(flow_states.iter()).enumerate() {
+        let mut gas_state = GasState{p: flow_state.p(), T: flow_state.T(), ..GasState::default()};
+        gas_model.update_from_pT(&mut gas_state)?;
+        let vel = flow_state.velocity().length();
+        let cell_quantities = [flow_state.p(), flow_state.rho(), flow_state.T(), vel, vel / gas_state.a];
+
+        let centre = cell.centre();
+        write!(csv, "{},{},{},{}", i, centre.x, centre.y, centre.z)?;
+        for (value, values) in cell_quantities.iter().zip(quantities.iter_mut()) {
+            write!(csv, ",{}", value)?;
+            values.push(*value);
+        }
+        writeln!(csv)?;
+    }
+    csv.flush()?;
+    Ok(quantities)
+}
+
+/// The minimum, maximum and mean of some values
+fn statistics(values: &[Real]) -> (Real, Real, Real) {
+    let min = values.iter().cloned().fold(Real::INFINITY, Real::min);
+    let max = values.iter().cloned().fold(Real::NEG_INFINITY, Real::max);
+    let mean = values.iter().sum::<Real>() / values.len() as Real;
+    (min, max, mean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statistics_of_values() {
+        let (min, max, mean) = statistics(&[2.0, -1.0, 5.0, 2.0]);
+
+        assert_eq!(min, -1.0);
+        assert_eq!(max, 5.0);
+        assert_eq!(mean, 2.0);
+    }
+}
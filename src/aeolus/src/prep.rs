@@ -1,14 +1,104 @@
-use std::path::PathBuf;
-use std::fs::read_to_string;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::fs::{self, read_to_string};
 
 use rlua::Table;
+use serde_derive::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
 
 use common::DynamicResult;
-use crate::settings::{AeolusSettings, SimSettings};
+use crate::settings::{AeolusSettings, FileStructure, SimSettings};
 use crate::lua::create_lua_state;
 
+/// The name of the copy of the simulation's lua script, written
+/// in the same directory as the config
+pub const PREP_INPUT: &str = "prep_input.lua";
+
+/// The name of the manifest, written in the same directory as the config
+pub const MANIFEST: &str = "manifest.toml";
+
 pub fn prep_sim(sim: &mut PathBuf, settings: &AeolusSettings) -> DynamicResult<()> {
-    settings.file_structure().create_directories();
+    let file_structure = settings.file_structure();
+    file_structure.create_directories();
+    let sim_settings = read_sim_settings(sim)?;
+    sim_settings.write_config(file_structure, settings.native_grid_format())?;
+
+    let config_dir = config_directory(file_structure);
+    fs::copy(sim, config_dir.join(PREP_INPUT))?;
+    Manifest::new(file_structure)?.write(&config_dir.join(MANIFEST))?;
+
+    Ok(())
+}
+
+/// The directory the config is written to
+pub(crate) fn config_directory(file_structure: &FileStructure) -> PathBuf {
+    file_structure.config()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// The version of aeolus which prepared a simulation, and the
+/// SHA-256 hash of every file written, so the run can be reproduced
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Manifest {
+    pub aeolus_version: String,
+
+    /// The path of each file, and its hash as a hex string
+    pub files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Hash the files written by prepping the simulation
+    pub fn new(file_structure: &FileStructure) -> DynamicResult<Manifest> {
+        let mut paths = vec![
+            file_structure.config().to_path_buf(),
+            file_structure.gas_model().to_path_buf(),
+            config_directory(file_structure).join(PREP_INPUT),
+        ];
+        add_files_in(file_structure.grid(), &mut paths)?;
+
+        let mut files = BTreeMap::new();
+        for path in paths {
+            let hash = sha256_file(&path)?;
+            files.insert(path.display().to_string(), hash);
+        }
+        Ok(Manifest{aeolus_version: env!("CARGO_PKG_VERSION").to_string(), files})
+    }
+
+    pub fn read(path: &Path) -> DynamicResult<Manifest> {
+        Ok(toml::from_str(&read_to_string(path)?)?)
+    }
+
+    pub fn write(&self, path: &Path) -> DynamicResult<()> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Add the paths of all the files in a directory and its sub-directories
+fn add_files_in(dir: &Path, paths: &mut Vec<PathBuf>) -> DynamicResult<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            add_files_in(&path, paths)?;
+        }
+        else {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The SHA-256 hash of a file, as a hex string
+pub fn sha256_file(path: &Path) -> DynamicResult<String> {
+    let hash = Sha256::digest(fs::read(path)?);
+    Ok(hash.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Run the lua script defining the simulation, and
+/// read the simulation configuration it sets
+pub fn read_sim_settings(sim: &Path) -> DynamicResult<SimSettings> {
     let mut sim_settings = SimSettings::default();
     let lua_file = read_to_string(sim)?;
     // set up simulation configuration from the lua script
@@ -22,12 +112,10 @@ pub fn prep_sim(sim: &mut PathBuf, settings: &AeolusSettings) -> DynamicResult<(
 
         // get the config table
         let config = globals.get::<_, Table>("config").unwrap();
-        sim_settings = SimSettings::from_lua_table(config).unwrap();
+        sim_settings = SimSettings::from_lua_table(config, lua_ctx)?;
 
         Ok(())
     })?;
 
-    sim_settings.write_config(settings.file_structure())?; 
-
-    Ok(())
+    Ok(sim_settings)
 }
@@ -0,0 +1,101 @@
+use std::io::{self, Write};
+
+use common::number::Real;
+
+use crate::settings::Verbosity;
+
+/// The number of characters inside the progress bar
+const BAR_WIDTH: usize = 20;
+
+/// Reports the progress of the solver to the terminal, overwriting
+/// the same line each update
+pub struct SimulationProgress {
+    total_iterations: usize,
+    enabled: bool,
+}
+
+impl SimulationProgress {
+    /// Progress is only shown for `Warning`, `Info` and `Debug` verbosity
+    pub fn new(total_iterations: usize, verbosity: &Verbosity) -> SimulationProgress {
+        let enabled = match verbosity {
+            Verbosity::Warning | Verbosity::Info | Verbosity::Debug => true,
+            Verbosity::Error => false,
+        };
+        SimulationProgress { total_iterations, enabled }
+    }
+
+    /// Show the progress after `iteration` steps
+    pub fn update(&self, iteration: usize, residual_l2: Real) {
+        if !self.enabled {
+            return;
+        }
+        let mut stdout = io::stdout().lock();
+        // failing to show progress isn't worth stopping the simulation for
+        let _ = write!(stdout, "\r{}", self.render(iteration, residual_l2));
+        let _ = stdout.flush();
+    }
+
+    /// Erase the progress bar, so a message can be printed in its
+    /// place. It's shown again by the next update.
+    pub fn clear(&self) {
+        if !self.enabled {
+            return;
+        }
+        let mut stdout = io::stdout().lock();
+        let _ = write!(stdout, "\r\x1b[2K");
+        let _ = stdout.flush();
+    }
+
+    /// Move off the line with the progress bar, so further
+    /// output doesn't overwrite it
+    pub fn finish(&self) {
+        if self.enabled {
+            println!();
+        }
+    }
+
+    fn render(&self, iteration: usize, residual_l2: Real) -> String {
+        let fraction = match self.total_iterations {
+            0 => 1.0,
+            total => (iteration.min(total) as Real) / (total as Real),
+        };
+        let filled = (fraction * BAR_WIDTH as Real) as usize;
+        format!("[{}{}] {}/{}  iter  res={:.2e}",
+                "=".repeat(filled), " ".repeat(BAR_WIDTH - filled),
+                iteration, self.total_iterations, residual_l2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_half_way() {
+        let progress = SimulationProgress::new(1000, &Verbosity::Warning);
+
+        assert_eq!(progress.render(500, 1.23e-6), "[==========          ] 500/1000  iter  res=1.23e-6");
+    }
+
+    #[test]
+    fn render_complete() {
+        let progress = SimulationProgress::new(10, &Verbosity::Debug);
+
+        assert_eq!(progress.render(10, 0.5), "[====================] 10/10  iter  res=5.00e-1");
+    }
+
+    #[test]
+    fn render_past_end() {
+        let progress = SimulationProgress::new(10, &Verbosity::Debug);
+
+        assert_eq!(progress.render(12, 0.5), "[====================] 12/10  iter  res=5.00e-1");
+    }
+
+    #[test]
+    fn suppressed_when_only_showing_errors() {
+        assert!(!SimulationProgress::new(10, &Verbosity::Error).enabled);
+        assert!(SimulationProgress::new(10, &Verbosity::Warning).enabled);
+        assert!(SimulationProgress::new(10, &Verbosity::Info).enabled);
+        assert!(SimulationProgress::new(10, &Verbosity::Debug).enabled);
+    }
+}
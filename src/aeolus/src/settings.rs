@@ -1,50 +1,94 @@
 use core::fmt;
-use std::path::{PathBuf, Path};
+use std::path::{Component, PathBuf, Path};
 use std::env;
+use std::ffi::OsStr;
 use std::fs::{self, create_dir_all};
 use std::str::FromStr;
 
 use serde_derive::{Serialize, Deserialize};
 use clap::ValueEnum;
-use rlua::{UserData, Table, Value};
+use rlua::{Context, FromLua, UserData, UserDataMethods, Table, Value};
 
 use crate::cli::Cli;
 use crate::logging::{UserLogger, Logger};
-use config::{Config, ConfigError, File};
-use common::{DynamicResult, unit::RefDim};
+use crate::initial_condition::InitialConditionSpec;
+use config::{Config, ConfigError, File, FileFormat};
+use common::{time_directory_name, DynamicResult, unit::RefDim};
 use common::number::Real;
+use grid::Block;
 use grid::block::{BlockCollection, GridFileType};
-use gas::gas_model::{GasModels, GasModel};
-use gas::ideal_gas::IdealGas;
+use grid::error::GridError;
+use gas::gas_model::{GasModel, GasModelRegistry};
+use finite_volume::boundary_conditions::spec::BoundaryConditionSpec;
+use finite_volume::fluid_block::{FluidBlock, FluidBlockError};
+use finite_volume::flux::FluxCalculator;
+use finite_volume::reconstruction;
 
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct InvalidConfig;
 
+impl std::error::Error for InvalidConfig {}
+
+impl std::fmt::Display for InvalidConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Invalid simulation configuration")
+    }
+}
+
 /// Simulation configuration
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SimSettings {
-    gas_model_type: GasModels,
+    /// The name the gas model is registered with in the [`GasModelRegistry`]
+    gas_model_type: String,
 
+    /// Configs written before the reference dimensions were
+    /// recorded don't scale anything
+    #[serde(default)]
     reference_dimensions: RefDim,
 
+    /// Configs written before the solver was configurable
+    /// use the default solver settings
+    #[serde(default)]
+    solver: SolverConfig,
+
     // these don't get written to the generic config file
     #[serde(skip)]
     gas_model: Box<dyn GasModel<Real>>,
 
     #[serde(skip)]
     grids: BlockCollection,
+
+    #[serde(skip)]
+    boundary_conditions: Vec<BoundaryConditionSpec>,
+
+    #[serde(skip)]
+    initial_condition: Option<InitialConditionSpec>,
 }
 
 
+impl Default for SimSettings {
+    fn default() -> Self {
+        SimSettings {
+            gas_model_type: "ideal_gas".to_string(),
+            reference_dimensions: RefDim::default(),
+            solver: SolverConfig::default(),
+            gas_model: Box::default(),
+            grids: BlockCollection::default(),
+            boundary_conditions: Vec::new(),
+            initial_condition: None,
+        }
+    }
+}
+
 impl UserData for SimSettings {}
 
 impl SimSettings { 
-    pub fn from_lua_table(config: Table) -> Result<SimSettings, InvalidConfig> {
+    pub fn from_lua_table<'lua>(config: Table<'lua>, lua: Context<'lua>) -> Result<SimSettings, InvalidConfig> {
         // first check to make sure there are no invalid names in the table
         // this ensures the user doesn't misspell something, and unknowingly
         // get the default value
-        let allowable_names = ["reference_values", "blocks", "gas_model_type", "gas_model"];
+        let allowable_names = ["reference_values", "blocks", "gas_model_type", "gas_model", "solver", "initial_condition"];
         for pair in config.clone().pairs::<String, Value>() {
             let (key, _) = pair.unwrap();
             if !allowable_names.contains(&key.as_str()) {
@@ -57,48 +101,524 @@ impl SimSettings {
         let grids = config.get::<_, BlockCollection>("blocks").unwrap();
 
         // read the gas model
-        let gas_model_str = config.get::<_, String>("gas_model_type").unwrap();
-        let gas_model_type = GasModels::from_str(&gas_model_str).unwrap();
-        let gas_model: Box<dyn GasModel<Real>> = match gas_model_type {
-            GasModels::IdealGas => Box::new(config.get::<_, IdealGas<Real>>("gas_model").unwrap()),
+        let gas_model_type = config.get::<_, String>("gas_model_type").unwrap();
+        let gas_model = GasModelRegistry::new()
+            .build_from_lua(&gas_model_type, config.get("gas_model").unwrap(), lua)
+            .map_err(|_| InvalidConfig)?;
+
+        // the solver settings are optional, and can be
+        // a table of settings or a SolverConfig
+        let solver = match config.get::<_, Value>("solver").map_err(|_| InvalidConfig)? {
+            Value::Table(solver_table) => SolverConfig::from_lua_table(solver_table)?,
+            Value::UserData(solver) => solver.borrow::<SolverConfig>().map_err(|_| InvalidConfig)?.clone(),
+            Value::Nil => SolverConfig::default(),
+            _ => return Err(InvalidConfig),
+        };
+
+        // without an initial condition the cells start with zero flow states
+        let initial_condition = match config.get::<_, Option<Table>>("initial_condition").map_err(|_| InvalidConfig)? {
+            Some(ic_table) => Some(InitialConditionSpec::from_lua_table(ic_table)?),
+            None => None,
         };
+
         Ok(SimSettings{
-            reference_dimensions, grids, gas_model_type, gas_model,
+            reference_dimensions, solver, grids, gas_model_type, gas_model,
+            boundary_conditions: Vec::new(), initial_condition,
         })
     }
 
-    pub fn write_config(&self, file_structure: &FileStructure) -> DynamicResult<()> {
+    pub fn solver(&self) -> &SolverConfig {
+        &self.solver
+    }
+
+    pub fn gas_model_type(&self) -> &str {
+        &self.gas_model_type
+    }
+
+    pub fn reference_dimensions(&self) -> &RefDim {
+        &self.reference_dimensions
+    }
+
+    pub fn grids(&self) -> &BlockCollection {
+        &self.grids
+    }
+
+    /// Set the gas model, along with the name it's registered
+    /// with in the [`GasModelRegistry`]
+    pub fn set_gas_model(&mut self, gas_model_type: &str, gas_model: Box<dyn GasModel<Real>>) {
+        self.gas_model_type = gas_model_type.to_string();
+        self.gas_model = gas_model;
+    }
+
+    pub fn set_reference_dimensions(&mut self, reference_dimensions: RefDim) {
+        self.reference_dimensions = reference_dimensions;
+    }
+
+    pub fn set_solver(&mut self, solver: SolverConfig) {
+        self.solver = solver;
+    }
+
+    /// Read a grid, and add it to the grids of the simulation
+    pub fn add_block(&mut self, file_path: &Path) -> Result<(), GridError> {
+        self.grids.add_block(file_path)
+    }
+
+    pub fn boundary_conditions(&self) -> &[BoundaryConditionSpec] {
+        &self.boundary_conditions
+    }
+
+    /// Set the boundary condition for a boundary, replacing any
+    /// boundary condition already set for the same tag
+    pub fn set_boundary_condition(&mut self, bc: BoundaryConditionSpec) {
+        match self.boundary_conditions.iter_mut().find(|spec| spec.tag() == bc.tag()) {
+            Some(spec) => *spec = bc,
+            None => self.boundary_conditions.push(bc),
+        }
+    }
+
+    pub fn initial_condition(&self) -> Option<&InitialConditionSpec> {
+        self.initial_condition.as_ref()
+    }
+
+    pub fn set_initial_condition(&mut self, initial_condition: InitialConditionSpec) {
+        self.initial_condition = Some(initial_condition);
+    }
+
+    /// Create a fluid block from each grid, with the boundary conditions for
+    /// the tags in that grid. It's an error for a boundary condition's tag
+    /// to be in none of the grids. Boundaries without a boundary condition
+    /// are logged as a warning. The initial condition, if there is one,
+    /// sets the flow states in the cells.
+    pub fn create_fluid_blocks(&self, log: &UserLogger) -> DynamicResult<Vec<FluidBlock>> {
+        let blocks = self.grids.blocks();
+        if let Some(spec) = self.boundary_conditions.iter().find(|spec| !blocks.iter().any(|block| block.has_boundary(spec.tag()))) {
+            return Err(FluidBlockError::UnknownBoundaryTag(spec.tag().to_string()).into());
+        }
+
+        let mut fluid_blocks = Vec::with_capacity(blocks.len());
+        for grid_block in blocks.iter() {
+            let bc_specs: Vec<BoundaryConditionSpec> = self.boundary_conditions
+                .iter()
+                .filter(|spec| grid_block.has_boundary(spec.tag()))
+                .cloned()
+                .collect();
+            for tag in FluidBlock::boundaries_without_conditions(grid_block, &bc_specs) {
+                log.warning(&format!("block {}: boundary '{}' has no boundary condition", grid_block.id(), tag));
+            }
+            let mut fluid_block = FluidBlock::from_grid_block(grid_block, &bc_specs, self.gas_model())?;
+            if let Some(initial_condition) = &self.initial_condition {
+                initial_condition.apply(&mut fluid_block, self.gas_model())?;
+            }
+            fluid_blocks.push(fluid_block);
+        }
+        Ok(fluid_blocks)
+    }
+
+    /// Write the config, the gas model and the initial grids,
+    /// with the grids written in the format `grid_format`
+    pub fn write_config(&self, file_structure: &FileStructure, grid_format: GridFileType) -> DynamicResult<()> {
         // write the config file
         let config_toml = toml::to_string(self).unwrap();
         fs::write(file_structure.config(), config_toml).unwrap();
 
-        match self.gas_model_type {
-            GasModels::IdealGas => {
-                let ideal_gas: &IdealGas<Real> = self.gas_model.as_any().downcast_ref().unwrap();
-                let ideal_gas_toml = toml::to_string(ideal_gas).unwrap();
-                fs::write(file_structure.gas_model(), ideal_gas_toml).unwrap();
-            }
-        }
+        let gas_model = GasModelRegistry::new().to_toml(&self.gas_model_type, self.gas_model())?;
+        fs::write(file_structure.gas_model(), toml::to_string(&gas_model)?)?;
 
-        self.write_initial_conditions(file_structure)?;
+        self.write_initial_conditions(file_structure, grid_format)?;
 
 
         Ok(())
     }
+
+    /// Read back the configuration written by [`SimSettings::write_config`].
+    /// The grids aren't read.
+    pub fn read_config(file_structure: &FileStructure) -> DynamicResult<SimSettings> {
+        let config_toml = fs::read_to_string(file_structure.config())?;
+        let mut sim_settings: SimSettings = toml::from_str(&config_toml)?;
+
+        let gas_model_toml = fs::read_to_string(file_structure.gas_model())?;
+        sim_settings.gas_model = GasModelRegistry::new().build(&sim_settings.gas_model_type, &toml::from_str(&gas_model_toml)?)?;
+        Ok(sim_settings)
+    }
+
+    pub fn gas_model(&self) -> &dyn GasModel<Real> {
+        self.gas_model.as_ref()
+    }
     
-    fn write_initial_conditions(&self, file_structure: &FileStructure) -> DynamicResult<()> {
-        self.write_initial_grid(file_structure) 
+    fn write_initial_conditions(&self, file_structure: &FileStructure, grid_format: GridFileType) -> DynamicResult<()> {
+        self.write_initial_grid(file_structure, grid_format) 
     }
 
-    fn write_initial_grid(&self, file_structure: &FileStructure) -> DynamicResult<()> {
-        let mut dir = file_structure.grid().to_path_buf(); 
-        dir.push(&PathBuf::from_str("t0000").unwrap());
+    fn write_initial_grid(&self, file_structure: &FileStructure, grid_format: GridFileType) -> DynamicResult<()> {
+        let dir = file_structure.grid_time_directory(0);
         create_dir_all(&dir)?;
-        self.grids.write_blocks(&dir)?;
+        self.grids.write_blocks_as(&dir, grid_format)?;
         Ok(())
     }
 }
 
+/// Method of integrating the solution in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeIntegrator {
+    ForwardEuler, RungeKutta3,
+}
+
+impl fmt::Display for TimeIntegrator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self {
+            TimeIntegrator::ForwardEuler => write!(f, "forward_euler"),
+            TimeIntegrator::RungeKutta3 => write!(f, "runge_kutta3"),
+        }
+    }
+}
+
+impl FromStr for TimeIntegrator {
+    type Err = InvalidConfig;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "forward_euler" => Ok(TimeIntegrator::ForwardEuler),
+            "runge_kutta3" => Ok(TimeIntegrator::RungeKutta3),
+            _ => Err(InvalidConfig),
+        }
+    }
+}
+
+/// Method of computing the convective flux through interfaces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FluxScheme {
+    Hanel, Ausmdv,
+}
+
+impl fmt::Display for FluxScheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self {
+            FluxScheme::Hanel => write!(f, "hanel"),
+            FluxScheme::Ausmdv => write!(f, "ausmdv"),
+        }
+    }
+}
+
+impl FromStr for FluxScheme {
+    type Err = InvalidConfig;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hanel" => Ok(FluxScheme::Hanel),
+            "ausmdv" => Ok(FluxScheme::Ausmdv),
+            _ => Err(InvalidConfig),
+        }
+    }
+}
+
+impl From<FluxScheme> for FluxCalculator {
+    fn from(flux_scheme: FluxScheme) -> Self {
+        match flux_scheme {
+            FluxScheme::Hanel => FluxCalculator::Hanel,
+            FluxScheme::Ausmdv => FluxCalculator::Ausmdv,
+        }
+    }
+}
+
+/// Order of the reconstruction of the flow states at the interfaces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconstructionOrder {
+    First, Second,
+}
+
+impl fmt::Display for ReconstructionOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self {
+            ReconstructionOrder::First => write!(f, "first"),
+            ReconstructionOrder::Second => write!(f, "second"),
+        }
+    }
+}
+
+impl FromStr for ReconstructionOrder {
+    type Err = InvalidConfig;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first" => Ok(ReconstructionOrder::First),
+            "second" => Ok(ReconstructionOrder::Second),
+            _ => Err(InvalidConfig),
+        }
+    }
+}
+
+impl From<ReconstructionOrder> for reconstruction::ReconstructionOrder {
+    fn from(order: ReconstructionOrder) -> Self {
+        match order {
+            ReconstructionOrder::First => reconstruction::ReconstructionOrder::First,
+            ReconstructionOrder::Second => reconstruction::ReconstructionOrder::Second,
+        }
+    }
+}
+
+/// Controls the solver time loop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolverConfig {
+    time_integrator: TimeIntegrator,
+    flux_scheme: FluxScheme,
+    reconstruction_order: ReconstructionOrder,
+    cfl: Real,
+    max_iterations: usize,
+    convergence_tolerance: Real,
+    output_interval: usize,
+    max_physical_time: Option<Real>,
+    #[serde(default)]
+    axisymmetric: bool,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig {
+            time_integrator: TimeIntegrator::ForwardEuler,
+            flux_scheme: FluxScheme::Ausmdv,
+            reconstruction_order: ReconstructionOrder::Second,
+            cfl: 0.5,
+            max_iterations: 1000,
+            convergence_tolerance: 1e-6,
+            output_interval: 100,
+            max_physical_time: None,
+            axisymmetric: false,
+        }
+    }
+}
+
+/// Each setting has a getter and a setter named after the rust methods,
+/// e.g. `solver:cfl()` and `solver:set_cfl(0.8)`. The methods are given
+/// the same strings as the lua table, e.g. `solver:set_flux_scheme("hanel")`.
+/// Setting an invalid value raises an error, and leaves the setting as it was.
+impl UserData for SolverConfig {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("time_integrator", |_, solver, ()| Ok(solver.time_integrator.to_string()));
+        methods.add_method_mut("set_time_integrator", |_, solver, value: String| {
+            let time_integrator = TimeIntegrator::from_str(&value).map_err(|_| invalid_setting("time_integrator"))?;
+            solver.set_time_integrator(time_integrator);
+            Ok(())
+        });
+        methods.add_method("flux_scheme", |_, solver, ()| Ok(solver.flux_scheme.to_string()));
+        methods.add_method_mut("set_flux_scheme", |_, solver, value: String| {
+            let flux_scheme = FluxScheme::from_str(&value).map_err(|_| invalid_setting("flux_scheme"))?;
+            solver.set_flux_scheme(flux_scheme);
+            Ok(())
+        });
+        methods.add_method("reconstruction_order", |_, solver, ()| Ok(solver.reconstruction_order.to_string()));
+        methods.add_method_mut("set_reconstruction_order", |_, solver, value: String| {
+            let order = ReconstructionOrder::from_str(&value).map_err(|_| invalid_setting("reconstruction_order"))?;
+            solver.set_reconstruction_order(order);
+            Ok(())
+        });
+        methods.add_method("cfl", |_, solver, ()| Ok(solver.cfl));
+        methods.add_method_mut("set_cfl", |_, solver, cfl: Real| {
+            set_validated(solver, "cfl", |solver| solver.cfl = cfl)
+        });
+        methods.add_method("max_iterations", |_, solver, ()| Ok(solver.max_iterations));
+        methods.add_method_mut("set_max_iterations", |_, solver, max_iterations: usize| {
+            set_validated(solver, "max_iterations", |solver| solver.max_iterations = max_iterations)
+        });
+        methods.add_method("convergence_tolerance", |_, solver, ()| Ok(solver.convergence_tolerance));
+        methods.add_method_mut("set_convergence_tolerance", |_, solver, tolerance: Real| {
+            set_validated(solver, "convergence_tolerance", |solver| solver.convergence_tolerance = tolerance)
+        });
+        methods.add_method("output_interval", |_, solver, ()| Ok(solver.output_interval));
+        methods.add_method_mut("set_output_interval", |_, solver, output_interval: usize| {
+            set_validated(solver, "output_interval", |solver| solver.output_interval = output_interval)
+        });
+        methods.add_method("max_physical_time", |_, solver, ()| Ok(solver.max_physical_time));
+        methods.add_method_mut("set_max_physical_time", |_, solver, max_time: Option<Real>| {
+            set_validated(solver, "max_physical_time", |solver| solver.max_physical_time = max_time)
+        });
+        methods.add_method("axisymmetric", |_, solver, ()| Ok(solver.axisymmetric));
+        methods.add_method_mut("set_axisymmetric", |_, solver, axisymmetric: bool| {
+            solver.set_axisymmetric(axisymmetric);
+            Ok(())
+        });
+    }
+}
+
+/// Apply `change` to the solver config, unless it makes the config invalid
+fn set_validated(solver: &mut SolverConfig, setting: &str, change: impl FnOnce(&mut SolverConfig)) -> rlua::Result<()> {
+    let mut changed = solver.clone();
+    change(&mut changed);
+    changed.validate().map_err(|_| invalid_setting(setting))?;
+    *solver = changed;
+    Ok(())
+}
+
+fn invalid_setting(setting: &str) -> rlua::Error {
+    rlua::Error::RuntimeError(format!("Invalid value of the solver setting {}", setting))
+}
+
+impl SolverConfig {
+    /// Read the solver configuration from a lua table. Anything
+    /// not in the table takes the default value.
+    pub fn from_lua_table(table: Table) -> Result<SolverConfig, InvalidConfig> {
+        let allowable_names = [
+            "time_integrator", "flux_scheme", "reconstruction_order", "cfl", "max_iterations",
+            "convergence_tolerance", "output_interval", "max_physical_time", "axisymmetric"
+        ];
+        for pair in table.clone().pairs::<String, Value>() {
+            let (key, _) = pair.map_err(|_| InvalidConfig)?;
+            if !allowable_names.contains(&key.as_str()) {
+                return Err(InvalidConfig);
+            }
+        }
+
+        let mut solver = SolverConfig::default();
+        if let Some(time_integrator) = get_optional::<String>(&table, "time_integrator")? {
+            solver.time_integrator = TimeIntegrator::from_str(&time_integrator)?;
+        }
+        if let Some(flux_scheme) = get_optional::<String>(&table, "flux_scheme")? {
+            solver.flux_scheme = FluxScheme::from_str(&flux_scheme)?;
+        }
+        if let Some(order) = get_optional::<String>(&table, "reconstruction_order")? {
+            solver.reconstruction_order = ReconstructionOrder::from_str(&order)?;
+        }
+        if let Some(cfl) = get_optional(&table, "cfl")? {
+            solver.cfl = cfl;
+        }
+        if let Some(max_iterations) = get_optional(&table, "max_iterations")? {
+            solver.max_iterations = max_iterations;
+        }
+        if let Some(tolerance) = get_optional(&table, "convergence_tolerance")? {
+            solver.convergence_tolerance = tolerance;
+        }
+        if let Some(output_interval) = get_optional(&table, "output_interval")? {
+            solver.output_interval = output_interval;
+        }
+        solver.max_physical_time = get_optional(&table, "max_physical_time")?;
+        if let Some(axisymmetric) = get_optional(&table, "axisymmetric")? {
+            solver.axisymmetric = axisymmetric;
+        }
+
+        solver.validate()?;
+        Ok(solver)
+    }
+
+    /// Check the values are within the allowable ranges
+    pub fn validate(&self) -> Result<(), InvalidConfig> {
+        let cfl_ok = self.cfl > 0.0 && self.cfl <= 2.0;
+        let tolerance_ok = self.convergence_tolerance > 0.0;
+        let output_interval_ok = self.output_interval > 0;
+        // is_none_or would need Rust 1.82
+        #[allow(clippy::unnecessary_map_or)]
+        let max_time_ok = self.max_physical_time.map_or(true, |time| time > 0.0);
+        match cfl_ok && tolerance_ok && output_interval_ok && max_time_ok {
+            true => Ok(()),
+            false => Err(InvalidConfig),
+        }
+    }
+
+    pub fn time_integrator(&self) -> TimeIntegrator {
+        self.time_integrator
+    }
+
+    pub fn flux_scheme(&self) -> FluxScheme {
+        self.flux_scheme
+    }
+
+    pub fn reconstruction_order(&self) -> ReconstructionOrder {
+        self.reconstruction_order
+    }
+
+    pub fn cfl(&self) -> Real {
+        self.cfl
+    }
+
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    pub fn convergence_tolerance(&self) -> Real {
+        self.convergence_tolerance
+    }
+
+    pub fn output_interval(&self) -> usize {
+        self.output_interval
+    }
+
+    pub fn max_physical_time(&self) -> Option<Real> {
+        self.max_physical_time
+    }
+
+    /// Whether the solver should stop after `iteration` iterations, which
+    /// is when the maximum number of iterations is reached, or the physical
+    /// time reaches the maximum physical time if there is one
+    pub fn is_finished(&self, iteration: usize, physical_time: Real) -> bool {
+        iteration >= self.max_iterations
+            || self.max_physical_time.is_some_and(|max_time| physical_time >= max_time)
+    }
+
+    /// Whether the flow is axisymmetric about the x axis,
+    /// in which case the y coordinate is the radius
+    pub fn is_axisymmetric(&self) -> bool {
+        self.axisymmetric
+    }
+
+    pub fn set_time_integrator(&mut self, time_integrator: TimeIntegrator) {
+        self.time_integrator = time_integrator;
+    }
+
+    pub fn set_flux_scheme(&mut self, flux_scheme: FluxScheme) {
+        self.flux_scheme = flux_scheme;
+    }
+
+    pub fn set_reconstruction_order(&mut self, reconstruction_order: ReconstructionOrder) {
+        self.reconstruction_order = reconstruction_order;
+    }
+
+    pub fn set_cfl(&mut self, cfl: Real) {
+        self.cfl = cfl;
+    }
+
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    pub fn set_convergence_tolerance(&mut self, convergence_tolerance: Real) {
+        self.convergence_tolerance = convergence_tolerance;
+    }
+
+    pub fn set_output_interval(&mut self, output_interval: usize) {
+        self.output_interval = output_interval;
+    }
+
+    pub fn set_max_physical_time(&mut self, max_physical_time: Option<Real>) {
+        self.max_physical_time = max_physical_time;
+    }
+
+    pub fn set_axisymmetric(&mut self, axisymmetric: bool) {
+        self.axisymmetric = axisymmetric;
+    }
+}
+
+fn get_optional<'lua, T: FromLua<'lua>>(table: &Table<'lua>, key: &str) -> Result<Option<T>, InvalidConfig> {
+    table.get::<_, Option<T>>(key).map_err(|_| InvalidConfig)
+}
+
+/// The verbosity given by the value of the `AEOLUS_VERBOSITY`
+/// environment variable, e.g. `debug`, or `None` if it isn't set
+fn verbosity_from_env(value: Option<&str>) -> Result<Option<Verbosity>, ConfigError> {
+    let verbosity = match value {
+        Some(verbosity) => verbosity,
+        None => return Ok(None),
+    };
+    parse_verbosity(verbosity)
+        .map(Some)
+        .map_err(|err| ConfigError::Message(format!("{} in AEOLUS_VERBOSITY: {}", err, verbosity)))
+}
+
+/// Read a verbosity, ignoring case, e.g. `debug` or `Warning`
+pub fn parse_verbosity(value: &str) -> Result<Verbosity, ConfigValidationError> {
+    Verbosity::from_str(value, true).map_err(|_| ConfigValidationError::InvalidVerbosity)
+}
+
 /// Configuration for the program
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AeolusSettings {
@@ -108,24 +628,97 @@ pub struct AeolusSettings {
 }
 
 impl AeolusSettings {
+    /// Read the settings, in order of precedence, from the command line,
+    /// the `AEOLUS_VERBOSITY` environment variable, `local.toml` and
+    /// the defaults in `$AEOLUS_HOME/resources/defaults`
     pub fn new(args: &Cli) -> Result<AeolusSettings, ConfigError> {
-        // where to look for default config
         let aeolus_home = env::var("AEOLUS_HOME").unwrap_or_else(|_| ".".into());
-        let aeolus_default_path = format!("{}/resources/defaults/aeolus_defaults.toml", aeolus_home);
+        let env_verbosity = env::var("AEOLUS_VERBOSITY").ok();
+        AeolusSettings::with_environment(Path::new(&aeolus_home), env_verbosity.as_deref(), args)
+    }
+
+    /// Read the settings as [`AeolusSettings::new`] does, given the values
+    /// of the `AEOLUS_HOME` and `AEOLUS_VERBOSITY` environment variables
+    fn with_environment(aeolus_home: &Path, env_verbosity: Option<&str>, args: &Cli)
+        -> Result<AeolusSettings, ConfigError>
+    {
+        // where to look for default config
+        let aeolus_default_path = aeolus_home.join("resources/defaults/aeolus_defaults.toml");
         // begin configuring from files
         let s = Config::builder()
-            .add_source(File::from(Path::new(&aeolus_default_path)))
+            .add_source(File::from(aeolus_default_path))
             .add_source(File::from(Path::new("local.toml")).required(false))
+            .set_override_option("verbosity", verbosity_from_env(env_verbosity)?.map(|v| v.to_string()))?
             .set_override_option("verbosity", args.verbosity.as_ref().map(|v| v.to_string()))?;
 
         // Attempt to read the configuration
         s.build()?.try_deserialize()
     }
 
+    /// Read the settings from a single file, detecting the format
+    /// from the extension (`.toml`, `.json`, `.yaml` or `.yml`)
+    pub fn from_file(path: &Path) -> Result<AeolusSettings, ConfigError> {
+        let ext = path.extension().and_then(OsStr::to_str);
+        let format = match ext {
+            Some("toml") => FileFormat::Toml,
+            Some("json") => FileFormat::Json,
+            Some("yaml") | Some("yml") => FileFormat::Yaml,
+            _ => return Err(ConfigError::Message(
+                format!("Unknown config file format: {}", path.display())
+            )),
+        };
+        AeolusSettings::from_file_with_format(path, format)
+    }
+
+    pub fn from_toml_file(path: &Path) -> Result<AeolusSettings, ConfigError> {
+        AeolusSettings::from_file_with_format(path, FileFormat::Toml)
+    }
+
+    pub fn from_json_file(path: &Path) -> Result<AeolusSettings, ConfigError> {
+        AeolusSettings::from_file_with_format(path, FileFormat::Json)
+    }
+
+    pub fn from_yaml_file(path: &Path) -> Result<AeolusSettings, ConfigError> {
+        AeolusSettings::from_file_with_format(path, FileFormat::Yaml)
+    }
+
+    fn from_file_with_format(path: &Path, format: FileFormat) -> Result<AeolusSettings, ConfigError> {
+        Config::builder()
+            .add_source(File::from(path).format(format))
+            .build()?
+            .try_deserialize()
+    }
+
+    pub fn to_toml_string(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn to_yaml_string(&self) -> String {
+        serde_yaml::to_string(self).unwrap()
+    }
+
+    /// Check the settings make sense, returning every problem found
+    pub fn validate(&self) -> Result<(), Vec<ConfigValidationError>> {
+        let errors = self.file_structure.validate();
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
     pub fn verbosity(&self) -> &Verbosity {
         &self.verbosity
     }
 
+    /// The format the grids are written in
+    pub fn native_grid_format(&self) -> GridFileType {
+        self.native_grid_format
+    }
+
     pub fn file_structure(&self) -> &FileStructure {
         &self.file_structure
     }
@@ -133,7 +726,7 @@ impl AeolusSettings {
 
 /// The location the program should look
 /// for different parts of the configuration
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FileStructure {
     config: PathBuf,
     gas_model: PathBuf,
@@ -171,8 +764,97 @@ fn remove_base_folder(dir: &Path, log: &UserLogger) -> Result<(), std::io::Error
     }
 }
 
+/// Problems with the configuration that can be found
+/// before running anything
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigValidationError {
+    /// The path can't be created, because one of its parents isn't a directory
+    PathNotFound(PathBuf),
+
+    /// The verbosity isn't one of the allowed values
+    InvalidVerbosity,
+
+    /// Two settings refer to the same location
+    AliasedPaths(PathBuf, PathBuf),
+}
+
+impl std::error::Error for ConfigValidationError {}
+
+impl std::fmt::Display for ConfigValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self {
+            ConfigValidationError::PathNotFound(path) => write!(
+                f, "Unable to create the parent directory of {}", path.display()
+            ),
+            ConfigValidationError::InvalidVerbosity => write!(f, "Invalid verbosity"),
+            ConfigValidationError::AliasedPaths(first, second) => write!(
+                f, "{} and {} refer to the same location", first.display(), second.display()
+            ),
+        }
+    }
+}
+
+/// Check the closest existing ancestor of `path` is a directory,
+/// so that the OS is able to create the rest of the path
+fn parent_is_creatable(path: &Path) -> bool {
+    let mut ancestors = path.ancestors().skip(1);
+    loop {
+        match ancestors.next() {
+            Some(ancestor) if ancestor.as_os_str().is_empty() => return true,
+            Some(ancestor) if ancestor.exists() => return ancestor.is_dir(),
+            Some(_) => continue,
+            None => return true,
+        }
+    }
+}
+
+/// Compare paths by their components, which ignores things
+/// like trailing or repeated separators and references to `.`
+fn same_location(first: &Path, second: &Path) -> bool {
+    let not_cur_dir = |component: &Component| *component != Component::CurDir;
+    first.components().filter(not_cur_dir).eq(second.components().filter(not_cur_dir))
+}
+
+/// The name of the log written while running a simulation
+pub const LOG_FILE: &str = "aeolus.log";
+
 impl FileStructure {
+    /// The default layout of the files, within `dir`
+    pub fn in_directory(dir: &Path) -> FileStructure {
+        FileStructure {
+            config: dir.join("config").join("config.toml"),
+            gas_model: dir.join("config").join("gas_model.toml"),
+            solver: dir.join("config").join("solver.toml"),
+            discretisation: dir.join("config").join("discretisation.toml"),
+            grid: dir.join("solution").join("grid"),
+            fluid: dir.join("solution").join("fluid"),
+        }
+    }
+
+    fn paths(&self) -> [&Path; 6] {
+        [&self.config, &self.gas_model, &self.solver, &self.discretisation, &self.grid, &self.fluid]
+    }
+
+    fn validate(&self) -> Vec<ConfigValidationError> {
+        let mut errors = Vec::new();
+        let paths = self.paths();
+        for path in paths.iter() {
+            if !parent_is_creatable(path) {
+                errors.push(ConfigValidationError::PathNotFound(path.to_path_buf()));
+            }
+        }
+        for (i, first) in paths.iter().enumerate() {
+            for second in paths[i+1..].iter() {
+                if same_location(first, second) {
+                    errors.push(ConfigValidationError::AliasedPaths(first.to_path_buf(), second.to_path_buf()));
+                }
+            }
+        }
+        errors
+    }
+
     pub fn create_directories(&self) {
+        create_parent_directory(&self.config);
         create_parent_directory(&self.solver);
         create_parent_directory(&self.discretisation);
         create_parent_directory(&self.grid);
@@ -212,11 +894,43 @@ impl FileStructure {
     pub fn gas_model(&self) -> &Path {
         &self.gas_model
     }
+
+    /// The log of running the simulation, in the fluid directory
+    pub fn log(&self) -> PathBuf {
+        self.fluid.join(LOG_FILE)
+    }
+
+    /// The directory holding the fluid solution at a given time index
+    pub fn time_directory(&self, index: usize) -> PathBuf {
+        self.fluid.join(time_directory_name(index))
+    }
+
+    /// The directory holding the grid at a given time index
+    pub fn grid_time_directory(&self, index: usize) -> PathBuf {
+        self.grid.join(time_directory_name(index))
+    }
+
+    /// Find the highest time index in the fluid directory
+    pub fn latest_time_index(&self) -> DynamicResult<usize> {
+        let mut latest = None;
+        for entry in fs::read_dir(&self.fluid)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let index = entry.file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix('t'))
+                .and_then(|index| index.parse::<usize>().ok());
+            latest = latest.max(index);
+        }
+        latest.ok_or_else(|| format!("No time directories in {}", self.fluid.display()).into())
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, ValueEnum, Clone)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum, Clone)]
 pub enum Verbosity {
-    Error, Warning, Debug 
+    Error, Warning, Info, Debug 
 }
 
 impl std::fmt::Display for Verbosity {
@@ -224,7 +938,361 @@ impl std::fmt::Display for Verbosity {
         match &self {
             Verbosity::Error => write!(f, "Error"),
             Verbosity::Warning => write!(f, "Warning"),
+            Verbosity::Info => write!(f, "Info"),
             Verbosity::Debug => write!(f, "Debug"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULTS: &str = "../../resources/defaults/aeolus_defaults.toml";
+
+    fn round_trip(contents: String, file_name: &str) -> AeolusSettings {
+        let path = env::temp_dir().join(file_name);
+        fs::write(&path, contents).unwrap();
+        let settings = AeolusSettings::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        settings
+    }
+
+    #[test]
+    fn verbosity_precedence() {
+        use clap::Parser;
+
+        let home = Path::new("../..");
+        let without_flag = Cli::try_parse_from(["aeolus", "post"]).unwrap();
+        let with_flag = Cli::try_parse_from(["aeolus", "--verbosity", "info", "post"]).unwrap();
+
+        let settings = AeolusSettings::with_environment(home, None, &without_flag).unwrap();
+        assert_eq!(settings.verbosity(), &Verbosity::Error);
+        for (value, verbosity) in [("error", Verbosity::Error), ("warning", Verbosity::Warning),
+                                   ("info", Verbosity::Info), ("debug", Verbosity::Debug)] {
+            let settings = AeolusSettings::with_environment(home, Some(value), &without_flag).unwrap();
+            assert_eq!(settings.verbosity(), &verbosity);
+            let settings = AeolusSettings::with_environment(home, Some(value), &with_flag).unwrap();
+            assert_eq!(settings.verbosity(), &Verbosity::Info);
+        }
+        assert!(AeolusSettings::with_environment(home, Some("loud"), &without_flag).is_err());
+    }
+
+    #[test]
+    fn parse_verbosity_ignores_case() {
+        assert_eq!(parse_verbosity("debug"), Ok(Verbosity::Debug));
+        assert_eq!(parse_verbosity("Warning"), Ok(Verbosity::Warning));
+        assert_eq!(parse_verbosity("ERROR"), Ok(Verbosity::Error));
+    }
+
+    #[test]
+    fn parse_verbosity_invalid() {
+        assert_eq!(parse_verbosity("loud"), Err(ConfigValidationError::InvalidVerbosity));
+        assert_eq!(parse_verbosity(""), Err(ConfigValidationError::InvalidVerbosity));
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let settings = AeolusSettings::from_toml_file(Path::new(DEFAULTS)).unwrap();
+        let read_settings = round_trip(settings.to_toml_string(), "aeolus_settings_test.toml");
+
+        assert_eq!(read_settings.verbosity(), settings.verbosity());
+        assert_eq!(read_settings.file_structure(), settings.file_structure());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let settings = AeolusSettings::from_toml_file(Path::new(DEFAULTS)).unwrap();
+        let read_settings = round_trip(settings.to_json_string(), "aeolus_settings_test.json");
+
+        assert_eq!(read_settings.verbosity(), settings.verbosity());
+        assert_eq!(read_settings.file_structure(), settings.file_structure());
+    }
+
+    #[test]
+    fn yaml_round_trip() {
+        let settings = AeolusSettings::from_toml_file(Path::new(DEFAULTS)).unwrap();
+        let read_settings = round_trip(settings.to_yaml_string(), "aeolus_settings_test.yml");
+
+        assert_eq!(read_settings.verbosity(), settings.verbosity());
+        assert_eq!(read_settings.file_structure(), settings.file_structure());
+    }
+
+    #[test]
+    fn file_structure_in_directory() {
+        let settings = AeolusSettings::from_toml_file(Path::new(DEFAULTS)).unwrap();
+
+        assert_eq!(&FileStructure::in_directory(Path::new("")), settings.file_structure());
+    }
+
+    fn read_solver_config(script: &str) -> Result<SolverConfig, InvalidConfig> {
+        let lua = rlua::Lua::new();
+        lua.context(|lua_ctx| {
+            let table = lua_ctx.load(script).eval::<Table>().unwrap();
+            SolverConfig::from_lua_table(table)
+        })
+    }
+
+    #[test]
+    fn solver_config_default() {
+        let solver = SolverConfig::default();
+
+        assert_eq!(solver.validate(), Ok(()));
+        assert_eq!(solver.time_integrator(), TimeIntegrator::ForwardEuler);
+        assert!(solver.cfl() > 0.0 && solver.cfl() < 1.0);
+        assert!(solver.max_iterations() > 0);
+        assert!(solver.output_interval() <= solver.max_iterations());
+        assert_eq!(solver.max_physical_time(), None);
+        assert!(!solver.is_axisymmetric());
+    }
+
+    #[test]
+    fn solver_config_from_lua() {
+        let solver = read_solver_config(
+            "{cfl = 0.8, flux_scheme = 'hanel', reconstruction_order = 'first', max_physical_time = 1e-3, axisymmetric = true}"
+        ).unwrap();
+
+        assert_eq!(solver.cfl(), 0.8);
+        assert_eq!(solver.flux_scheme(), FluxScheme::Hanel);
+        assert_eq!(solver.reconstruction_order(), ReconstructionOrder::First);
+        assert_eq!(solver.max_physical_time(), Some(1e-3));
+        assert!(solver.is_axisymmetric());
+        assert_eq!(solver.max_iterations(), SolverConfig::default().max_iterations());
+    }
+
+    #[test]
+    fn solver_config_from_lua_user_data() {
+        let lua = crate::lua::create_lua_state();
+        let sim_settings = lua.context(|lua_ctx| {
+            lua_ctx.load(r#"
+                solver = SolverConfig()
+                solver:set_cfl(0.8)
+                solver:set_flux_scheme("hanel")
+                solver:set_max_physical_time(1e-3)
+                assert(solver:cfl() == 0.8)
+                assert(solver:flux_scheme() == "hanel")
+                assert(not pcall(solver.set_cfl, solver, 2.5))
+                assert(not pcall(solver.set_reconstruction_order, solver, "third"))
+                assert(solver:cfl() == 0.8)
+
+                blks = blocks()
+                blks:add_block("../grid/tests/data/square.su2")
+                config.reference_values = RefDim(UnitNum(1000., "m/s"), UnitNum(1., "kg/m^3"), UnitNum(1., "m"))
+                config.blocks = blks
+                config.gas_model_type = "ideal_gas"
+                config.gas_model = IdealGas(287, 1.4)
+                config.solver = solver
+            "#).exec().unwrap();
+            let config = lua_ctx.globals().get::<_, Table>("config").unwrap();
+            SimSettings::from_lua_table(config, lua_ctx).unwrap()
+        });
+
+        let solver = sim_settings.solver();
+        assert_eq!(solver.cfl(), 0.8);
+        assert_eq!(solver.flux_scheme(), FluxScheme::Hanel);
+        assert_eq!(solver.max_physical_time(), Some(1e-3));
+        assert_eq!(solver.max_iterations(), SolverConfig::default().max_iterations());
+    }
+
+    #[test]
+    fn solver_config_is_finished() {
+        let mut solver = SolverConfig::default();
+        solver.set_max_iterations(100);
+        assert!(!solver.is_finished(99, 1e6));
+        assert!(solver.is_finished(100, 0.0));
+
+        solver.set_max_physical_time(Some(1e-3));
+        assert!(!solver.is_finished(10, 0.5e-3));
+        assert!(solver.is_finished(10, 1e-3));
+        assert!(solver.is_finished(100, 0.5e-3));
+    }
+
+    #[test]
+    fn solver_config_invalid_cfl() {
+        let solver = read_solver_config("{cfl = 2.5}");
+
+        assert_eq!(solver, Err(InvalidConfig));
+    }
+
+    #[test]
+    fn solver_config_unknown_name() {
+        let solver = read_solver_config("{clf = 0.5}");
+
+        assert_eq!(solver, Err(InvalidConfig));
+    }
+
+    #[test]
+    fn solver_config_unknown_flux_scheme() {
+        let solver = read_solver_config("{flux_scheme = 'upwind'}");
+
+        assert_eq!(solver, Err(InvalidConfig));
+    }
+
+    fn file_structure(grid: &str, fluid: &str) -> FileStructure {
+        FileStructure {
+            config: PathBuf::from("config/config.toml"),
+            gas_model: PathBuf::from("config/gas_model.toml"),
+            solver: PathBuf::from("config/solver.toml"),
+            discretisation: PathBuf::from("config/discretisation.toml"),
+            grid: PathBuf::from(grid),
+            fluid: PathBuf::from(fluid),
+        }
+    }
+
+    #[test]
+    fn validate_default() {
+        let settings = AeolusSettings::from_toml_file(Path::new(DEFAULTS)).unwrap();
+
+        assert_eq!(settings.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_aliased_paths() {
+        let file_structure = file_structure("solution/grid", "./solution//grid/");
+        let errors = file_structure.validate();
+
+        assert_eq!(errors, vec![ConfigValidationError::AliasedPaths(
+            PathBuf::from("solution/grid"), PathBuf::from("./solution//grid/")
+        )]);
+    }
+
+    #[test]
+    fn validate_uncreatable_paths() {
+        // Cargo.toml is a file, so can't have anything inside it
+        let file_structure = file_structure("Cargo.toml/grid", "Cargo.toml/fluid/t0000");
+        let errors = file_structure.validate();
+
+        assert_eq!(errors, vec![
+            ConfigValidationError::PathNotFound(PathBuf::from("Cargo.toml/grid")),
+            ConfigValidationError::PathNotFound(PathBuf::from("Cargo.toml/fluid/t0000")),
+        ]);
+    }
+
+    #[test]
+    fn time_directories() {
+        let file_structure = file_structure("solution/grid", "solution/fluid");
+
+        assert_eq!(file_structure.time_directory(12), PathBuf::from("solution/fluid/t0012"));
+        assert_eq!(file_structure.grid_time_directory(0), PathBuf::from("solution/grid/t0000"));
+        assert_eq!(file_structure.time_directory(12345), PathBuf::from("solution/fluid/t12345"));
+    }
+
+    #[test]
+    fn latest_time_index() {
+        let fluid = env::temp_dir().join("aeolus_latest_time_index");
+        let _ = fs::remove_dir_all(&fluid);
+        fs::create_dir_all(&fluid).unwrap();
+        let file_structure = file_structure("solution/grid", fluid.to_str().unwrap());
+        assert!(file_structure.latest_time_index().is_err());
+
+        for index in [0, 2, 11] {
+            fs::create_dir(file_structure.time_directory(index)).unwrap();
+        }
+        // only directories named after a time index count
+        fs::write(file_structure.time_directory(20), "").unwrap();
+        fs::create_dir(fluid.join("tmp")).unwrap();
+
+        assert_eq!(file_structure.latest_time_index().unwrap(), 11);
+        fs::remove_dir_all(&fluid).unwrap();
+    }
+
+    #[test]
+    fn unknown_format() {
+        let settings = AeolusSettings::from_file(Path::new("settings.ini"));
+
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn create_fluid_blocks() {
+        let log = UserLogger::with_verbosity(&Verbosity::Error);
+        let mut sim_settings = SimSettings::default();
+        sim_settings.grids.add_block(Path::new("../grid/tests/data/square.su2")).unwrap();
+        sim_settings.set_boundary_condition(BoundaryConditionSpec::supersonic_outflow("outflow"));
+
+        let fluid_blocks = sim_settings.create_fluid_blocks(&log).unwrap();
+        assert_eq!(fluid_blocks.len(), 1);
+        assert_eq!(fluid_blocks[0].boundaries().len(), 1);
+
+        sim_settings.set_boundary_condition(BoundaryConditionSpec::slip_wall("wall"));
+        let err = sim_settings.create_fluid_blocks(&log).err().unwrap();
+        assert!(err.to_string().contains("'wall'"));
+    }
+
+    #[test]
+    fn uniform_initial_condition_from_lua() {
+        let log = UserLogger::with_verbosity(&Verbosity::Error);
+        let lua = crate::lua::create_lua_state();
+        let sim_settings = lua.context(|lua_ctx| {
+            lua_ctx.load(r#"
+                blks = blocks()
+                blks:add_block("../grid/tests/data/square.su2")
+                config.reference_values = RefDim(UnitNum(1000., "m/s"), UnitNum(1., "kg/m^3"), UnitNum(1., "m"))
+                config.blocks = blks
+                config.gas_model_type = "ideal_gas"
+                config.gas_model = IdealGas(287, 1.4)
+                config.initial_condition = {type = "uniform", p = 101325, T = 300, vel_x = 500}
+            "#).exec().unwrap();
+            let config = lua_ctx.globals().get::<_, Table>("config").unwrap();
+            SimSettings::from_lua_table(config, lua_ctx).unwrap()
+        });
+
+        let fluid_blocks = sim_settings.create_fluid_blocks(&log).unwrap();
+        let flow_states = fluid_blocks[0].cells().flow_states();
+        assert_eq!(flow_states.len(), 9);
+        for i in 0 .. flow_states.len() {
+            assert_eq!(flow_states.p[i], 101325.0);
+            assert_eq!(flow_states.t[i], 300.0);
+            assert_eq!(flow_states.vel_x[i], 500.0);
+            assert_eq!(flow_states.vel_y[i], 0.0);
+            assert!((flow_states.rho[i] - 101325.0 / (287.0 * 300.0)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn reference_dimensions_round_trip() {
+        use common::unit::UnitNum;
+
+        let reference_dimensions = RefDim::new(vec![
+            UnitNum::new(1000., "m/s"), UnitNum::new(1., "kg/m^3"), UnitNum::new(2., "m"),
+        ]);
+        let sim_settings = SimSettings{reference_dimensions, ..SimSettings::default()};
+        let config_toml = toml::to_string(&sim_settings).unwrap();
+        assert!(config_toml.contains("[reference_dimensions]"));
+
+        let read: SimSettings = toml::from_str(&config_toml).unwrap();
+        assert_eq!(read.reference_dimensions(), sim_settings.reference_dimensions());
+    }
+
+    #[test]
+    fn missing_reference_dimensions() {
+        let mut config = toml::Value::try_from(SimSettings::default()).unwrap();
+        config.as_table_mut().unwrap().remove("reference_dimensions");
+
+        let read: SimSettings = config.try_into().unwrap();
+        assert_eq!(read.reference_dimensions(), &RefDim::default());
+        assert_eq!(read.reference_dimensions().length(), 1.0);
+    }
+
+    #[test]
+    fn missing_solver_config() {
+        let mut config = toml::Value::try_from(SimSettings::default()).unwrap();
+        config.as_table_mut().unwrap().remove("solver");
+
+        let read: SimSettings = config.try_into().unwrap();
+        assert_eq!(read.solver(), &SolverConfig::default());
+    }
+
+    #[test]
+    fn set_boundary_condition() {
+        let mut sim_settings = SimSettings::default();
+        sim_settings.set_boundary_condition(BoundaryConditionSpec::slip_wall("wall"));
+        sim_settings.set_boundary_condition(BoundaryConditionSpec::supersonic_outflow("outflow"));
+        sim_settings.set_boundary_condition(BoundaryConditionSpec::pressure_outflow("wall", 1000.0));
+
+        assert_eq!(sim_settings.boundary_conditions(), &[
+            BoundaryConditionSpec::pressure_outflow("wall", 1000.0),
+            BoundaryConditionSpec::supersonic_outflow("outflow"),
+        ]);
+    }
+}
@@ -0,0 +1,195 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use common::DynamicResult;
+use common::number::Real;
+use finite_volume::fluid_block::{FluidBlock, FluidBlockCollection};
+use gas::gas_model::GasModel;
+
+use crate::logging::{Logger, UserLogger};
+use crate::prep::{config_directory, read_sim_settings, PREP_INPUT};
+use crate::progress::SimulationProgress;
+use crate::settings::{AeolusSettings, SolverConfig, TimeIntegrator};
+use crate::status::{CONVERGENCE_FILE, TIMING_FILE};
+use crate::timing::{SolverPhase, TimingReport};
+
+/// Run the simulation prepared in the file structure of `settings`. The copy
+/// of the lua script written by prep is run again for the grids and the
+/// initial condition. The solver stops after `max_iterations` iterations,
+/// at `max_physical_time` if there is one, or once the residual is below
+/// `convergence_tolerance`. The flow is written to a new time directory every
+/// `output_interval` iterations, and when the solver stops, with the residual
+/// and the largest Mach number logged at `Warning` verbosity and above. The residual of every iteration is
+/// written to the [`CONVERGENCE_FILE`], and the time spent in each phase of the
+/// solver to the [`TIMING_FILE`], for `aeolus status` to read while the solver
+/// runs. Both are brought up to date whenever the flow is written. The time
+/// spent in each phase is also logged at the end.
+pub fn run_sim(settings: &AeolusSettings, log: &UserLogger) -> DynamicResult<()> {
+    let file_structure = settings.file_structure();
+    let mut timing = TimingReport::new();
+    let sim_settings = timing.time(SolverPhase::FileIO, || {
+        read_sim_settings(&config_directory(file_structure).join(PREP_INPUT))
+    })?;
+    let solver = sim_settings.solver();
+    let gas_model = sim_settings.gas_model();
+    if solver.time_integrator() != TimeIntegrator::ForwardEuler {
+        return Err("Only the forward_euler time integrator is implemented".into());
+    }
+
+    let fluid_blocks = timing.time(SolverPhase::FileIO, || sim_settings.create_fluid_blocks(log))?;
+    let mut fluid_blocks = FluidBlockCollection::new(fluid_blocks);
+    for fluid_block in fluid_blocks.fluid_blocks_mut() {
+        fluid_block.encode_conserved_variables();
+    }
+
+    let fluid_dir = file_structure.fluid();
+    fs::create_dir_all(fluid_dir)?;
+    let mut convergence = BufWriter::new(File::create(fluid_dir.join(CONVERGENCE_FILE))?);
+    writeln!(convergence, "iteration,residual")?;
+
+    let progress = SimulationProgress::new(solver.max_iterations(), settings.verbosity());
+    let mut iteration = 0;
+    let mut last_write = 0;
+    let mut physical_time = 0.0;
+    while !solver.is_finished(iteration, physical_time) {
+        // the last step is shortened to finish at the maximum physical time
+        let max_dt = solver.max_physical_time().map_or(Real::INFINITY, |max_time| max_time - physical_time);
+        physical_time += step(fluid_blocks.fluid_blocks_mut(), solver, gas_model, max_dt, &mut timing, log)?;
+        iteration += 1;
+        let residual = mass_residual_l2(fluid_blocks.fluid_blocks());
+        timing.time(SolverPhase::FileIO, || writeln!(convergence, "{},{:e}", iteration, residual))?;
+        let converged = residual < solver.convergence_tolerance();
+        if iteration % solver.output_interval() == 0 || converged {
+            progress.clear();
+            log.info(&format!(
+                "iteration {}: residual = {:.4e}, max Mach number = {:.3}",
+                iteration, residual, max_mach_number(fluid_blocks.fluid_blocks())
+            ));
+            let time_since_write = physical_time - fluid_blocks.physical_time();
+            timing.time(SolverPhase::FileIO, || {
+                fluid_blocks.write_fluids_blocks(fluid_dir, time_since_write)
+            })?;
+            write_progress(&mut convergence, &mut timing, fluid_dir)?;
+            last_write = iteration;
+        }
+        progress.update(iteration, residual);
+        if converged {
+            break;
+        }
+    }
+    if iteration > last_write {
+        let time_since_write = physical_time - fluid_blocks.physical_time();
+        timing.time(SolverPhase::FileIO, || {
+            fluid_blocks.write_fluids_blocks(fluid_dir, time_since_write)
+        })?;
+        write_progress(&mut convergence, &mut timing, fluid_dir)?;
+    }
+    progress.finish();
+    log.info(&timing.table());
+    Ok(())
+}
+
+/// Flush the convergence history, and write the time spent in each phase so far
+fn write_progress(convergence: &mut BufWriter<File>, timing: &mut TimingReport, fluid_dir: &Path) -> DynamicResult<()> {
+    timing.time(SolverPhase::FileIO, || convergence.flush())?;
+    fs::write(fluid_dir.join(TIMING_FILE), timing.table())?;
+    Ok(())
+}
+
+/// Advance the flow in every block with the forward Euler method, by the
+/// largest time step which is stable in all of the blocks, up to `max_dt`,
+/// adding the time spent in each phase to `timing`. A warning is logged for
+/// any block with a negative pressure before it is advanced. Returns the time step.
+fn step(fluid_blocks: &mut [FluidBlock], solver: &SolverConfig, gas_model: &dyn GasModel<Real>,
+        max_dt: Real, timing: &mut TimingReport, log: &UserLogger) -> DynamicResult<Real>
+{
+    let dt = timing.time(SolverPhase::ResidualUpdate, || {
+        fluid_blocks.iter()
+            .map(|fluid_block| fluid_block.stable_time_step(solver.cfl()))
+            .fold(Real::INFINITY, Real::min)
+    });
+    if !dt.is_finite() {
+        return Err("There is no stable time step, is there an initial condition?".into());
+    }
+    let dt = dt.min(max_dt);
+
+    for fluid_block in fluid_blocks.iter_mut() {
+        let min_pressure = fluid_block.cells().flow_states().min_pressure();
+        if min_pressure < 0.0 {
+            log.warning(&format!("Negative pressure in block {}: min pressure = {:.4e}", fluid_block.id(), min_pressure));
+        }
+        timing.time(SolverPhase::BoundaryConditions, || fluid_block.apply_pre_reconstruction_boundary_conditions());
+        timing.time(SolverPhase::Reconstruction, || {
+            fluid_block.reconstruct(solver.reconstruction_order().into(), gas_model)
+        })?;
+        timing.time(SolverPhase::BoundaryConditions, || fluid_block.apply_post_reconstruction_boundary_conditions());
+        timing.time(SolverPhase::Flux, || fluid_block.compute_fluxes(solver.flux_scheme().into()));
+        timing.time(SolverPhase::ResidualUpdate, || {
+            fluid_block.apply_flux_to_residuals(dt);
+            if solver.is_axisymmetric() {
+                let source = fluid_block.compute_axisymmetric_source_terms(gas_model);
+                fluid_block.add_source_terms(&source);
+            }
+            fluid_block.update_conserved_variables(dt, gas_model)
+        })?;
+    }
+    Ok(dt)
+}
+
+/// The root mean square of the rate of change of
+/// the mass in the cells of all the blocks
+fn mass_residual_l2(fluid_blocks: &[FluidBlock]) -> Real {
+    let (sum, n_cells) = fluid_blocks.iter()
+        .map(|fluid_block| &fluid_block.cells().residuals().mass)
+        .fold((0.0, 0), |(sum, n_cells), mass| {
+            (sum + mass.iter().map(|rate| rate * rate).sum::<Real>(), n_cells + mass.len())
+        });
+    (sum / n_cells as Real).sqrt()
+}
+
+/// The largest Mach number in any cell of all the blocks
+fn max_mach_number(fluid_blocks: &[FluidBlock]) -> Real {
+    fluid_blocks.iter()
+        .map(|fluid_block| fluid_block.cells().flow_states().max_mach_number())
+        .fold(0.0, Real::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use common::vector3::Vector3;
+    use finite_volume::flow::FlowStates;
+    use gas::flow_state::FlowState;
+    use gas::gas_state::GasState;
+    use gas::ideal_gas::IdealGas;
+    use grid::block::BlockCollection;
+
+    use crate::settings::Verbosity;
+    use super::*;
+
+    #[test]
+    fn step_warns_of_negative_pressure() {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(Path::new("../grid/tests/data/square.su2")).unwrap();
+        let gm = IdealGas::new(287.0, 1.4);
+        let mut fluid_block = FluidBlock::from_grid_block(block_collection.get_block(0), &[], &gm).unwrap();
+        let gas_state = GasState{p: -1000.0, T: 300.0, rho: 0.0116, u: 215250.0, a: 347.2};
+        let flow_state = FlowState::new(gas_state, Vector3{x: 5.0, y: 0.0, z: 0.0});
+        fluid_block.set_flow_states(FlowStates::with_constant(9, &flow_state));
+        fluid_block.encode_conserved_variables();
+        let path = std::env::temp_dir().join("aeolus_negative_pressure.log");
+        let _ = fs::remove_file(&path);
+        let log = UserLogger::with_file(&Verbosity::Warning, &path).unwrap();
+
+        // the step itself may fail, since the state isn't physical
+        let solver = SolverConfig::default();
+        let _ = step(&mut [fluid_block], &solver, &gm, Real::INFINITY, &mut TimingReport::new(), &log);
+        log.flush().unwrap();
+
+        let log_contents = fs::read_to_string(&path).unwrap();
+        assert!(log_contents.starts_with("Warning: Negative pressure in block 0"), "{}", log_contents);
+        fs::remove_file(&path).unwrap();
+    }
+}
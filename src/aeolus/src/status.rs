@@ -0,0 +1,201 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use common::{time_directory_name, DynamicResult};
+use common::number::Real;
+
+use crate::settings::{FileStructure, SimSettings};
+
+/// The convergence history written by the solver to the fluid directory,
+/// with a header line followed by one `iteration,residual` line per iteration
+pub const CONVERGENCE_FILE: &str = "convergence.csv";
+
+/// The [`TimingReport`](crate::timing::TimingReport) table written by the
+/// solver to the fluid directory
+pub const TIMING_FILE: &str = "timing.txt";
+
+/// A snapshot of the progress of a simulation, read from the
+/// files it has written so far
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationStatus {
+    iteration: usize,
+    max_iterations: usize,
+    residual: Real,
+    latest_time_index: Option<usize>,
+    latest_write: Option<SystemTime>,
+    time_per_iteration: Option<Duration>,
+    disk_usage: u64,
+}
+
+impl SimulationStatus {
+    /// Read the status of the simulation from its output files. This
+    /// only reads files, so can be used while the simulation is running.
+    pub fn read(file_structure: &FileStructure) -> DynamicResult<SimulationStatus> {
+        let sim_settings = SimSettings::read_config(file_structure)?;
+        let fluid_dir = file_structure.fluid();
+
+        let convergence_path = fluid_dir.join(CONVERGENCE_FILE);
+        let convergence = fs::read_to_string(&convergence_path)
+            .map_err(|err| format!("Failed to read {}: {}", convergence_path.display(), err))?;
+        let (iteration, residual) = latest_residual(&convergence)
+            .ok_or_else(|| format!("No iterations in {}", convergence_path.display()))?;
+
+        let latest_time_index = file_structure.latest_time_index().ok();
+        let latest_write = latest_time_index
+            .and_then(|index| fs::metadata(file_structure.time_directory(index)).ok())
+            .and_then(|metadata| metadata.modified().ok());
+
+        // the timing file is optional, since it might not have been written yet
+        let time_per_iteration = fs::read_to_string(fluid_dir.join(TIMING_FILE))
+            .ok()
+            .and_then(|timing| total_time(&timing))
+            .filter(|_| iteration > 0)
+            .map(|total| total.div_f64(iteration as f64));
+
+        let disk_usage = directory_size(fluid_dir)? + directory_size(file_structure.grid())?;
+
+        Ok(SimulationStatus {
+            iteration,
+            max_iterations: sim_settings.solver().max_iterations(),
+            residual,
+            latest_time_index,
+            latest_write,
+            time_per_iteration,
+            disk_usage,
+        })
+    }
+
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    pub fn residual(&self) -> Real {
+        self.residual
+    }
+
+    pub fn latest_time_index(&self) -> Option<usize> {
+        self.latest_time_index
+    }
+
+    /// The total size of the grid and fluid files, in bytes
+    pub fn disk_usage(&self) -> u64 {
+        self.disk_usage
+    }
+
+    /// The estimated time until the maximum number of iterations is
+    /// reached, or `None` if the time per iteration isn't known, or
+    /// the estimate is too long to be represented
+    pub fn estimated_time_remaining(&self) -> Option<Duration> {
+        let remaining = self.max_iterations.saturating_sub(self.iteration);
+        self.time_per_iteration
+            .and_then(|time| Duration::try_from_secs_f64(time.as_secs_f64() * remaining as f64).ok())
+    }
+
+    /// A summary of the status, one item per line
+    pub fn summary(&self) -> String {
+        let mut summary = format!("Iteration: {}/{}\n", self.iteration, self.max_iterations);
+        summary.push_str(&format!("Residual: {:.4e}\n", self.residual));
+        match (self.latest_time_index, self.latest_write) {
+            (Some(index), Some(written)) => {
+                let age = SystemTime::now().duration_since(written).unwrap_or_default();
+                summary.push_str(&format!(
+                    "Latest solution: {}, written {} ago\n", time_directory_name(index), format_duration(age)
+                ));
+            }
+            (Some(index), None) => summary.push_str(&format!("Latest solution: {}\n", time_directory_name(index))),
+            (None, _) => summary.push_str("Latest solution: none written\n"),
+        }
+        match self.estimated_time_remaining() {
+            Some(remaining) => summary.push_str(&format!("Estimated time remaining: {}\n", format_duration(remaining))),
+            None => summary.push_str("Estimated time remaining: unknown\n"),
+        }
+        summary.push_str(&format!("Disk usage: {:.1} MB\n", self.disk_usage as f64 / 1e6));
+        summary
+    }
+
+    pub fn print(&self) {
+        print!("{}", self.summary());
+    }
+}
+
+/// The last iteration and residual in the convergence history
+fn latest_residual(convergence: &str) -> Option<(usize, Real)> {
+    let line = convergence.lines().skip(1).filter(|line| !line.trim().is_empty()).last()?;
+    let mut fields = line.split(',').map(str::trim);
+    let iteration = fields.next()?.parse().ok()?;
+    let residual = fields.next()?.parse().ok()?;
+    Some((iteration, residual))
+}
+
+/// The total time from a timing table
+fn total_time(timing: &str) -> Option<Duration> {
+    let line = timing.lines().find(|line| line.starts_with("Total"))?;
+    let seconds: f64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// The total size of the files in a directory and its sub-directories,
+/// or zero if the directory doesn't exist
+fn directory_size(dir: &Path) -> std::io::Result<u64> {
+    if !dir.is_dir() {
+        return Ok(0);
+    }
+    let mut size = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += match metadata.is_dir() {
+            true => directory_size(&entry.path())?,
+            false => metadata.len(),
+        };
+    }
+    Ok(size)
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}h {:0>2}m {:0>2}s", seconds / 3600, (seconds / 60) % 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convergence_history() {
+        let convergence = "iteration,residual\n1,1.0e-1\n2,5.0e-2\n\n";
+
+        assert_eq!(latest_residual(convergence), Some((2, 5.0e-2)));
+        assert_eq!(latest_residual("iteration,residual\n"), None);
+    }
+
+    #[test]
+    fn total_time_from_table() {
+        let mut timing = crate::timing::TimingReport::new();
+        timing.add(crate::timing::SolverPhase::Flux, Duration::from_millis(1500));
+
+        assert_eq!(total_time(&timing.table()), Some(Duration::from_millis(1500)));
+        assert_eq!(total_time(""), None);
+    }
+
+    #[test]
+    fn time_remaining_beyond_u32_iterations() {
+        let status = SimulationStatus {
+            iteration: 1,
+            max_iterations: 10_000_000_001,
+            residual: 1e-3,
+            latest_time_index: None,
+            latest_write: None,
+            time_per_iteration: Some(Duration::from_millis(1)),
+            disk_usage: 0,
+        };
+
+        assert_eq!(status.estimated_time_remaining(), Some(Duration::from_secs(10_000_000)));
+    }
+
+    #[test]
+    fn duration_format() {
+        assert_eq!(format_duration(Duration::from_secs(3725)), "1h 02m 05s");
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// The phases of a solver iteration that are timed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverPhase {
+    BoundaryConditions,
+    Reconstruction,
+    Flux,
+    ResidualUpdate,
+    FileIO,
+}
+
+impl SolverPhase {
+    const ALL: [SolverPhase; 5] = [
+        SolverPhase::BoundaryConditions,
+        SolverPhase::Reconstruction,
+        SolverPhase::Flux,
+        SolverPhase::ResidualUpdate,
+        SolverPhase::FileIO,
+    ];
+
+    fn index(&self) -> usize {
+        match &self {
+            SolverPhase::BoundaryConditions => 0,
+            SolverPhase::Reconstruction => 1,
+            SolverPhase::Flux => 2,
+            SolverPhase::ResidualUpdate => 3,
+            SolverPhase::FileIO => 4,
+        }
+    }
+}
+
+impl std::fmt::Display for SolverPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self {
+            SolverPhase::BoundaryConditions => write!(f, "Boundary conditions"),
+            SolverPhase::Reconstruction => write!(f, "Reconstruction"),
+            SolverPhase::Flux => write!(f, "Flux"),
+            SolverPhase::ResidualUpdate => write!(f, "Residual update"),
+            SolverPhase::FileIO => write!(f, "File I/O"),
+        }
+    }
+}
+
+/// Cumulative wall-clock time spent in each phase of the solver
+#[derive(Debug, Default, Clone)]
+pub struct TimingReport {
+    durations: [Duration; 5],
+}
+
+impl TimingReport {
+    pub fn new() -> TimingReport {
+        TimingReport::default()
+    }
+
+    /// Run `f`, adding the time it takes to `phase`
+    pub fn time<T>(&mut self, phase: SolverPhase, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.add(phase, start.elapsed());
+        result
+    }
+
+    pub fn add(&mut self, phase: SolverPhase, duration: Duration) {
+        self.durations[phase.index()] += duration;
+    }
+
+    pub fn duration(&self, phase: SolverPhase) -> Duration {
+        self.durations[phase.index()]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.durations.iter().sum()
+    }
+
+    /// A table of the time spent in each phase, and the
+    /// percentage of the total time
+    pub fn table(&self) -> String {
+        let total = self.total().as_secs_f64();
+        let mut table = format!("{:<20} {:>12} {:>8}\n", "Phase", "Time (s)", "%");
+        for phase in SolverPhase::ALL {
+            let duration = self.duration(phase).as_secs_f64();
+            let percentage = match total > 0.0 {
+                true => 100.0 * duration / total,
+                false => 0.0,
+            };
+            writeln!(table, "{:<20} {:>12.6} {:>8.2}", phase.to_string(), duration, percentage).unwrap();
+        }
+        writeln!(table, "{:<20} {:>12.6} {:>8.2}", "Total", total, 100.0).unwrap();
+        table
+    }
+
+    pub fn print(&self) {
+        print!("{}", self.table());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulates() {
+        let mut timing = TimingReport::new();
+        timing.add(SolverPhase::Flux, Duration::from_millis(30));
+        timing.add(SolverPhase::Flux, Duration::from_millis(30));
+        timing.add(SolverPhase::FileIO, Duration::from_millis(40));
+
+        assert_eq!(timing.duration(SolverPhase::Flux), Duration::from_millis(60));
+        assert_eq!(timing.duration(SolverPhase::Reconstruction), Duration::ZERO);
+        assert_eq!(timing.total(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn time_returns_result() {
+        let mut timing = TimingReport::new();
+        let result = timing.time(SolverPhase::ResidualUpdate, || {
+            std::thread::sleep(Duration::from_millis(1));
+            42
+        });
+
+        assert_eq!(result, 42);
+        assert!(timing.duration(SolverPhase::ResidualUpdate) >= Duration::from_millis(1));
+    }
+
+    #[test]
+    fn table() {
+        let mut timing = TimingReport::new();
+        timing.add(SolverPhase::BoundaryConditions, Duration::from_millis(250));
+        timing.add(SolverPhase::Flux, Duration::from_millis(750));
+        let table = timing.table();
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[1], "Boundary conditions      0.250000    25.00");
+        assert_eq!(lines[3], "Flux                     0.750000    75.00");
+        assert_eq!(lines[6], "Total                    1.000000   100.00");
+    }
+
+    #[test]
+    fn empty_table() {
+        let timing = TimingReport::new();
+
+        assert!(timing.table().lines().nth(1).unwrap().ends_with("0.00"));
+    }
+}
@@ -0,0 +1,24 @@
+use std::fs;
+use std::path::Path;
+
+use aeolus::settings::AeolusSettings;
+
+/// Settings with all the simulation files in `dir`, which is emptied first
+pub fn settings_in(dir: &Path) -> AeolusSettings {
+    let _ = fs::remove_dir_all(dir);
+    fs::create_dir_all(dir).unwrap();
+    let settings_path = dir.join("settings.toml");
+    fs::write(&settings_path, format!(r#"
+        verbosity = "Error"
+        native_grid_format = "su2"
+
+        [file_structure]
+        config = "{0}/config/config.toml"
+        solver = "{0}/config/solver.toml"
+        discretisation = "{0}/config/discretisation.toml"
+        gas_model = "{0}/config/gas_model.toml"
+        grid = "{0}/solution/grid"
+        fluid = "{0}/solution/fluid"
+    "#, dir.display())).unwrap();
+    AeolusSettings::from_toml_file(&settings_path).unwrap()
+}
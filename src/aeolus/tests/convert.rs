@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use aeolus::convert::convert_grid;
+use grid::block::BlockCollection;
+use grid::Block;
+
+#[test]
+fn su2_to_native_and_back() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let su2 = PathBuf::from("../grid/tests/data/square.su2");
+    let native = dir.join("convert_square.grid");
+    let round_trip = dir.join("convert_square.su2");
+
+    convert_grid(&su2, &native).unwrap();
+    convert_grid(&native, &round_trip).unwrap();
+
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&su2).unwrap();
+    block_collection.add_block(&native).unwrap();
+    block_collection.add_block(&round_trip).unwrap();
+    let original = block_collection.get_block(0);
+    for block in block_collection.blocks()[1..].iter() {
+        assert_eq!(block.vertices(), original.vertices());
+        assert_eq!(block.interfaces(), original.interfaces());
+        assert_eq!(block.cells(), original.cells());
+        assert_eq!(block.boundaries(), original.boundaries());
+    }
+}
+
+#[test]
+fn unknown_output_format() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let su2 = PathBuf::from("../grid/tests/data/square.su2");
+    let unknown = dir.join("convert_square.msh");
+
+    let err = convert_grid(&su2, &unknown).unwrap_err();
+
+    assert!(err.to_string().contains("Unknown extension 'msh'"));
+    assert!(!unknown.exists());
+}
+
+#[test]
+fn unknown_input_format() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let err = convert_grid(&PathBuf::from("square"), &dir.join("square.su2")).unwrap_err();
+
+    assert!(err.to_string().contains("No extension"));
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use aeolus::post::post_process;
+use aeolus::settings::SimSettings;
+use ::common::number::Real;
+use ::common::vector3::Vector3;
+use finite_volume::fluid_block::FluidBlockCollection;
+use gas::flow_state::FlowState;
+use gas::gas_state::GasState;
+use gas::ideal_gas::IdealGas;
+use grid::block::{block_file_name, BlockCollection, GridFileType};
+
+fn linear_pressure(x: Real, _y: Real, _z: Real) -> FlowState<Real> {
+    let gas_state = GasState{p: 1000.0 * x, T: 300.0, ..GasState::default()};
+    FlowState::new(gas_state, Vector3{x: 3.0, y: 4.0, z: 0.0})
+}
+
+#[test]
+fn post_process_latest_time() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("post");
+    let settings = common::settings_in(&dir);
+    let file_structure = settings.file_structure();
+    file_structure.create_directories();
+    SimSettings::default().write_config(file_structure, GridFileType::Native).unwrap();
+
+    // write the solution at two times, to make sure the latest one is used
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+    let gas_model = IdealGas::new(287.1, 1.4);
+    let mut fluid_blocks = FluidBlockCollection::with_variable_initial_condition(&block_collection, linear_pressure, &gas_model).unwrap();
+    fluid_blocks.write_fluids_blocks(file_structure.fluid(), 1e-3).unwrap();
+    fluid_blocks.write_fluids_blocks(file_structure.fluid(), 1e-3).unwrap();
+    // files that look like time directories should be ignored
+    fs::write(file_structure.time_directory(10), "").unwrap();
+
+    let time_index = post_process(file_structure).unwrap();
+    assert_eq!(time_index, 2);
+
+    let time_dir = file_structure.time_directory(2);
+    let csv = fs::read_to_string(time_dir.join(block_file_name(0, "csv"))).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 10);
+    assert_eq!(lines[0], "cell_id,x,y,z,p,rho,T,|v|,M");
+    let cell_1: Vec<Real> = lines[2].split(',').map(|value| value.parse().unwrap()).collect();
+    assert_eq!(cell_1[0 .. 5], [1.0, 1.5, 0.5, 0.0, 1500.0]);
+    assert_eq!(cell_1[7], 5.0);
+    assert!((cell_1[8] - 5.0 / Real::sqrt(1.4 * 287.1 * 300.0)).abs() < 1e-12);
+
+    let summary = fs::read_to_string(time_dir.join("summary.csv")).unwrap();
+    assert!(summary.lines().any(|line| line == "0,p,500,2500,1500"));
+    assert!(!file_structure.time_directory(1).join("summary.csv").exists());
+}
@@ -0,0 +1,65 @@
+//! Prep a simulation, and check the manifest records
+//! every file written along with its hash.
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use aeolus::prep::{prep_sim, Manifest, MANIFEST, PREP_INPUT};
+use aeolus::settings::AeolusSettings;
+
+fn sha256(path: &Path) -> String {
+    let hash = Sha256::digest(fs::read(path).unwrap());
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Prep the square grid in `dir`, returning the settings used
+fn prep_square(dir: &Path) -> AeolusSettings {
+    let settings = common::settings_in(dir);
+
+    let mut prep_file = dir.join("prep.lua");
+    fs::write(&prep_file, format!(r#"
+        config.reference_values = RefDim(
+            UnitNum(1000., "m/s"),
+            UnitNum(1., "kg/m^3"),
+            UnitNum(1., "m")
+        )
+        blks = blocks()
+        blks:add_block("{}/../grid/tests/data/square.su2")
+        config.blocks = blks
+        config.gas_model_type = "ideal_gas"
+        config.gas_model = IdealGas(287, 1.4)
+    "#, env!("CARGO_MANIFEST_DIR"))).unwrap();
+    prep_sim(&mut prep_file, &settings).unwrap();
+    settings
+}
+
+#[test]
+fn manifest_hashes_match_files() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("prep_manifest");
+    prep_square(&dir);
+    let manifest = Manifest::read(&dir.join("config").join(MANIFEST)).unwrap();
+
+    assert_eq!(manifest.aeolus_version, env!("CARGO_PKG_VERSION"));
+    let config_dir = dir.join("config");
+    for path in [config_dir.join("config.toml"), config_dir.join("gas_model.toml"), config_dir.join(PREP_INPUT)] {
+        assert!(manifest.files.contains_key(&path.display().to_string()), "{} is missing", path.display());
+    }
+    assert!(manifest.files.keys().any(|path| path.ends_with("block_0000.su2")));
+    for (path, hash) in manifest.files.iter() {
+        assert_eq!(hash, &sha256(Path::new(path)), "{} has changed", path);
+    }
+}
+
+#[test]
+fn prep_input_is_copied() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("prep_input");
+    prep_square(&dir);
+
+    let original = fs::read_to_string(dir.join("prep.lua")).unwrap();
+    let copy = fs::read_to_string(dir.join("config").join(PREP_INPUT)).unwrap();
+    assert_eq!(copy, original);
+}
@@ -0,0 +1,121 @@
+//! Prep and run a simulation of a flow trapped in a box
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use aeolus::logging::UserLogger;
+use aeolus::prep::prep_sim;
+use aeolus::settings::{AeolusSettings, Verbosity};
+use aeolus::solver::run_sim;
+use aeolus::status::SimulationStatus;
+use finite_volume::fluid_block::read_physical_time;
+
+/// Prep the square grid in `dir`, with a uniform flow that the
+/// boundaries, which have no boundary conditions, reflect
+fn prep_square(dir: &Path, solver: &str) -> AeolusSettings {
+    let settings = common::settings_in(dir);
+
+    let mut prep_file = dir.join("prep.lua");
+    fs::write(&prep_file, format!(r#"
+        config.reference_values = RefDim(
+            UnitNum(1000., "m/s"),
+            UnitNum(1., "kg/m^3"),
+            UnitNum(1., "m")
+        )
+        blks = blocks()
+        blks:add_block("{}/../grid/tests/data/square.su2")
+        config.blocks = blks
+        config.gas_model_type = "ideal_gas"
+        config.gas_model = IdealGas(287, 1.4)
+        config.initial_condition = {{type = "uniform", p = 101325, T = 300, vel_x = 50}}
+        config.solver = {}
+    "#, env!("CARGO_MANIFEST_DIR"), solver)).unwrap();
+    prep_sim(&mut prep_file, &settings).unwrap();
+    settings
+}
+
+#[test]
+fn run_writes_every_output_interval() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("run_output_interval");
+    let settings = prep_square(&dir, "{max_iterations = 5, output_interval = 2, convergence_tolerance = 1e-12}");
+    let log = UserLogger::with_verbosity(settings.verbosity());
+
+    run_sim(&settings, &log).unwrap();
+
+    // written after iterations 2 and 4, then when the solver stops after 5
+    let file_structure = settings.file_structure();
+    assert_eq!(file_structure.latest_time_index().unwrap(), 3);
+    let times: Vec<f64> = (1 ..= 3)
+        .map(|index| read_physical_time(&file_structure.time_directory(index)).unwrap())
+        .collect();
+    assert!(times[0] > 0.0);
+    assert!(times[0] < times[1] && times[1] < times[2]);
+}
+
+#[test]
+fn run_stops_when_converged() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("run_converged");
+    let settings = prep_square(&dir, "{max_iterations = 100, output_interval = 1000, convergence_tolerance = 1e10}");
+    let log = UserLogger::with_verbosity(settings.verbosity());
+
+    run_sim(&settings, &log).unwrap();
+
+    assert_eq!(settings.file_structure().latest_time_index().unwrap(), 1);
+}
+
+#[test]
+fn run_logs_residual_every_output_interval() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("run_log");
+    let settings = prep_square(&dir, "{max_iterations = 5, output_interval = 2, convergence_tolerance = 1e-12}");
+    let log_path = dir.join("run.log");
+    let log = UserLogger::with_file(&Verbosity::Warning, &log_path).unwrap();
+
+    run_sim(&settings, &log).unwrap();
+    log.flush().unwrap();
+
+    let log_contents = fs::read_to_string(&log_path).unwrap();
+    let iterations: Vec<&str> = log_contents
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(iteration, _)| iteration)
+        .filter(|iteration| iteration.starts_with("iteration"))
+        .collect();
+    assert_eq!(iterations, ["iteration 2", "iteration 4"]);
+    assert!(log_contents.lines().filter(|line| line.starts_with("iteration")).all(|line| line.contains("max Mach number")));
+}
+
+#[test]
+fn status_of_finished_run() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("run_status");
+    let settings = prep_square(&dir, "{max_iterations = 5, output_interval = 2, convergence_tolerance = 1e-12}");
+    let log = UserLogger::with_verbosity(settings.verbosity());
+
+    run_sim(&settings, &log).unwrap();
+
+    let status = SimulationStatus::read(settings.file_structure()).unwrap();
+    assert_eq!(status.iteration(), 5);
+    assert!(status.residual() > 0.0);
+    assert_eq!(status.latest_time_index(), Some(3));
+    assert_eq!(status.estimated_time_remaining(), Some(Duration::ZERO));
+}
+
+#[test]
+fn run_stops_at_max_physical_time() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("run_max_physical_time");
+    let settings = prep_square(
+        &dir, "{max_iterations = 1000, output_interval = 1000, convergence_tolerance = 1e-12, max_physical_time = 1e-2}"
+    );
+    let log = UserLogger::with_verbosity(settings.verbosity());
+
+    run_sim(&settings, &log).unwrap();
+
+    let file_structure = settings.file_structure();
+    assert_eq!(file_structure.latest_time_index().unwrap(), 1);
+    let physical_time = read_physical_time(&file_structure.time_directory(1)).unwrap();
+    assert!((physical_time - 1e-2).abs() < 1e-15, "stopped at {}", physical_time);
+    let status = SimulationStatus::read(file_structure).unwrap();
+    assert!(status.iteration() > 1 && status.iteration() < 1000);
+}
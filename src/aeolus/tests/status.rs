@@ -0,0 +1,54 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use aeolus::settings::{AeolusSettings, SimSettings};
+use aeolus::status::{SimulationStatus, CONVERGENCE_FILE, TIMING_FILE};
+use aeolus::timing::{SolverPhase, TimingReport};
+use finite_volume::fluid_block_io::FLOW_EXTENSION;
+use grid::block::{block_file_name, GridFileType};
+
+fn settings_with_config(dir: &Path) -> AeolusSettings {
+    let settings = common::settings_in(dir);
+    settings.file_structure().create_directories();
+    SimSettings::default().write_config(settings.file_structure(), GridFileType::Native).unwrap();
+    settings
+}
+
+#[test]
+fn status_of_running_simulation() {
+    let settings = settings_with_config(&PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("status"));
+    let file_structure = settings.file_structure();
+    let max_iterations = SimSettings::default().solver().max_iterations();
+
+    let fluid_dir = file_structure.fluid();
+    fs::create_dir_all(fluid_dir).unwrap();
+    fs::write(fluid_dir.join(CONVERGENCE_FILE), "iteration,residual\n1,0.1\n2,0.05\n4,0.01\n").unwrap();
+    let mut timing = TimingReport::new();
+    timing.add(SolverPhase::Flux, std::time::Duration::from_secs(8));
+    fs::write(fluid_dir.join(TIMING_FILE), timing.table()).unwrap();
+    for time_index in [0, 1] {
+        let time_dir = file_structure.time_directory(time_index);
+        fs::create_dir_all(&time_dir).unwrap();
+        fs::write(time_dir.join(block_file_name(0, FLOW_EXTENSION)), vec![0u8; 100]).unwrap();
+    }
+
+    let status = SimulationStatus::read(file_structure).unwrap();
+    assert_eq!(status.iteration(), 4);
+    assert_eq!(status.residual(), 0.01);
+    assert_eq!(status.latest_time_index(), Some(1));
+    assert!(status.disk_usage() >= 200);
+    let remaining = status.estimated_time_remaining().unwrap();
+    assert_eq!(remaining.as_secs() as usize, 2 * (max_iterations - 4));
+    let summary = status.summary();
+    assert!(summary.starts_with(&format!("Iteration: 4/{}\n", max_iterations)));
+    assert!(summary.contains("Latest solution: t0001"));
+}
+
+#[test]
+fn status_before_first_iteration() {
+    let settings = settings_with_config(&PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("status_not_started"));
+
+    assert!(SimulationStatus::read(settings.file_structure()).is_err());
+}
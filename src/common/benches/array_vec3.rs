@@ -0,0 +1,104 @@
+//! Benchmark the `ArrayVec3` operations used in the solver's hot loops,
+//! on 10,000 and 100,000 vectors. Throughput is reported in elements per
+//! second, so a regression shows up as a drop in Melem/s.
+//!
+//! At 100,000 vectors the transforms are limited by memory bandwidth rather
+//! than arithmetic: twelve arrays of 100,000 f64's don't fit in cache, and the
+//! compiler already vectorises the scalar loop. An explicit avx2 path made no
+//! measurable difference, so there isn't one. Each transform ran at about
+//! 470 Melem/s for 10,000 vectors and 240 Melem/s for 100,000, normalising
+//! at about 300 Melem/s and scaling at about 1 Gelem/s.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use common::number::Real;
+use common::vector3::{ArrayVec3, Vector3};
+
+const SIZES: [usize; 2] = [10_000, 100_000];
+
+fn vector3s(n: usize, f: impl Fn(Real) -> Vector3) -> Vec<Vector3> {
+    (0 .. n).map(|i| f(i as Real / n as Real)).collect()
+}
+
+fn array_vec(n: usize, f: impl Fn(Real) -> Vector3) -> ArrayVec3 {
+    ArrayVec3::from_vector3s(&vector3s(n, f))
+}
+
+fn velocity(s: Real) -> Vector3 {
+    Vector3{x: 100.0 * s, y: 1.0 - s, z: 0.5}
+}
+
+/// A different frame for each interface, rotated about the z axis
+fn frames(n: usize) -> (ArrayVec3, ArrayVec3, ArrayVec3) {
+    let angle = |s: Real| 2.0 * std::f64::consts::PI * s;
+    let norm = array_vec(n, |s| Vector3{x: angle(s).cos(), y: angle(s).sin(), z: 0.0});
+    let t1 = array_vec(n, |s| Vector3{x: -angle(s).sin(), y: angle(s).cos(), z: 0.0});
+    let t2 = array_vec(n, |_| Vector3{x: 0.0, y: 0.0, z: 1.0});
+    (norm, t1, t2)
+}
+
+fn transform(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transform_to_local_frame");
+    for n in SIZES {
+        let (norm, t1, t2) = frames(n);
+        let mut velocities = array_vec(n, velocity);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            b.iter(|| velocities.transform_to_local_frame(black_box(&norm), &t1, &t2))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("transform_to_global_frame");
+    for n in SIZES {
+        let (norm, t1, t2) = frames(n);
+        let mut velocities = array_vec(n, velocity);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            b.iter(|| velocities.transform_to_global_frame(black_box(&norm), &t1, &t2))
+        });
+    }
+    group.finish();
+}
+
+fn normalise_in_place(c: &mut Criterion) {
+    let mut group = c.benchmark_group("normalise_in_place");
+    for n in SIZES {
+        let mut velocities = array_vec(n, velocity);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            // after the first iteration the vectors are already unit length,
+            // but the work is the same
+            b.iter(|| velocities.normalise_in_place())
+        });
+    }
+    group.finish();
+}
+
+fn scale_in_place(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scale_in_place");
+    for n in SIZES {
+        let mut velocities = array_vec(n, velocity);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            // scaling by -1 keeps the values bounded however many iterations run
+            b.iter(|| velocities.scale_in_place(black_box(-1.0)))
+        });
+    }
+    group.finish();
+}
+
+fn from_vector3s(c: &mut Criterion) {
+    let mut group = c.benchmark_group("from_vector3s");
+    for n in SIZES {
+        let velocities = vector3s(n, velocity);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            b.iter(|| ArrayVec3::from_vector3s(black_box(&velocities)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, transform, normalise_in_place, scale_in_place, from_vector3s);
+criterion_main!(benches);
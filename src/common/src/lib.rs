@@ -7,3 +7,9 @@ pub mod unit;
 /// Short hand for returning a result with some generic `Ok` type
 /// and a dynamic `Err` type
 pub type DynamicResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// The name of the directory the grid or flow is written to
+/// for a time index, e.g. t0012
+pub fn time_directory_name(index: usize) -> String {
+    format!("t{:04}", index)
+}
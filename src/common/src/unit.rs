@@ -54,6 +54,19 @@ impl UnitNum {
     pub fn set_value(&mut self, new_value: Real) {
         self.value = new_value;
     }
+
+    /// Whether the units are the same, and the values
+    /// differ by no more than `tol`
+    pub fn approx_eq(&self, other: &UnitNum, tol: Real) -> bool {
+        self.unit == other.unit && (self.value - other.value).abs() <= tol
+    }
+
+    /// Whether the units are the same, and the values differ by no
+    /// more than `rtol` times the larger of their magnitudes
+    pub fn relative_eq(&self, other: &UnitNum, rtol: Real) -> bool {
+        let scale = self.value.abs().max(other.value.abs());
+        self.unit == other.unit && (self.value - other.value).abs() <= rtol * scale
+    }
 }
 
 impl UserData for UnitNum {}
@@ -111,41 +124,45 @@ impl FromStr for Unit {
         let mut unit = s;
         let mut unit_rep: UnitBase = [0, 0, 0, 0];
 
-        let mut sign = 1;
-        let mut pow;
+        let error = || UnitParseError{unit: s.to_string()};
+        let mut sign: i8 = 1;
         loop {
-            if unit.starts_with("kg"){
-                unit = &unit[2..];
-                (unit, pow) = read_and_remove_power(unit);
-                unit_rep[0] += sign * pow;
-            }
-            else if unit.starts_with('m') {
-                unit = &unit[1..];
-                (unit, pow) = read_and_remove_power(unit);
-                unit_rep[1] += sign * pow;
-            }
-            else if unit.starts_with('s') {
-                unit = &unit[1..];
-                (unit, pow) = read_and_remove_power(unit);
-                unit_rep[2] += sign * pow;
-            }
-            else if unit.starts_with('K') {
-                unit = &unit[1..];
-                (unit, pow) = read_and_remove_power(unit);
-                unit_rep[3] += sign * pow;
-            }
-            else if unit.starts_with('*') {
+            if let Some(rest) = unit.strip_prefix('*') {
                 sign = 1;
-                unit = &unit[1..];
+                unit = rest;
             }
-            else if unit.starts_with('/') {
+            else if let Some(rest) = unit.strip_prefix('/') {
                 sign = -1;
-                unit = &unit[1..];
+                unit = rest;
             }
             else {
-                return Err(UnitParseError{unit: s.to_string()});
+                let index = if let Some(rest) = unit.strip_prefix("kg") {
+                    unit = rest;
+                    0
+                }
+                else if let Some(rest) = unit.strip_prefix('m') {
+                    unit = rest;
+                    1
+                }
+                else if let Some(rest) = unit.strip_prefix('s') {
+                    unit = rest;
+                    2
+                }
+                else if let Some(rest) = unit.strip_prefix('K') {
+                    unit = rest;
+                    3
+                }
+                else {
+                    return Err(error());
+                };
+                let pow;
+                (unit, pow) = read_and_remove_power(unit).ok_or_else(error)?;
+                // powers too large to store are an error, rather than wrapping around
+                unit_rep[index] = sign.checked_mul(pow)
+                    .and_then(|pow| unit_rep[index].checked_add(pow))
+                    .ok_or_else(error)?;
             }
-            if unit.chars().count() == 0 {
+            if unit.is_empty() {
                 break;
             }
         }
@@ -154,26 +171,30 @@ impl FromStr for Unit {
     }
 }
 
-fn first_char_to_i8(unit: &str) -> i8 {
-    unit.chars()
-        .next()
-        .unwrap()
-        .to_string()
-        .parse()
-        .unwrap()
+/// Read the integer at the start of `unit`, which may be negative, returning
+/// the rest of the string and the integer. `None` if there isn't an integer,
+/// or it doesn't fit in an i8.
+fn leading_i8(unit: &str) -> Option<(&str, i8)> {
+    let digits_start = if unit.starts_with('-') { 1 } else { 0 };
+    let end = unit[digits_start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(unit.len(), |end| end + digits_start);
+    let value = unit[..end].parse().ok()?;
+    Some((&unit[end..], value))
 }
 
-fn read_and_remove_power(mut unit: &str) -> (&str, i8) {
-    if unit.starts_with('^') {
-        unit = &unit[1..]; 
-        let pow = first_char_to_i8(unit);
-        unit = &unit[1..];
-        return (unit, pow);
+/// Read the power following a `^`, if there is one, returning the rest of
+/// the string and the power. `None` if the power can't be read.
+fn read_and_remove_power(unit: &str) -> Option<(&str, i8)> {
+    match unit.strip_prefix('^') {
+        Some(power) => leading_i8(power),
+        None => Some((unit, 1)),
     }
-    (unit, 1)
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+/// The reference mass, length, time and temperature used to non-dimensionalise
+/// the flow. In config files this is a `[reference_dimensions]` table of floats.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RefDim {
     ref_mass: Real,
     ref_length: Real,
@@ -181,6 +202,13 @@ pub struct RefDim {
     ref_temp: Real,
 }
 
+/// Every reference dimension is one, so nothing is scaled
+impl Default for RefDim {
+    fn default() -> Self {
+        RefDim { ref_mass: 1.0, ref_length: 1.0, ref_time: 1.0, ref_temp: 1.0 }
+    }
+}
+
 impl RefDim {
     pub fn new(reference_values: Vec<UnitNum>) -> RefDim {
         let (included_units, n_units) = RefDim::count_units(&reference_values);
@@ -232,6 +260,10 @@ impl RefDim {
         self.ref_mass / self.ref_length.powi(3)
     }
 
+    pub fn pressure(&self) -> Real {
+        self.density() * self.velocity().powi(2)
+    }
+
     pub fn viscosity(&self) -> Real {
         self.ref_length * self.velocity()
     }
@@ -275,6 +307,21 @@ mod tests {
         assert_eq!(unit, Err(UnitParseError{unit: "kg/m/d".to_string()}));
     }
 
+    #[test]
+    fn unit_from_string_multi_digit_powers() {
+        assert_eq!(Unit::from_str("kg^10"), Ok(Unit([10, 0, 0, 0])));
+        assert_eq!(Unit::from_str("m*s^-2"), Ok(Unit([0, 1, -2, 0])));
+        assert_eq!(Unit::from_str("m^-1"), Ok(Unit([0, -1, 0, 0])));
+        assert_eq!(Unit::from_str("kg/m^-12*K^127"), Ok(Unit([1, 12, 0, 127])));
+    }
+
+    #[test]
+    fn unit_from_string_invalid_powers() {
+        for unit in ["m^128", "m^99*m^99", "s/s^-128", "m^", "m^-", "m^a", "m^2.5"] {
+            assert_eq!(Unit::from_str(unit), Err(UnitParseError{unit: unit.to_string()}), "{}", unit);
+        }
+    }
+
     #[test]
     fn add_unit_nums() {
         let num1 = UnitNum::new(1., "kg/m/s");
@@ -341,6 +388,16 @@ mod tests {
         assert!((ref_dim.time() - 6.0) < 1e-13);
     }
 
+    #[test]
+    fn ref_dim_pressure() {
+        let length = UnitNum::new(6., "m");
+        let velocity = UnitNum::new(3., "m/s");
+        let density = UnitNum::new(2., "kg/m^3");
+        let ref_dim = RefDim::new(vec![length, velocity, density]);
+
+        assert!((ref_dim.pressure() - 18.0).abs() < 1e-12);
+    }
+
     #[test]
     fn ref_dim_temp() {
         let mass = UnitNum::new(6., "kg");
@@ -372,5 +429,34 @@ mod tests {
 
         let _ref_dim = RefDim::new(vec![density, velocity, length, mass]);
     }
-}
 
+    #[test]
+    fn approx_eq() {
+        let num = UnitNum::new(1.0, "kg/m^3");
+
+        assert!(num.approx_eq(&UnitNum::new(1.0, "kg/m^3"), 0.0));
+        assert!(num.approx_eq(&UnitNum::new(1.0 + 1e-10, "kg/m^3"), 1e-9));
+        assert!(!num.approx_eq(&UnitNum::new(1.0 + 1e-8, "kg/m^3"), 1e-9));
+        assert!(!num.approx_eq(&UnitNum::new(1.0, "kg/m^2"), 1e-9));
+    }
+
+    #[test]
+    fn relative_eq() {
+        let num = UnitNum::new(1e5, "kg/m/s^2");
+
+        assert!(num.relative_eq(&UnitNum::new(1e5, "kg/m/s^2"), 0.0));
+        assert!(num.relative_eq(&UnitNum::new(1e5 + 1e-6, "kg/m/s^2"), 1e-10));
+        assert!(!num.relative_eq(&UnitNum::new(1e5 + 1.0, "kg/m/s^2"), 1e-10));
+        assert!(!num.relative_eq(&UnitNum::new(1e5, "kg/m/s"), 1e-10));
+    }
+
+    #[test]
+    fn dimensionless_approx_eq() {
+        let mach = UnitNum{value: 2.0, unit: Unit::default()};
+        let ratio = UnitNum::new(4.0, "m/s") / UnitNum::new(2.0, "m/s");
+
+        assert!(mach.approx_eq(&ratio, 1e-12));
+        assert!(mach.relative_eq(&ratio, 1e-12));
+        assert!(!mach.approx_eq(&UnitNum{value: 2.1, unit: Unit::default()}, 1e-12));
+    }
+}
@@ -4,7 +4,7 @@ use crate::number::Real;
 use std::ops;
 
 /// A generic 3 dimensional vector
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 pub struct Vector3 {
     /// The x component
     pub x: Real,
@@ -116,6 +116,68 @@ impl ops::Sub for &Vector3 {
     }
 }
 
+impl ops::Mul<Real> for &Vector3 {
+    type Output = Vector3;
+
+    fn mul(self, factor: Real) -> Vector3 {
+        Vector3 {
+            x: self.x * factor,
+            y: self.y * factor,
+            z: self.z * factor,
+        }
+    }
+}
+
+impl ops::Mul<Real> for Vector3 {
+    type Output = Vector3;
+
+    fn mul(mut self, factor: Real) -> Vector3 {
+        self.scale_in_place(factor);
+        self
+    }
+}
+
+impl ops::Mul<&Vector3> for Real {
+    type Output = Vector3;
+
+    fn mul(self, vector: &Vector3) -> Vector3 {
+        vector * self
+    }
+}
+
+impl ops::Mul<Vector3> for Real {
+    type Output = Vector3;
+
+    fn mul(self, vector: Vector3) -> Vector3 {
+        vector * self
+    }
+}
+
+// dividing a number by a vector doesn't mean anything,
+// so only the vector can be on the left
+impl ops::Div<Real> for &Vector3 {
+    type Output = Vector3;
+
+    fn div(self, divisor: Real) -> Vector3 {
+        Vector3 {
+            x: self.x / divisor,
+            y: self.y / divisor,
+            z: self.z / divisor,
+        }
+    }
+}
+
+impl ops::Div<Real> for Vector3 {
+    type Output = Vector3;
+
+    fn div(mut self, divisor: Real) -> Vector3 {
+        self.x /= divisor;
+        self.y /= divisor;
+        self.z /= divisor;
+        self
+    }
+}
+
 impl PartialEq for Vector3 {
     fn eq(&self, other: &Self) -> bool {
         let tol = 1e-14;
@@ -137,6 +199,24 @@ pub struct ArrayVec3 {
 }
 
 impl ArrayVec3 {
+    /// An empty array with room for `capacity` vectors
+    pub fn with_capacity(capacity: usize) -> ArrayVec3 {
+        ArrayVec3 {
+            x: Vec::with_capacity(capacity),
+            y: Vec::with_capacity(capacity),
+            z: Vec::with_capacity(capacity),
+            len: 0,
+        }
+    }
+
+    /// Append a vector to the end of the array
+    pub fn push(&mut self, vector: &Vector3) {
+        self.x.push(vector.x);
+        self.y.push(vector.y);
+        self.z.push(vector.z);
+        self.len += 1;
+    }
+
     pub fn from_vector3s(vector3s: &[Vector3]) -> ArrayVec3 {
         // allocate memory
         let capacity = vector3s.len();
@@ -157,6 +237,10 @@ impl ArrayVec3 {
         self.len
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     pub fn scale_in_place(&mut self, factor: Real) {
         for i in 0 .. self.x.len() {
             self.x[i] *= factor;
@@ -174,6 +258,42 @@ impl ArrayVec3 {
         }
     }
 
+    /// The smallest x component. The minimum of each component is
+    /// infinity for an empty array, and the maximum is negative infinity.
+    pub fn x_min(&self) -> Real {
+        self.x.iter().cloned().fold(Real::INFINITY, Real::min)
+    }
+
+    pub fn x_max(&self) -> Real {
+        self.x.iter().cloned().fold(Real::NEG_INFINITY, Real::max)
+    }
+
+    pub fn y_min(&self) -> Real {
+        self.y.iter().cloned().fold(Real::INFINITY, Real::min)
+    }
+
+    pub fn y_max(&self) -> Real {
+        self.y.iter().cloned().fold(Real::NEG_INFINITY, Real::max)
+    }
+
+    pub fn z_min(&self) -> Real {
+        self.z.iter().cloned().fold(Real::INFINITY, Real::min)
+    }
+
+    pub fn z_max(&self) -> Real {
+        self.z.iter().cloned().fold(Real::NEG_INFINITY, Real::max)
+    }
+
+    /// The length of the longest vector, or zero if there are no vectors
+    pub fn magnitude_max(&self) -> Real {
+        let mut max_squared: Real = 0.0;
+        for i in 0 .. self.x.len() {
+            max_squared = max_squared.max(self.x[i]*self.x[i] + self.y[i]*self.y[i] + self.z[i]*self.z[i]);
+        }
+        Real::sqrt(max_squared)
+    }
+
+    /// Rotate each vector into the frame with basis vectors `n`, `t1` and `t2`
     pub fn transform_to_local_frame(&mut self, n: &Self, t1: &Self, t2: &Self) {
         for i in 0 .. self.x.len() {
             let x = self.x[i]*n.x[i]  + self.y[i]*n.y[i]  + self.z[i]*n.z[i];
@@ -185,6 +305,7 @@ impl ArrayVec3 {
         }
     }
 
+    /// Rotate each vector out of the frame with basis vectors `n`, `t1` and `t2`
     pub fn transform_to_global_frame(&mut self, n: &Self, t1: &Self, t2: &Self) {
         for i in 0 .. self.x.len() {
             let x = self.x[i]*n.x[i] + self.y[i]*t1.x[i] + self.z[i]*t2.x[i];
@@ -197,6 +318,13 @@ impl ArrayVec3 {
     }
 }
 
+/// The (min, max) corners of the smallest axis aligned box containing `vectors`
+pub fn bounding_box(vectors: &ArrayVec3) -> (Vector3, Vector3) {
+    let min = Vector3{x: vectors.x_min(), y: vectors.y_min(), z: vectors.z_min()};
+    let max = Vector3{x: vectors.x_max(), y: vectors.y_max(), z: vectors.z_max()};
+    (min, max)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,6 +359,25 @@ mod tests {
         assert_eq!(vec, Vector3{x: 0.5, y: 1.0, z: 1.5});
     }
 
+    #[test]
+    fn mul_scalar() {
+        let vec = Vector3{x: 1.0, y: 2.0, z: 3.0};
+
+        assert_eq!(2.0 * vec, Vector3{x: 2.0, y: 4.0, z: 6.0});
+        assert_eq!(2.0 * &vec, Vector3{x: 2.0, y: 4.0, z: 6.0});
+        assert_eq!(vec * 0.5, Vector3{x: 0.5, y: 1.0, z: 1.5});
+        assert_eq!(&vec * 3.0, Vector3{x: 3.0, y: 6.0, z: 9.0});
+        assert_eq!(&vec * 0.0, Vector3{x: 0.0, y: 0.0, z: 0.0});
+    }
+
+    #[test]
+    fn div_scalar() {
+        let vec = Vector3{x: 1.0, y: 2.0, z: 3.0};
+
+        assert_eq!(vec / 1.0, vec);
+        assert_eq!(&vec / 2.0, Vector3{x: 0.5, y: 1.0, z: 1.5});
+    }
+
     #[test]
     fn normalise() {
         let mut vec = Vector3{x: 1.0, y: 2.0, z: 3.0};
@@ -369,6 +516,48 @@ mod tests {
         assert_eq!(array_vec.z, vec![0.0, 0.0, 0.0]);
     }
 
+    #[test]
+    fn array_vec_push() {
+        let mut array_vec = ArrayVec3::with_capacity(2);
+        assert!(array_vec.is_empty());
+        array_vec.push(&Vector3{x: 1.0, y: 0.0, z: 0.0});
+        array_vec.push(&Vector3{x: 1.0, y: 1.0, z: 0.0});
+        array_vec.push(&Vector3{x: 0.0, y: 1.0, z: 0.0});
+        let expected = create_array_vec();
+
+        assert_eq!(array_vec.len(), 3);
+        assert_eq!(array_vec.x, expected.x);
+        assert_eq!(array_vec.y, expected.y);
+        assert_eq!(array_vec.z, expected.z);
+    }
+
+    #[test]
+    fn array_vec_min_max() {
+        let array_vec = ArrayVec3::from_vector3s(&[
+            Vector3{x: 1.0, y: -2.0, z: 0.5},
+            Vector3{x: -3.0, y: 4.0, z: 0.0},
+            Vector3{x: 2.0, y: 1.0, z: -1.5},
+        ]);
+
+        assert_eq!((array_vec.x_min(), array_vec.x_max()), (-3.0, 2.0));
+        assert_eq!((array_vec.y_min(), array_vec.y_max()), (-2.0, 4.0));
+        assert_eq!((array_vec.z_min(), array_vec.z_max()), (-1.5, 0.5));
+        assert_eq!(bounding_box(&array_vec), (
+            Vector3{x: -3.0, y: -2.0, z: -1.5},
+            Vector3{x: 2.0, y: 4.0, z: 0.5},
+        ));
+        assert_eq!(array_vec.magnitude_max(), 5.0);
+    }
+
+    #[test]
+    fn array_vec_min_max_empty() {
+        let array_vec = ArrayVec3::from_vector3s(&[]);
+
+        assert_eq!(array_vec.x_min(), Real::INFINITY);
+        assert_eq!(array_vec.z_max(), Real::NEG_INFINITY);
+        assert_eq!(array_vec.magnitude_max(), 0.0);
+    }
+
     #[test]
     fn array_vec_transform_to_local_frame() {
         let mut array_vec = create_array_vec();
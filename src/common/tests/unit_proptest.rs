@@ -0,0 +1,87 @@
+//! Property based tests of the arithmetic of `UnitNum`, over units with
+//! exponents of each base unit between -4 and 4.
+
+use proptest::prelude::*;
+
+use common::number::Real;
+use common::unit::{UnitBase, UnitNum};
+
+const BASE_UNITS: [&str; 4] = ["kg", "m", "s", "K"];
+
+/// The unit string with the given exponents, e.g. [1, -2, 0, 0] -> "*kg^1/m^2*s^0*K^0"
+fn unit_string(exponents: UnitBase) -> String {
+    let mut unit = String::new();
+    for (base_unit, exponent) in BASE_UNITS.iter().zip(exponents) {
+        let sign = if exponent < 0 { '/' } else { '*' };
+        unit.push_str(&format!("{}{}^{}", sign, base_unit, exponent.abs()));
+    }
+    unit
+}
+
+fn unit_num(value: Real, exponents: UnitBase) -> UnitNum {
+    UnitNum::new(value, &unit_string(exponents))
+}
+
+fn exponents() -> impl Strategy<Value = UnitBase> {
+    [-4i8 ..= 4, -4i8 ..= 4, -4i8 ..= 4, -4i8 ..= 4]
+}
+
+/// Pairs of units which are the same about half of the time,
+/// so that `prop_assume!` doesn't reject most of the cases
+fn unit_pairs() -> impl Strategy<Value = (UnitBase, UnitBase)> {
+    exponents().prop_flat_map(|a| (Just(a), prop_oneof![Just(a), exponents()]))
+}
+
+/// Values away from zero, so dividing by them is well conditioned
+fn value() -> impl Strategy<Value = Real> {
+    prop_oneof![-1e6 .. -1e-6, 1e-6 .. 1e6]
+}
+
+fn close(a: Real, b: Real) -> bool {
+    (a - b).abs() <= 1e-12 * a.abs().max(b.abs())
+}
+
+proptest! {
+    #[test]
+    fn mul_then_div_is_identity(a in value(), a_unit in exponents(), b in value(), b_unit in exponents()) {
+        let result = unit_num(a, a_unit) * unit_num(b, b_unit) / unit_num(b, b_unit);
+
+        prop_assert!(close(result.value, a), "{} != {}", result.value, a);
+        prop_assert_eq!(**result.unit(), a_unit);
+    }
+
+    #[test]
+    fn add_commutes(a in value(), b in value(), (a_unit, b_unit) in unit_pairs()) {
+        prop_assume!(a_unit == b_unit);
+        let a_plus_b = unit_num(a, a_unit) + unit_num(b, b_unit);
+        let b_plus_a = unit_num(b, b_unit) + unit_num(a, a_unit);
+
+        prop_assert_eq!(a_plus_b, b_plus_a);
+    }
+
+    #[test]
+    fn mul_adds_exponents(a in value(), a_unit in exponents(), b in value(), b_unit in exponents()) {
+        let result = unit_num(a, a_unit) * unit_num(b, b_unit);
+
+        let expected: UnitBase = std::array::from_fn(|i| a_unit[i] + b_unit[i]);
+        prop_assert_eq!(**result.unit(), expected);
+        prop_assert!(close(result.value, a * b));
+    }
+
+    #[test]
+    fn div_subtracts_exponents(a in value(), a_unit in exponents(), b in value(), b_unit in exponents()) {
+        let result = unit_num(a, a_unit) / unit_num(b, b_unit);
+
+        let expected: UnitBase = std::array::from_fn(|i| a_unit[i] - b_unit[i]);
+        prop_assert_eq!(**result.unit(), expected);
+        prop_assert!(close(result.value, a / b));
+    }
+
+    #[test]
+    fn add_incompatible_units_panics(a in value(), a_unit in exponents(), b in value(), b_unit in exponents()) {
+        prop_assume!(a_unit != b_unit);
+        let result = std::panic::catch_unwind(|| unit_num(a, a_unit) + unit_num(b, b_unit));
+
+        prop_assert!(result.is_err());
+    }
+}
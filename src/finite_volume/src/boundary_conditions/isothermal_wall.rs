@@ -0,0 +1,103 @@
+use common::number::Real;
+use grid::block::GridBlock;
+use grid::Block;
+
+use crate::fluid_block::FluidBlockError;
+use crate::interface::Interfaces;
+use super::PostReconstructionAction;
+
+/// Hold the gas on a wall at the temperature of the wall, and compute
+/// the heat conducted into the gas from the temperature difference
+/// between the wall and the centre of the cell next to it.
+pub struct IsothermalWallAction {
+    t_wall: Real,
+    thermal_conductivity: Real,
+
+    // for each face on the boundary, the distance from the face
+    // to the centre of its cell along the normal of the face. This
+    // is negative if the normal points out of the cell.
+    cell_distances: Vec<Real>,
+}
+
+impl IsothermalWallAction {
+    /// Returns an error if one of the `boundary_faces` isn't
+    /// attached to a cell in `grid_block`
+    pub fn new(grid_block: &GridBlock, boundary_faces: &[usize], t_wall: Real, thermal_conductivity: Real)
+        -> Result<IsothermalWallAction, FluidBlockError>
+    {
+        let cell_distances = boundary_faces
+            .iter()
+            .map(|&face| {
+                // a boundary face only has a cell on one side
+                let (interface, cell) = grid_block.interfaces()
+                    .get(face)
+                    .and_then(|interface| Some((interface, interface.left_cell().or(interface.right_cell())?)))
+                    .ok_or(FluidBlockError::DetachedBoundaryFace(face))?;
+                let centre = grid_block.cells()[cell].centre();
+                Ok((centre - &interface.centre()).dot(&interface.norm()))
+            })
+            .collect::<Result<Vec<Real>, FluidBlockError>>()?;
+        Ok(IsothermalWallAction { t_wall, thermal_conductivity, cell_distances })
+    }
+}
+
+impl PostReconstructionAction for IsothermalWallAction {
+    /// Set the temperature either side of each face to the wall temperature,
+    /// and the heat flux into the gas to `q_wall = k * dT/dn`, where n points
+    /// from the gas into the wall
+    fn apply_post_reconstruction_action(&self, boundary_faces: &[usize], interfaces: &mut Interfaces) {
+        for (&face, &distance) in boundary_faces.iter().zip(self.cell_distances.iter()) {
+            // the gas is on the side of the face the normal points to if the distance is positive
+            let t_gas = if distance > 0.0 {
+                interfaces.right_flow_states().t[face]
+            } else {
+                interfaces.left_flow_states().t[face]
+            };
+            let dt_dn = (self.t_wall - t_gas) / distance.abs();
+            interfaces.heat_fluxes_mut()[face] = self.thermal_conductivity * dt_dn;
+            interfaces.left_flow_states_mut().t[face] = self.t_wall;
+            interfaces.right_flow_states_mut().t[face] = self.t_wall;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use grid::block::BlockCollection;
+    use super::*;
+
+    #[test]
+    fn heat_flux_into_gas() {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+        let grid_block = block_collection.get_block(0);
+        let faces = grid_block.boundaries()["slip_wall_bottom"].clone();
+        let action = IsothermalWallAction::new(grid_block, &faces, 400.0, 0.025).unwrap();
+        let mut interfaces = Interfaces::new(grid_block.interfaces());
+        interfaces.left_flow_states_mut().t.fill(300.0);
+        interfaces.right_flow_states_mut().t.fill(300.0);
+
+        action.apply_post_reconstruction_action(&faces, &mut interfaces);
+
+        // the cells are 1 m square, so the centres are 0.5 m from the wall
+        for &face in faces.iter() {
+            assert!((interfaces.heat_fluxes()[face] - 0.025 * 100.0 / 0.5).abs() < 1e-12);
+            assert_eq!(interfaces.left_flow_states().t[face], 400.0);
+            assert_eq!(interfaces.right_flow_states().t[face], 400.0);
+        }
+        assert_eq!(interfaces.heat_fluxes().iter().filter(|&&q| q != 0.0).count(), 3);
+    }
+
+    #[test]
+    fn detached_face() {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+        let grid_block = block_collection.get_block(0);
+        let face = grid_block.interfaces().len();
+        let result = IsothermalWallAction::new(grid_block, &[0, face], 400.0, 0.025);
+
+        assert!(matches!(result, Err(FluidBlockError::DetachedBoundaryFace(id)) if id == face));
+    }
+}
@@ -1,7 +1,12 @@
+pub mod spec;
+pub mod isothermal_wall;
+
 use crate::interface::Interfaces;
+use spec::{BoundaryConditionKind, BoundaryConditionSpec};
 
 pub struct BoundaryCondition {
     tag: String,
+    kind: BoundaryConditionKind,
     convective_flux_computed_in_boundary: bool,
     has_ghost_cells: bool,
 
@@ -9,15 +14,40 @@ pub struct BoundaryCondition {
     interfaces: Vec<usize>,
 
     pre_reconstruction_actions: Vec<Box<dyn PreReconstructionAction>>,
+    post_reconstruction_actions: Vec<Box<dyn PostReconstructionAction>>,
 }
 
 impl BoundaryCondition {
+    /// Build the boundary condition described by `spec`, applied
+    /// to the interfaces with the given id's
+    pub fn from_spec(spec: &BoundaryConditionSpec, interfaces: Vec<usize>) -> BoundaryCondition {
+        BoundaryCondition {
+            tag: spec.tag().to_string(),
+            kind: spec.kind().clone(),
+            convective_flux_computed_in_boundary: false,
+            has_ghost_cells: true,
+            interfaces,
+            pre_reconstruction_actions: Vec::new(),
+            post_reconstruction_actions: Vec::new(),
+        }
+    }
+
     pub fn apply_pre_reconstruction_actions(&self, interfaces: &mut Interfaces) {
         for pre_reconstruction_action in self.pre_reconstruction_actions.iter() {
             pre_reconstruction_action.apply_pre_reconstruction_action(&self.interfaces, interfaces);
         }
     }
 
+    pub fn add_post_reconstruction_action(&mut self, action: Box<dyn PostReconstructionAction>) {
+        self.post_reconstruction_actions.push(action);
+    }
+
+    pub fn apply_post_reconstruction_actions(&self, interfaces: &mut Interfaces) {
+        for post_reconstruction_action in self.post_reconstruction_actions.iter() {
+            post_reconstruction_action.apply_post_reconstruction_action(&self.interfaces, interfaces);
+        }
+    }
+
     pub fn has_ghost_cells(&self) -> bool {
         self.has_ghost_cells
     }
@@ -29,8 +59,23 @@ impl BoundaryCondition {
     pub fn tag(&self) -> &str {
         &self.tag
     }
+
+    pub fn kind(&self) -> &BoundaryConditionKind {
+        &self.kind
+    }
+
+    pub fn interfaces(&self) -> &[usize] {
+        &self.interfaces
+    }
 }
 
 pub trait PreReconstructionAction {
     fn apply_pre_reconstruction_action(&self, boundary_faces: &[usize], interfaces: &mut Interfaces);
 }
+
+/// An action applied to the interfaces on a boundary after the flow
+/// states either side of them are reconstructed, e.g. to compute the
+/// viscous fluxes through walls
+pub trait PostReconstructionAction {
+    fn apply_post_reconstruction_action(&self, boundary_faces: &[usize], interfaces: &mut Interfaces);
+}
@@ -0,0 +1,84 @@
+use common::number::Real;
+use gas::flow_state::FlowState;
+
+/// The kinds of boundary condition that can be applied to a boundary
+#[derive(Debug, Clone, PartialEq)]
+pub enum BoundaryConditionKind {
+    SlipWall,
+    SupersonicInflow(FlowState<Real>),
+    SupersonicOutflow,
+    PressureOutflow { p_back: Real },
+    IsothermalWall { t_wall: Real, thermal_conductivity: Real },
+}
+
+/// Describes the boundary condition to apply to the boundary
+/// with a particular tag. The boundary condition itself is
+/// built from this when the fluid blocks are created.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundaryConditionSpec {
+    tag: String,
+    kind: BoundaryConditionKind,
+}
+
+impl BoundaryConditionSpec {
+    pub fn new(tag: &str, kind: BoundaryConditionKind) -> BoundaryConditionSpec {
+        BoundaryConditionSpec { tag: tag.to_string(), kind }
+    }
+
+    pub fn slip_wall(tag: &str) -> BoundaryConditionSpec {
+        BoundaryConditionSpec::new(tag, BoundaryConditionKind::SlipWall)
+    }
+
+    pub fn supersonic_inflow(tag: &str, flow_state: FlowState<Real>) -> BoundaryConditionSpec {
+        BoundaryConditionSpec::new(tag, BoundaryConditionKind::SupersonicInflow(flow_state))
+    }
+
+    pub fn supersonic_outflow(tag: &str) -> BoundaryConditionSpec {
+        BoundaryConditionSpec::new(tag, BoundaryConditionKind::SupersonicOutflow)
+    }
+
+    /// A subsonic outflow, with the pressure held at `p_back`
+    pub fn pressure_outflow(tag: &str, p_back: Real) -> BoundaryConditionSpec {
+        BoundaryConditionSpec::new(tag, BoundaryConditionKind::PressureOutflow { p_back })
+    }
+
+    /// A wall held at temperature `t_wall`, which conducts heat
+    /// into the gas with the given thermal conductivity (W / m / K)
+    pub fn isothermal_wall(tag: &str, t_wall: Real, thermal_conductivity: Real) -> BoundaryConditionSpec {
+        BoundaryConditionSpec::new(tag, BoundaryConditionKind::IsothermalWall { t_wall, thermal_conductivity })
+    }
+
+    /// The tag of the boundary this applies to
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn kind(&self) -> &BoundaryConditionKind {
+        &self.kind
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::vector3::Vector3;
+    use gas::gas_state::GasState;
+    use super::*;
+
+    #[test]
+    fn supersonic_inflow() {
+        let gas_state = GasState{p: 1000.0, T: 300.0, ..GasState::default()};
+        let flow_state = FlowState::new(gas_state, Vector3{x: 1000.0, y: 0.0, z: 0.0});
+        let spec = BoundaryConditionSpec::supersonic_inflow("inflow", flow_state);
+
+        assert_eq!(spec.tag(), "inflow");
+        assert_eq!(spec.kind(), &BoundaryConditionKind::SupersonicInflow(flow_state));
+    }
+
+    #[test]
+    fn pressure_outflow() {
+        let spec = BoundaryConditionSpec::pressure_outflow("outflow", 101325.0);
+
+        assert_eq!(spec.tag(), "outflow");
+        assert_eq!(spec.kind(), &BoundaryConditionKind::PressureOutflow { p_back: 101325.0 });
+    }
+}
@@ -1,17 +1,24 @@
 use common::number::Real;
-use grid::cell::CellShape;
+use common::vector3::{ArrayVec3, Vector3};
+use grid::cell::{CellShape, GridCell};
 use grid::interface::Direction;
+use grid::Cell;
+use gas::gas_model::{GasModel, GasModelError};
+use gas::flow_state::FlowState;
+use gas::gas_state::GasState;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
-use crate::util::Ids;
 use crate::flow::{FlowStates, ConservedQuantities};
+use crate::interface::Interfaces;
 
 pub struct Cells {
     // geometric information
-    vertices: Ids,
-    interfaces: Ids,
-    interface_directions: Vec<Direction>,
+    vertices: Vec<Vec<usize>>,
+    interfaces: Vec<Vec<usize>>,
+    interface_directions: Vec<Vec<Direction>>,
     volume: Vec<Real>,
-    centre: Vec<Real>,
+    centre: ArrayVec3,
     shape: Vec<CellShape>,
 
     flow_states: FlowStates,
@@ -22,35 +29,407 @@ pub struct Cells {
 }
 
 impl Cells {
-    pub fn vertices(&self) -> &Ids {
+    /// Copy the geometric data out of the cells of a grid, and
+    /// take ownership of the flow states in those cells
+    pub fn new(grid_cells: &[GridCell], flow_states: FlowStates) -> Cells {
+        let length = grid_cells.len();
+        assert_eq!(flow_states.len(), length, "Expected a flow state for each cell");
+        let vertices = grid_cells.iter().map(|cell| cell.vertex_ids().clone()).collect();
+        let interfaces = grid_cells.iter().map(|cell| cell.interface_ids()).collect();
+        let interface_directions = grid_cells
+            .iter()
+            .map(|cell| cell.cell_faces().iter().map(|face| face.direction().clone()).collect())
+            .collect();
+        let volume = grid_cells.iter().map(|cell| cell.volume()).collect();
+        let centre: Vec<Vector3> = grid_cells.iter().map(|cell| *cell.centre()).collect();
+        let shape = grid_cells.iter().map(|cell| *cell.shape()).collect();
+        Cells {
+            vertices,
+            interfaces,
+            interface_directions,
+            volume,
+            centre: ArrayVec3::from_vector3s(&centre),
+            shape,
+            flow_states,
+            conserved_quantities: ConservedQuantities::zeros(length),
+            residuals: ConservedQuantities::zeros(length),
+            length,
+        }
+    }
+
+    /// No cells yet, with room for `capacity` cells to be added
+    /// with [`Cells::push_cell`]
+    pub fn with_capacity(capacity: usize) -> Cells {
+        Cells {
+            vertices: Vec::with_capacity(capacity),
+            interfaces: Vec::with_capacity(capacity),
+            interface_directions: Vec::with_capacity(capacity),
+            volume: Vec::with_capacity(capacity),
+            centre: ArrayVec3::with_capacity(capacity),
+            shape: Vec::with_capacity(capacity),
+            flow_states: FlowStates::with_capacity(capacity),
+            conserved_quantities: ConservedQuantities::with_capacity(capacity),
+            residuals: ConservedQuantities::with_capacity(capacity),
+            length: 0,
+        }
+    }
+
+    /// Add a cell to the end. The flow state in the cell is zero until the
+    /// flow states are set, and the directions of its interfaces aren't known
+    /// until [`Cells::compute_interface_directions`] is called.
+    pub fn push_cell(&mut self, shape: CellShape, volume: Real, centre: Vector3, vertex_ids: Vec<usize>,
+                     interface_ids: Vec<usize>) {
+        self.vertices.push(vertex_ids);
+        self.interfaces.push(interface_ids);
+        self.interface_directions.push(Vec::new());
+        self.volume.push(volume);
+        self.centre.push(&centre);
+        self.shape.push(shape);
+        self.flow_states.push(&FlowState::new(GasState::default(), Vector3::default()));
+        self.conserved_quantities.push([0.0; 5]);
+        self.residuals.push([0.0; 5]);
+        self.length += 1;
+    }
+
+    /// Work out the direction of each interface relative to each cell, which
+    /// is needed to sum the fluxes. An interface points into a cell if its
+    /// normal points towards the centre of the cell.
+    pub fn compute_interface_directions(&mut self, interfaces: &Interfaces) {
+        let (normals, face_centres) = (interfaces.normals(), interfaces.centres());
+        for (i, cell_interfaces) in self.interfaces.iter().enumerate() {
+            let directions = cell_interfaces.iter().map(|&iface| {
+                let dot = (self.centre.x[i] - face_centres.x[iface]) * normals.x[iface]
+                        + (self.centre.y[i] - face_centres.y[iface]) * normals.y[iface]
+                        + (self.centre.z[i] - face_centres.z[iface]) * normals.z[iface];
+                match dot > 0.0 {
+                    true => Direction::Inwards,
+                    false => Direction::Outwards,
+                }
+            });
+            self.interface_directions[i] = directions.collect();
+        }
+    }
+
+    /// The id's of the vertices of each cell
+    pub fn vertices(&self) -> &[Vec<usize>] {
         &self.vertices
     }
 
-    pub fn interfaces(&self) -> &Ids {
+    /// The id's of the interfaces surrounding each cell
+    pub fn interfaces(&self) -> &[Vec<usize>] {
         &self.interfaces
     }
 
-    pub fn interface_directions(&self) -> &[Direction] {
+    /// The direction of each interface surrounding each cell,
+    /// in the same order as [`Cells::interfaces`]
+    pub fn interface_directions(&self) -> &[Vec<Direction>] {
         &self.interface_directions
     }
 
-    pub fn volume(&self) -> &[Real] {
+    /// The volume of each cell
+    pub fn volumes(&self) -> &[Real] {
         &self.volume
     }
 
-    pub fn centre(&self) -> &[Real] {
+    /// The geometric centre of each cell
+    pub fn centres(&self) -> &ArrayVec3 {
         &self.centre
     }
 
+    /// The shape of each cell
+    pub fn shapes(&self) -> &[CellShape] {
+        &self.shape
+    }
+
+    #[deprecated(note = "use `Cells::volumes` instead")]
+    pub fn volume(&self) -> &[Real] {
+        self.volumes()
+    }
+
+    #[deprecated(note = "use `Cells::centres` instead")]
+    pub fn centre(&self) -> &ArrayVec3 {
+        self.centres()
+    }
+
+    #[deprecated(note = "use `Cells::shapes` instead")]
+    pub fn shape(&self) -> &[CellShape] {
+        self.shapes()
+    }
+
     pub fn flow_states(&self) -> &FlowStates {
         &self.flow_states
     }
 
+    pub fn conserved_quantities(&self) -> &ConservedQuantities {
+        &self.conserved_quantities
+    }
+
+    /// The rate of change of the conserved quantities in each cell,
+    /// as of the last call to [`Cells::compute_residuals`]
+    pub fn residuals(&self) -> &ConservedQuantities {
+        &self.residuals
+    }
+
+    /// Sum the fluxes through the interfaces of each cell to get the
+    /// rate of change of the conserved quantities in the cell. Each cell
+    /// gathers the fluxes from its own interfaces, so each cell only
+    /// writes to its own residual.
+    #[cfg(not(feature = "parallel"))]
+    pub(crate) fn compute_residuals(&mut self, interfaces: &Interfaces) {
+        for i in 0 .. self.length {
+            let residual = cell_residual(&self.interfaces[i], &self.interface_directions[i],
+                                         self.volume[i], interfaces);
+            self.residuals.mass[i] = residual[0];
+            self.residuals.momentum_x[i] = residual[1];
+            self.residuals.momentum_y[i] = residual[2];
+            self.residuals.momentum_z[i] = residual[3];
+            self.residuals.energy[i] = residual[4];
+        }
+    }
+
+    /// Sum the fluxes through the interfaces of each cell to get the
+    /// rate of change of the conserved quantities in the cell. Each cell
+    /// gathers the fluxes from its own interfaces, so each cell only
+    /// writes to its own residual, and the cells can be processed in parallel.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn compute_residuals(&mut self, interfaces: &Interfaces) {
+        let cell_interfaces = &self.interfaces;
+        let directions = &self.interface_directions;
+        let volumes = &self.volume;
+        let residuals = &mut self.residuals;
+        residuals.mass.par_iter_mut()
+            .zip(residuals.momentum_x.par_iter_mut())
+            .zip(residuals.momentum_y.par_iter_mut())
+            .zip(residuals.momentum_z.par_iter_mut())
+            .zip(residuals.energy.par_iter_mut())
+            .enumerate()
+            .for_each(|(i, ((((mass, momentum_x), momentum_y), momentum_z), energy))| {
+                let residual = cell_residual(&cell_interfaces[i], &directions[i], volumes[i], interfaces);
+                *mass = residual[0];
+                *momentum_x = residual[1];
+                *momentum_y = residual[2];
+                *momentum_z = residual[3];
+                *energy = residual[4];
+            });
+    }
+
+    /// Add `source`, per unit volume, to the residuals
+    pub(crate) fn add_to_residuals(&mut self, source: &ConservedQuantities) {
+        let residuals = &mut self.residuals;
+        let fields = [
+            (&mut residuals.mass, &source.mass),
+            (&mut residuals.momentum_x, &source.momentum_x),
+            (&mut residuals.momentum_y, &source.momentum_y),
+            (&mut residuals.momentum_z, &source.momentum_z),
+            (&mut residuals.energy, &source.energy),
+        ];
+        for (residual, source) in fields {
+            for (residual, source) in residual.iter_mut().zip(source.iter()) {
+                *residual += source;
+            }
+        }
+    }
+
+    /// Advance the conserved quantities by `dt`, using the current residuals
+    pub(crate) fn update_conserved_quantities(&mut self, dt: Real) {
+        let cq = &mut self.conserved_quantities;
+        let residuals = &self.residuals;
+        let fields = [
+            (&mut cq.mass, &residuals.mass),
+            (&mut cq.momentum_x, &residuals.momentum_x),
+            (&mut cq.momentum_y, &residuals.momentum_y),
+            (&mut cq.momentum_z, &residuals.momentum_z),
+            (&mut cq.energy, &residuals.energy),
+        ];
+        for (value, residual) in fields {
+            for (value, residual) in value.iter_mut().zip(residual.iter()) {
+                *value += dt * residual;
+            }
+        }
+    }
+
+    /// Compute the conserved quantities (per unit volume) in each
+    /// cell from the flow states
+    pub(crate) fn encode_conserved_quantities(&mut self) {
+        let fs = &self.flow_states;
+        let cq = &mut self.conserved_quantities;
+        for i in 0 .. self.length {
+            let rho = fs.rho[i];
+            let ke = 0.5 * (fs.vel_x[i].powi(2) + fs.vel_y[i].powi(2) + fs.vel_z[i].powi(2));
+            cq.mass[i] = rho;
+            cq.momentum_x[i] = rho * fs.vel_x[i];
+            cq.momentum_y[i] = rho * fs.vel_y[i];
+            cq.momentum_z[i] = rho * fs.vel_z[i];
+            cq.energy[i] = rho * (fs.u[i] + ke);
+        }
+    }
+
+    /// Recover the flow state in each cell from the conserved quantities,
+    /// using the gas model to compute the pressure and temperature from
+    /// the density and internal energy
+    pub(crate) fn decode_conserved_quantities(&mut self, gm: &dyn GasModel<Real>) -> Result<(), GasModelError> {
+        let fs = &mut self.flow_states;
+        let cq = &self.conserved_quantities;
+        for i in 0 .. self.length {
+            let rho = cq.mass[i];
+            if rho <= 0.0 {
+                return Err(GasModelError::InvalidState(
+                    format!("Non-positive density {} in cell {}", rho, i)
+                ));
+            }
+            let vel_x = cq.momentum_x[i] / rho;
+            let vel_y = cq.momentum_y[i] / rho;
+            let vel_z = cq.momentum_z[i] / rho;
+            let ke = 0.5 * (vel_x.powi(2) + vel_y.powi(2) + vel_z.powi(2));
+            let mut gas_state = GasState{rho, u: cq.energy[i] / rho - ke, ..GasState::default()};
+            gm.update_from_rhou(&mut gas_state)?;
+            fs.p[i] = gas_state.p;
+            fs.t[i] = gas_state.T;
+            fs.u[i] = gas_state.u;
+            fs.rho[i] = gas_state.rho;
+            fs.a[i] = gas_state.a;
+            fs.vel_x[i] = vel_x;
+            fs.vel_y[i] = vel_y;
+            fs.vel_z[i] = vel_z;
+        }
+        Ok(())
+    }
+
+    /// Replace the flow state in each cell
+    pub(crate) fn set_flow_states(&mut self, flow_states: FlowStates) {
+        assert_eq!(flow_states.len(), self.length, "Expected a flow state for each cell");
+        self.flow_states = flow_states;
+    }
+
     pub fn len(&self) -> usize {
         self.length
     }
 
-    pub fn shape(&self) -> &[CellShape] {
-        &self.shape
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+}
+
+/// The rate of change of the conserved quantities in a single cell,
+/// from the fluxes through its interfaces
+fn cell_residual(cell_interfaces: &[usize], directions: &[Direction],
+                 volume: Real, interfaces: &Interfaces) -> [Real; 5] {
+    assert_eq!(cell_interfaces.len(), directions.len(), "The interface directions haven't been computed");
+    let flux = interfaces.fluxes();
+    let mut residual = [0.0; 5];
+    for (&iface, direction) in cell_interfaces.iter().zip(directions.iter()) {
+        // flux out of the cell decreases the conserved quantities
+        let area = match direction {
+            Direction::Outwards => -interfaces.areas()[iface],
+            Direction::Inwards => interfaces.areas()[iface],
+        };
+        residual[0] += flux.mass[iface] * area;
+        residual[1] += flux.momentum_x[iface] * area;
+        residual[2] += flux.momentum_y[iface] * area;
+        residual[3] += flux.momentum_z[iface] * area;
+        residual[4] += flux.energy[iface] * area;
+    }
+    residual.map(|r| r / volume)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use grid::block::BlockCollection;
+    use grid::Block;
+    use super::*;
+
+    fn create_cells() -> Cells {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+        let block = block_collection.get_block(0);
+        Cells::new(block.cells(), FlowStates::zeros(block.cells().len()))
+    }
+
+    #[test]
+    fn push_cells() {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+        let block = block_collection.get_block(0);
+        let grid_cells = block.cells();
+        let bulk = create_cells();
+        let mut cells = Cells::with_capacity(grid_cells.len());
+        assert!(cells.is_empty());
+        for cell in grid_cells.iter() {
+            cells.push_cell(*cell.shape(), cell.volume(), *cell.centre(), cell.vertex_ids().clone(),
+                            cell.interface_ids());
+        }
+        cells.compute_interface_directions(&Interfaces::new(block.interfaces()));
+
+        assert_eq!(cells.len(), bulk.len());
+        assert_eq!(cells.vertices(), bulk.vertices());
+        assert_eq!(cells.interfaces(), bulk.interfaces());
+        assert_eq!(cells.interface_directions(), bulk.interface_directions());
+        assert_eq!(cells.volumes(), bulk.volumes());
+        assert_eq!(cells.centres().x, bulk.centres().x);
+        assert_eq!(cells.centres().y, bulk.centres().y);
+        assert_eq!(cells.centres().z, bulk.centres().z);
+        assert_eq!(cells.shapes(), bulk.shapes());
+        assert_eq!(cells.flow_states().p, bulk.flow_states().p);
+        assert_eq!(cells.conserved_quantities().energy, bulk.conserved_quantities().energy);
+        assert_eq!(cells.residuals().mass, bulk.residuals().mass);
+    }
+
+    #[test]
+    fn len() {
+        let cells = create_cells();
+
+        assert_eq!(cells.len(), 9);
+    }
+
+    #[test]
+    fn volumes() {
+        let cells = create_cells();
+
+        assert_eq!(cells.volumes(), &[1.0; 9]);
+    }
+
+    #[test]
+    fn centres() {
+        let cells = create_cells();
+
+        assert_eq!(cells.centres().x, vec![0.5, 1.5, 2.5, 0.5, 1.5, 2.5, 0.5, 1.5, 2.5]);
+        assert_eq!(cells.centres().y, vec![0.5, 0.5, 0.5, 1.5, 1.5, 1.5, 2.5, 2.5, 2.5]);
+        assert_eq!(cells.centres().z, vec![0.0; 9]);
+    }
+
+    #[test]
+    fn shapes() {
+        let cells = create_cells();
+
+        assert_eq!(cells.shapes(), &[CellShape::Quadrilateral; 9]);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_accessors() {
+        let cells = create_cells();
+
+        assert_eq!(cells.volume(), cells.volumes());
+        assert_eq!(cells.centre().x, cells.centres().x);
+        assert_eq!(cells.shape(), cells.shapes());
+    }
+
+    #[test]
+    fn vertices() {
+        let cells = create_cells();
+
+        assert_eq!(cells.vertices()[0], vec![0, 1, 5, 4]);
+        assert_eq!(cells.vertices()[5], vec![6, 7, 11, 10]);
+    }
+
+    #[test]
+    fn interfaces() {
+        let cells = create_cells();
+
+        assert_eq!(cells.interfaces()[0], vec![0, 1, 2, 3]);
+        assert_eq!(cells.interfaces()[5], vec![9, 15, 16, 13]);
+        assert_eq!(cells.interface_directions()[0].len(), 4);
     }
 }
@@ -1,4 +1,10 @@
+use std::io::{Read, Write};
+
+use common::DynamicResult;
 use common::number::Real;
+use common::vector3::Vector3;
+use gas::flow_state::FlowState;
+use gas::gas_state::GasState;
 
 #[derive(Clone)]
 pub struct FlowStates {
@@ -6,6 +12,8 @@ pub struct FlowStates {
     pub t: Vec<Real>,
     pub u: Vec<Real>,
     pub rho: Vec<Real>,
+    /// The speed of sound
+    pub a: Vec<Real>,
     pub vel_x: Vec<Real>,
     pub vel_y: Vec<Real>,
     pub vel_z: Vec<Real>,
@@ -17,10 +25,312 @@ impl FlowStates {
         let t = Vec::with_capacity(capacity);
         let u = Vec::with_capacity(capacity);
         let rho = Vec::with_capacity(capacity);
+        let a = Vec::with_capacity(capacity);
         let vel_x = Vec::with_capacity(capacity);
         let vel_y = Vec::with_capacity(capacity);
         let vel_z = Vec::with_capacity(capacity);
-        FlowStates{p, t, u, rho, vel_x, vel_y, vel_z}
+        FlowStates{p, t, u, rho, a, vel_x, vel_y, vel_z}
+    }
+
+    pub fn zeros(n: usize) -> FlowStates {
+        FlowStates{
+            p: vec![0.0; n],
+            t: vec![0.0; n],
+            u: vec![0.0; n],
+            rho: vec![0.0; n],
+            a: vec![0.0; n],
+            vel_x: vec![0.0; n],
+            vel_y: vec![0.0; n],
+            vel_z: vec![0.0; n],
+        }
+    }
+
+    /// Flow states for `n_cells` cells, all with the same state
+    pub fn with_constant(n_cells: usize, state: &FlowState<Real>) -> FlowStates {
+        let mut flow_states = FlowStates::zeros(n_cells);
+        flow_states.set_all(state);
+        flow_states
+    }
+
+    /// Set the flow state in every cell to `state`
+    pub fn set_all(&mut self, state: &FlowState<Real>) {
+        let gas_state = state.gas_state();
+        let vel = state.velocity();
+        self.p.fill(gas_state.p);
+        self.t.fill(gas_state.T);
+        self.u.fill(gas_state.u);
+        self.rho.fill(gas_state.rho);
+        self.a.fill(gas_state.a);
+        self.vel_x.fill(vel.x);
+        self.vel_y.fill(vel.y);
+        self.vel_z.fill(vel.z);
+    }
+
+    /// Append a flow state to the end of the arrays
+    pub fn push(&mut self, flow_state: &FlowState<Real>) {
+        let gas_state = flow_state.gas_state();
+        let vel = flow_state.velocity();
+        self.p.push(gas_state.p);
+        self.t.push(gas_state.T);
+        self.u.push(gas_state.u);
+        self.rho.push(gas_state.rho);
+        self.a.push(gas_state.a);
+        self.vel_x.push(vel.x);
+        self.vel_y.push(vel.y);
+        self.vel_z.push(vel.z);
+    }
+
+    /// Copy the flow states in `other` into these flow states. This
+    /// reuses the existing arrays, so only allocates if `other` has
+    /// more cells than these flow states have capacity for.
+    pub fn copy_from(&mut self, other: &FlowStates) {
+        self.p.clone_from(&other.p);
+        self.t.clone_from(&other.t);
+        self.u.clone_from(&other.u);
+        self.rho.clone_from(&other.rho);
+        self.a.clone_from(&other.a);
+        self.vel_x.clone_from(&other.vel_x);
+        self.vel_y.clone_from(&other.vel_y);
+        self.vel_z.clone_from(&other.vel_z);
+    }
+
+    /// Swap the flow states with `other`, without copying or allocating
+    pub fn swap_with(&mut self, other: &mut FlowStates) {
+        std::mem::swap(&mut self.p, &mut other.p);
+        std::mem::swap(&mut self.t, &mut other.t);
+        std::mem::swap(&mut self.u, &mut other.u);
+        std::mem::swap(&mut self.rho, &mut other.rho);
+        std::mem::swap(&mut self.a, &mut other.a);
+        std::mem::swap(&mut self.vel_x, &mut other.vel_x);
+        std::mem::swap(&mut self.vel_y, &mut other.vel_y);
+        std::mem::swap(&mut self.vel_z, &mut other.vel_z);
+    }
+
+    /// The flow state at index `i`
+    pub fn flow_state(&self, i: usize) -> FlowState<Real> {
+        let gas_state = GasState{p: self.p[i], T: self.t[i], rho: self.rho[i], u: self.u[i], a: self.a[i]};
+        FlowState::new(gas_state, Vector3{x: self.vel_x[i], y: self.vel_y[i], z: self.vel_z[i]})
+    }
+
+    /// Set the flow state at index `i`
+    pub fn set_flow_state(&mut self, i: usize, flow_state: &FlowState<Real>) {
+        let gas_state = flow_state.gas_state();
+        let vel = flow_state.velocity();
+        self.p[i] = gas_state.p;
+        self.t[i] = gas_state.T;
+        self.u[i] = gas_state.u;
+        self.rho[i] = gas_state.rho;
+        self.a[i] = gas_state.a;
+        self.vel_x[i] = vel.x;
+        self.vel_y[i] = vel.y;
+        self.vel_z[i] = vel.z;
+    }
+
+    pub fn len(&self) -> usize {
+        self.p.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.p.is_empty()
+    }
+
+    /// The largest speed of the flow in any cell, or zero if there are no cells
+    pub fn max_velocity_magnitude(&self) -> Real {
+        let mut max_squared: Real = 0.0;
+        for i in 0 .. self.len() {
+            let speed_squared = self.vel_x[i]*self.vel_x[i] + self.vel_y[i]*self.vel_y[i] + self.vel_z[i]*self.vel_z[i];
+            max_squared = max_squared.max(speed_squared);
+        }
+        Real::sqrt(max_squared)
+    }
+
+    /// The lowest pressure in any cell, or infinity if there are no cells
+    pub fn min_pressure(&self) -> Real {
+        self.p.iter().cloned().fold(Real::INFINITY, Real::min)
+    }
+
+    /// The lowest density in any cell, or infinity if there are no cells
+    pub fn min_density(&self) -> Real {
+        self.rho.iter().cloned().fold(Real::INFINITY, Real::min)
+    }
+
+    /// The highest temperature in any cell, or negative
+    /// infinity if there are no cells
+    pub fn max_temperature(&self) -> Real {
+        self.t.iter().cloned().fold(Real::NEG_INFINITY, Real::max)
+    }
+
+    /// The largest Mach number in any cell, or zero if there are no cells
+    pub fn max_mach_number(&self) -> Real {
+        self.vel_x.iter()
+            .zip(self.vel_y.iter())
+            .zip(self.vel_z.iter())
+            .zip(self.a.iter())
+            .map(|(((vel_x, vel_y), vel_z), a)| Real::sqrt(vel_x*vel_x + vel_y*vel_y + vel_z*vel_z) / a)
+            .fold(0.0, Real::max)
+    }
+
+    /// A view of the flow state in each cell, in order
+    pub fn iter(&self) -> impl Iterator<Item = FlowStateView<'_>> {
+        (0 .. self.len()).map(move |index| FlowStateView { flow_states: self, index })
+    }
+
+    /// A mutable view of the flow state in each cell, in order
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = FlowStateMutView<'_>> {
+        self.p.iter_mut()
+            .zip(self.t.iter_mut())
+            .zip(self.u.iter_mut())
+            .zip(self.rho.iter_mut())
+            .zip(self.a.iter_mut())
+            .zip(self.vel_x.iter_mut())
+            .zip(self.vel_y.iter_mut())
+            .zip(self.vel_z.iter_mut())
+            .map(|(((((((p, t), u), rho), a), vel_x), vel_y), vel_z)| {
+                FlowStateMutView { p, t, u, rho, a, vel_x, vel_y, vel_z }
+            })
+    }
+
+    /// Write the flow states in binary. Each field is written in turn,
+    /// one little-endian f64 per cell.
+    pub fn write_binary(&self, writer: &mut impl Write) -> DynamicResult<()> {
+        for field in [&self.p, &self.t, &self.u, &self.rho, &self.a, &self.vel_x, &self.vel_y, &self.vel_z] {
+            for value in field.iter() {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read flow states for `n_cells` cells written by [`FlowStates::write_binary`]
+    pub fn read_binary(reader: &mut impl Read, n_cells: usize) -> DynamicResult<FlowStates> {
+        let mut flow_states = FlowStates::with_capacity(n_cells);
+        for field in [&mut flow_states.p, &mut flow_states.t, &mut flow_states.u, &mut flow_states.rho,
+                      &mut flow_states.a, &mut flow_states.vel_x, &mut flow_states.vel_y, &mut flow_states.vel_z] {
+            let mut bytes = [0u8; 8];
+            for _ in 0 .. n_cells {
+                reader.read_exact(&mut bytes)?;
+                field.push(Real::from_le_bytes(bytes));
+            }
+        }
+        Ok(flow_states)
+    }
+}
+
+/// The flow state in a single cell of [`FlowStates`]
+#[derive(Clone, Copy)]
+pub struct FlowStateView<'a> {
+    flow_states: &'a FlowStates,
+    index: usize,
+}
+
+#[allow(non_snake_case)]
+impl<'a> FlowStateView<'a> {
+    /// The index of the cell
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn p(&self) -> Real {
+        self.flow_states.p[self.index]
+    }
+
+    pub fn T(&self) -> Real {
+        self.flow_states.t[self.index]
+    }
+
+    pub fn rho(&self) -> Real {
+        self.flow_states.rho[self.index]
+    }
+
+    pub fn u(&self) -> Real {
+        self.flow_states.u[self.index]
+    }
+
+    /// The speed of sound
+    pub fn a(&self) -> Real {
+        self.flow_states.a[self.index]
+    }
+
+    pub fn velocity(&self) -> Vector3 {
+        let i = self.index;
+        Vector3 { x: self.flow_states.vel_x[i], y: self.flow_states.vel_y[i], z: self.flow_states.vel_z[i] }
+    }
+}
+
+/// The flow state in a single cell of [`FlowStates`], which can be modified
+pub struct FlowStateMutView<'a> {
+    p: &'a mut Real,
+    t: &'a mut Real,
+    u: &'a mut Real,
+    rho: &'a mut Real,
+    a: &'a mut Real,
+    vel_x: &'a mut Real,
+    vel_y: &'a mut Real,
+    vel_z: &'a mut Real,
+}
+
+#[allow(non_snake_case)]
+impl<'a> FlowStateMutView<'a> {
+    pub fn p(&self) -> Real {
+        *self.p
+    }
+
+    pub fn T(&self) -> Real {
+        *self.t
+    }
+
+    pub fn rho(&self) -> Real {
+        *self.rho
+    }
+
+    pub fn u(&self) -> Real {
+        *self.u
+    }
+
+    /// The speed of sound
+    pub fn a(&self) -> Real {
+        *self.a
+    }
+
+    pub fn velocity(&self) -> Vector3 {
+        Vector3 { x: *self.vel_x, y: *self.vel_y, z: *self.vel_z }
+    }
+
+    pub fn set_p(&mut self, p: Real) {
+        *self.p = p;
+    }
+
+    pub fn set_T(&mut self, T: Real) {
+        *self.t = T;
+    }
+
+    pub fn set_rho(&mut self, rho: Real) {
+        *self.rho = rho;
+    }
+
+    pub fn set_u(&mut self, u: Real) {
+        *self.u = u;
+    }
+
+    pub fn set_a(&mut self, a: Real) {
+        *self.a = a;
+    }
+
+    pub fn set_velocity(&mut self, velocity: &Vector3) {
+        *self.vel_x = velocity.x;
+        *self.vel_y = velocity.y;
+        *self.vel_z = velocity.z;
+    }
+
+    /// Set the whole flow state in the cell
+    pub fn set(&mut self, flow_state: &FlowState<Real>) {
+        let gas_state = flow_state.gas_state();
+        self.set_p(gas_state.p);
+        self.set_T(gas_state.T);
+        self.set_rho(gas_state.rho);
+        self.set_u(gas_state.u);
+        self.set_a(gas_state.a);
+        self.set_velocity(flow_state.velocity());
     }
 }
 
@@ -31,3 +341,236 @@ pub struct ConservedQuantities {
     pub momentum_z: Vec<Real>,
     pub energy: Vec<Real>,
 }
+
+/// Source terms of the conserved quantities in each cell, per unit volume,
+/// to add to the rate of change from the fluxes
+pub type ConservativeSource = ConservedQuantities;
+
+impl ConservedQuantities {
+    pub fn zeros(n: usize) -> ConservedQuantities {
+        ConservedQuantities{
+            mass: vec![0.0; n],
+            momentum_x: vec![0.0; n],
+            momentum_y: vec![0.0; n],
+            momentum_z: vec![0.0; n],
+            energy: vec![0.0; n],
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> ConservedQuantities {
+        ConservedQuantities{
+            mass: Vec::with_capacity(capacity),
+            momentum_x: Vec::with_capacity(capacity),
+            momentum_y: Vec::with_capacity(capacity),
+            momentum_z: Vec::with_capacity(capacity),
+            energy: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Append the conserved quantities of a cell,
+    /// [mass, momentum_x, momentum_y, momentum_z, energy]
+    pub fn push(&mut self, values: [Real; 5]) {
+        self.mass.push(values[0]);
+        self.momentum_x.push(values[1]);
+        self.momentum_y.push(values[2]);
+        self.momentum_z.push(values[3]);
+        self.energy.push(values[4]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_flow_states(n_cells: usize) -> FlowStates {
+        let mut flow_states = FlowStates::with_capacity(n_cells);
+        for i in 0 .. n_cells {
+            let x = i as Real;
+            flow_states.p.push(101325.0 + 0.1 * x);
+            flow_states.t.push(300.0 + 0.01 * x);
+            flow_states.u.push(215287.5 - 0.3 * x);
+            flow_states.rho.push(1.176624281484062 / (1.0 + x));
+            flow_states.a.push(300.0);
+            flow_states.vel_x.push(1000.0 / 3.0 * x);
+            flow_states.vel_y.push(-x / 7.0);
+            flow_states.vel_z.push(0.0);
+        }
+        flow_states
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let flow_states = create_flow_states(1000);
+        let mut buffer: Vec<u8> = Vec::new();
+        flow_states.write_binary(&mut buffer).unwrap();
+        let read_flow_states = FlowStates::read_binary(&mut buffer.as_slice(), 1000).unwrap();
+
+        assert_eq!(read_flow_states.p, flow_states.p);
+        assert_eq!(read_flow_states.t, flow_states.t);
+        assert_eq!(read_flow_states.u, flow_states.u);
+        assert_eq!(read_flow_states.rho, flow_states.rho);
+        assert_eq!(read_flow_states.a, flow_states.a);
+        assert_eq!(read_flow_states.vel_x, flow_states.vel_x);
+        assert_eq!(read_flow_states.vel_y, flow_states.vel_y);
+        assert_eq!(read_flow_states.vel_z, flow_states.vel_z);
+    }
+
+    #[test]
+    fn binary_smaller_than_text() {
+        let flow_states = create_flow_states(1000);
+        let mut binary: Vec<u8> = Vec::new();
+        flow_states.write_binary(&mut binary).unwrap();
+
+        let mut text: Vec<u8> = Vec::new();
+        for i in 0 .. flow_states.len() {
+            writeln!(text, "{} {} {} {} {} {} {} {}",
+                     flow_states.p[i], flow_states.t[i], flow_states.u[i], flow_states.rho[i],
+                     flow_states.a[i], flow_states.vel_x[i], flow_states.vel_y[i], flow_states.vel_z[i]).unwrap();
+        }
+
+        assert_eq!(binary.len(), 8 * 8 * 1000);
+        assert!(binary.len() < text.len());
+    }
+
+    #[test]
+    fn summaries() {
+        let flow_states = create_flow_states(10);
+
+        assert_eq!(flow_states.min_pressure(), 101325.0);
+        assert_eq!(flow_states.min_density(), 1.176624281484062 / 10.0);
+        assert_eq!(flow_states.max_temperature(), 300.0 + 0.01 * 9.0);
+        let max_speed = Real::sqrt((3000.0 as Real).powi(2) + (9.0 as Real / 7.0).powi(2));
+        assert!((flow_states.max_velocity_magnitude() - max_speed).abs() < 1e-9);
+        assert!((flow_states.max_mach_number() - max_speed / 300.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn summaries_empty() {
+        let flow_states = FlowStates::zeros(0);
+
+        assert_eq!(flow_states.min_pressure(), Real::INFINITY);
+        assert_eq!(flow_states.max_temperature(), Real::NEG_INFINITY);
+        assert_eq!(flow_states.max_velocity_magnitude(), 0.0);
+        assert_eq!(flow_states.max_mach_number(), 0.0);
+    }
+
+    fn constant_flow_state() -> FlowState<Real> {
+        let gas_state = GasState{p: 101325.0, T: 300.0, rho: 1.1766, u: 215287.5, a: 347.2};
+        FlowState::new(gas_state, Vector3{x: 100.0, y: -20.0, z: 5.0})
+    }
+
+    fn assert_all_equal(flow_states: &FlowStates, state: &FlowState<Real>) {
+        for view in flow_states.iter() {
+            assert_eq!(view.p(), state.gas_state().p);
+            assert_eq!(view.T(), state.gas_state().T);
+            assert_eq!(view.rho(), state.gas_state().rho);
+            assert_eq!(view.u(), state.gas_state().u);
+            assert_eq!(view.a(), state.gas_state().a);
+            assert_eq!(&view.velocity(), state.velocity());
+        }
+    }
+
+    #[test]
+    fn with_constant() {
+        let state = constant_flow_state();
+        let flow_states = FlowStates::with_constant(20, &state);
+
+        assert_eq!(flow_states.len(), 20);
+        assert_all_equal(&flow_states, &state);
+    }
+
+    #[test]
+    fn set_all() {
+        let state = constant_flow_state();
+        let mut flow_states = create_flow_states(20);
+        flow_states.set_all(&state);
+
+        assert_eq!(flow_states.len(), 20);
+        assert_all_equal(&flow_states, &state);
+    }
+
+    #[test]
+    fn set_flow_state() {
+        let state = constant_flow_state();
+        let mut flow_states = create_flow_states(20);
+        flow_states.set_flow_state(7, &state);
+
+        assert_eq!(flow_states.flow_state(7), state);
+        assert_eq!(flow_states.flow_state(8), create_flow_states(20).flow_state(8));
+    }
+
+    #[test]
+    fn copy_from() {
+        let state = constant_flow_state();
+        let other = FlowStates::with_constant(20, &state);
+        let mut flow_states = create_flow_states(20);
+        let p_ptr = flow_states.p.as_ptr();
+        flow_states.copy_from(&other);
+
+        assert_eq!(flow_states.len(), 20);
+        assert_all_equal(&flow_states, &state);
+        assert_eq!(flow_states.p.as_ptr(), p_ptr);
+
+        // a different number of cells is fine too
+        flow_states.copy_from(&create_flow_states(5));
+        assert_eq!(flow_states.len(), 5);
+        assert_eq!(flow_states.vel_x, create_flow_states(5).vel_x);
+    }
+
+    #[test]
+    fn swap_with() {
+        let state = constant_flow_state();
+        let mut constant = FlowStates::with_constant(20, &state);
+        let mut flow_states = create_flow_states(10);
+        flow_states.swap_with(&mut constant);
+
+        assert_eq!(flow_states.len(), 20);
+        assert_all_equal(&flow_states, &state);
+        assert_eq!(constant.len(), 10);
+        assert_eq!(constant.p, create_flow_states(10).p);
+    }
+
+    #[test]
+    fn iter() {
+        let flow_states = create_flow_states(10);
+
+        assert_eq!(flow_states.iter().count(), 10);
+        for (i, view) in flow_states.iter().enumerate() {
+            assert_eq!(view.index(), i);
+            assert_eq!(view.p(), flow_states.p[i]);
+            assert_eq!(view.T(), flow_states.t[i]);
+            assert_eq!(view.rho(), flow_states.rho[i]);
+            assert_eq!(view.u(), flow_states.u[i]);
+            assert_eq!(view.a(), flow_states.a[i]);
+            assert_eq!(view.velocity(), Vector3{x: flow_states.vel_x[i], y: flow_states.vel_y[i], z: 0.0});
+        }
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut flow_states = create_flow_states(10);
+        for mut view in flow_states.iter_mut() {
+            let p = view.p();
+            view.set_p(2.0 * p);
+            view.set_a(340.0);
+            view.set_velocity(&Vector3{x: 1.0, y: 2.0, z: 3.0});
+        }
+        let expected = create_flow_states(10);
+
+        for (view, expected) in flow_states.iter().zip(expected.iter()) {
+            assert_eq!(view.p(), 2.0 * expected.p());
+            assert_eq!(view.T(), expected.T());
+            assert_eq!(view.a(), 340.0);
+            assert_eq!(view.velocity(), Vector3{x: 1.0, y: 2.0, z: 3.0});
+        }
+    }
+
+    #[test]
+    fn read_binary_too_short() {
+        let flow_states = create_flow_states(10);
+        let mut buffer: Vec<u8> = Vec::new();
+        flow_states.write_binary(&mut buffer).unwrap();
+
+        assert!(FlowStates::read_binary(&mut buffer.as_slice(), 11).is_err());
+    }
+}
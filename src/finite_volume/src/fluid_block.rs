@@ -1,28 +1,156 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
-use common::DynamicResult;
+use common::{time_directory_name, DynamicResult};
 use common::number::Real;
-use common::vector3::ArrayVec3;
-use grid::block::BlockCollection;
+use common::vector3::{ArrayVec3, Vector3};
+use grid::block::{BlockCollection, GridBlock};
+use grid::{Block, Cell, Interface, Vertex};
+use grid::interface::Direction;
 use gas::flow_state::FlowState;
+use gas::gas_model::{GasModel, GasModelError};
+use gas::gas_state::GasState;
 
 use crate::boundary_conditions::BoundaryCondition;
+use crate::boundary_conditions::isothermal_wall::IsothermalWallAction;
+use crate::boundary_conditions::spec::{BoundaryConditionKind, BoundaryConditionSpec};
 use crate::fluid_block_io::FluidBlockIO;
 use crate::interface::Interfaces;
 use crate::cells::Cells;
+use crate::flow::{ConservativeSource, FlowStates};
+use crate::flux::FluxCalculator;
+use crate::reconstruction::{self, ReconstructionOrder};
 
 
+/// Problems creating a fluid block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FluidBlockError {
+    /// There is a boundary condition for a tag which isn't in the grid
+    UnknownBoundaryTag(String),
+
+    /// A face on a boundary isn't attached to any cell
+    DetachedBoundaryFace(usize),
+}
+
+impl std::error::Error for FluidBlockError {}
+
+impl std::fmt::Display for FluidBlockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self {
+            FluidBlockError::UnknownBoundaryTag(tag) => write!(
+                f, "There is a boundary condition for '{}', but no boundary with that tag", tag
+            ),
+            FluidBlockError::DetachedBoundaryFace(face) => write!(
+                f, "Boundary face {} isn't attached to a cell", face
+            ),
+        }
+    }
+}
 
 pub struct FluidBlock {
     vertices: ArrayVec3,
     interfaces: Interfaces,
     cells: Cells,
+
+    // the cells behind and in front of each interface
+    interface_cells: Vec<[Option<usize>; 2]>,
+
     boundaries: Vec<BoundaryCondition>,
     id: usize,
     dimensions: u8,
 }
 
 impl FluidBlock {
+    fn new(grid_block: &GridBlock, flow_states: FlowStates) -> FluidBlock {
+        let vertices: Vec<Vector3> = grid_block.vertices()
+            .iter()
+            .map(|vertex| *vertex.pos())
+            .collect();
+        let mut cells = Cells::with_capacity(grid_block.cells().len());
+        for cell in grid_block.cells().iter() {
+            cells.push_cell(*cell.shape(), cell.volume(), *cell.centre(), cell.vertex_ids().clone(),
+                            cell.interface_ids());
+        }
+        cells.set_flow_states(flow_states);
+        let mut interfaces = Interfaces::with_capacity(grid_block.interfaces().len());
+        for iface in grid_block.interfaces().iter() {
+            interfaces.push_interface(*iface.shape(), iface.area(), iface.norm(), iface.t1(), iface.t2(),
+                                      iface.centre(), iface.vertex_ids().clone());
+        }
+        cells.compute_interface_directions(&interfaces);
+        let mut interface_cells = vec![[None, None]; interfaces.len()];
+        for (cell, (faces, directions)) in cells.interfaces().iter().zip(cells.interface_directions()).enumerate() {
+            for (&face, direction) in faces.iter().zip(directions.iter()) {
+                match direction {
+                    Direction::Outwards => interface_cells[face][0] = Some(cell),
+                    Direction::Inwards => interface_cells[face][1] = Some(cell),
+                }
+            }
+        }
+        FluidBlock {
+            vertices: ArrayVec3::from_vector3s(&vertices),
+            interfaces,
+            cells,
+            interface_cells,
+            boundaries: Vec::new(),
+            id: grid_block.id(),
+            dimensions: grid_block.dimensions(),
+        }
+    }
+
+    /// Create a fluid block from the geometry of `grid_block`, with the
+    /// boundary conditions in `bc_specs` applied to the boundaries with
+    /// matching tags. The gas model fills in the thermodynamic properties
+    /// of any inflow states from their pressure and temperature. The flow
+    /// states in the cells are zero until an initial condition is set.
+    ///
+    /// Returns an error if a boundary condition is for a tag that isn't
+    /// in the grid. Boundaries without a boundary condition are left alone,
+    /// see [`FluidBlock::boundaries_without_conditions`].
+    ///
+    /// # Panics
+    ///
+    /// If the gas model can't compute the state of an inflow
+    pub fn from_grid_block(grid_block: &GridBlock, bc_specs: &[BoundaryConditionSpec], gm: &dyn GasModel<Real>)
+        -> Result<FluidBlock, FluidBlockError>
+    {
+        if let Some(spec) = bc_specs.iter().find(|spec| !grid_block.has_boundary(spec.tag())) {
+            return Err(FluidBlockError::UnknownBoundaryTag(spec.tag().to_string()));
+        }
+
+        let mut fluid_block = FluidBlock::new(grid_block, FlowStates::zeros(grid_block.cells().len()));
+        for spec in bc_specs.iter() {
+            let interfaces = &grid_block.boundaries()[spec.tag()];
+            let spec = match spec.kind() {
+                BoundaryConditionKind::SupersonicInflow(flow_state) => {
+                    let mut flow_state = *flow_state;
+                    gm.update_from_pT(flow_state.gas_state_mut())
+                        .expect("Invalid inflow state");
+                    BoundaryConditionSpec::supersonic_inflow(spec.tag(), flow_state)
+                }
+                _ => spec.clone(),
+            };
+            let mut boundary = BoundaryCondition::from_spec(&spec, interfaces.clone());
+            if let BoundaryConditionKind::IsothermalWall { t_wall, thermal_conductivity } = *spec.kind() {
+                let action = IsothermalWallAction::new(grid_block, interfaces, t_wall, thermal_conductivity)?;
+                boundary.add_post_reconstruction_action(Box::new(action));
+            }
+            fluid_block.boundaries.push(boundary);
+        }
+        Ok(fluid_block)
+    }
+
+    /// The tags of the boundaries of `grid_block` which don't have a
+    /// boundary condition in `bc_specs`, in alphabetical order. Nothing
+    /// is done to these boundaries, so they act like walls.
+    pub fn boundaries_without_conditions<'a>(grid_block: &'a GridBlock, bc_specs: &[BoundaryConditionSpec]) -> Vec<&'a str> {
+        grid_block.boundary_tags()
+            .into_iter()
+            .filter(|tag| !bc_specs.iter().any(|spec| spec.tag() == *tag))
+            .collect()
+    }
+
     pub fn vertices(&self) -> &ArrayVec3 {
         &self.vertices
     }
@@ -31,51 +159,395 @@ impl FluidBlock {
         &self.interfaces
     }
 
+    pub fn interfaces_mut(&mut self) -> &mut Interfaces {
+        &mut self.interfaces
+    }
+
     pub fn cells(&self) -> &Cells {
         &self.cells
     }
 
+    /// The cells behind and in front of each interface, which the normal
+    /// points away from and towards. Interfaces on a boundary only have
+    /// a cell on one side.
+    pub fn interface_cells(&self) -> &[[Option<usize>; 2]] {
+        &self.interface_cells
+    }
+
     pub fn id(&self) -> usize {
         self.id
     }
 
+    pub fn boundaries(&self) -> &[BoundaryCondition] {
+        &self.boundaries
+    }
+
+    /// Replace the flow states in the cells, e.g. with those read from a
+    /// flow file to restart a simulation. The conserved variables need
+    /// to be encoded again afterwards.
+    ///
+    /// # Panics
+    ///
+    /// If there isn't a flow state for each cell
+    pub fn set_flow_states(&mut self, flow_states: FlowStates) {
+        self.cells.set_flow_states(flow_states);
+    }
+
+    /// Set the flow state in each cell to `ic` evaluated at the centre of
+    /// the cell. The gas model fills in the thermodynamic properties of
+    /// the gas from the pressure and temperature. The conserved variables
+    /// need to be encoded again afterwards.
+    ///
+    /// Returns an error if `ic` gives a state with a non-positive pressure
+    /// or temperature, or the gas model can't update the state
+    pub fn set_initial_condition(&mut self, ic: impl Fn(Real, Real, Real) -> FlowState<Real>, gm: &dyn GasModel<Real>)
+        -> Result<(), GasModelError>
+    {
+        let centres = self.cells.centres();
+        let mut flow_states = FlowStates::with_capacity(self.cells.len());
+        for i in 0 .. self.cells.len() {
+            let mut flow_state = ic(centres.x[i], centres.y[i], centres.z[i]);
+            flow_state.gas_state().validate()?;
+            gm.update_from_pT(flow_state.gas_state_mut())?;
+            flow_states.push(&flow_state);
+        }
+        self.cells.set_flow_states(flow_states);
+        Ok(())
+    }
+
     pub fn apply_pre_reconstruction_boundary_conditions(&mut self) {
         for boundary in self.boundaries.iter() {
             boundary.apply_pre_reconstruction_actions(&mut self.interfaces);
         }
     }
 
+    /// Apply the boundary conditions which act on the flow states
+    /// reconstructed either side of the interfaces, such as the
+    /// temperature and heat flux of isothermal walls
+    pub fn apply_post_reconstruction_boundary_conditions(&mut self) {
+        for boundary in self.boundaries.iter() {
+            boundary.apply_post_reconstruction_actions(&mut self.interfaces);
+        }
+    }
+
+    /// Reconstruct the flow states either side of each interface from the
+    /// flow states in the cells, then set the flow states outside the
+    /// boundaries from the boundary conditions
+    pub fn reconstruct(&mut self, order: ReconstructionOrder, gm: &dyn GasModel<Real>) -> Result<(), GasModelError> {
+        reconstruction::reconstruct(order, &self.cells, &mut self.interfaces, &self.interface_cells,
+                                    self.dimensions, gm)?;
+        self.set_exterior_states(gm)
+    }
+
+    /// Set the flow state on the outside of each interface on a boundary,
+    /// from the state reconstructed on the inside. Walls, and boundaries
+    /// without a boundary condition, reflect the velocity normal to them.
+    fn set_exterior_states(&mut self, gm: &dyn GasModel<Real>) -> Result<(), GasModelError> {
+        let mut kinds = vec![None; self.interfaces.len()];
+        for boundary in self.boundaries.iter() {
+            for &face in boundary.interfaces() {
+                kinds[face] = Some(boundary.kind());
+            }
+        }
+        let interfaces = &mut self.interfaces;
+        for (face, face_cells) in self.interface_cells.iter().enumerate() {
+            let outside_in_front = match face_cells {
+                [Some(_), None] => true,
+                [None, Some(_)] => false,
+                _ => continue,
+            };
+            let inside = match outside_in_front {
+                true => interfaces.left_flow_states().flow_state(face),
+                false => interfaces.right_flow_states().flow_state(face),
+            };
+            let outside = match kinds[face] {
+                Some(BoundaryConditionKind::SupersonicInflow(flow_state)) => *flow_state,
+                Some(BoundaryConditionKind::SupersonicOutflow) => inside,
+                Some(BoundaryConditionKind::PressureOutflow { p_back }) => {
+                    let mut outside = inside;
+                    outside.gas_state_mut().p = *p_back;
+                    gm.update_from_rhop(outside.gas_state_mut())?;
+                    outside
+                }
+                _ => {
+                    let normals = interfaces.normals();
+                    let n = Vector3{x: normals.x[face], y: normals.y[face], z: normals.z[face]};
+                    let vel = inside.velocity();
+                    let reflected = vel - &(n * (2.0 * vel.dot(&n)));
+                    FlowState::new(*inside.gas_state(), reflected)
+                }
+            };
+            match outside_in_front {
+                true => interfaces.right_flow_states_mut().set_flow_state(face, &outside),
+                false => interfaces.left_flow_states_mut().set_flow_state(face, &outside),
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the convective flux through each interface from
+    /// the reconstructed flow states either side of it
+    pub fn compute_fluxes(&mut self, calculator: FluxCalculator) {
+        self.interfaces.compute_fluxes(calculator);
+    }
+
+    /// Sum the fluxes through the interfaces of each cell into the
+    /// residuals of the cell. Fluxes are positive in the direction of
+    /// the interface normal, so they are subtracted from cells the
+    /// normal points out of, and added to cells the normal points into.
+    /// The residuals are rates of change, so `dt`, the time step they
+    /// will be applied over, is only checked here, before any work is done.
+    ///
+    /// # Panics
+    ///
+    /// If `dt` isn't positive and finite
+    pub fn apply_flux_to_residuals(&mut self, dt: Real) {
+        assert!(dt > 0.0 && dt.is_finite(), "The time step must be positive and finite, not {}", dt);
+        self.cells.compute_residuals(&self.interfaces);
+    }
+
+    /// Add `source`, per unit volume, to the rate of change of the
+    /// conserved quantities in each cell. This needs to be done after
+    /// [`FluidBlock::apply_flux_to_residuals`], which overwrites them.
+    pub fn add_source_terms(&mut self, source: &ConservativeSource) {
+        self.cells.add_to_residuals(source);
+    }
+
+    /// The largest time step for explicit time integration at the Courant
+    /// number `cfl`. For each cell, the speed of the fastest wave through
+    /// each interface is weighted by the area of the interface, so on a
+    /// uniform grid this is `cfl` times the time for the fastest wave to
+    /// cross the cell.
+    pub fn stable_time_step(&self, cfl: Real) -> Real {
+        let fs = self.cells.flow_states();
+        let (areas, normals) = (self.interfaces.areas(), self.interfaces.normals());
+        let mut dt = Real::INFINITY;
+        for (cell, faces) in self.cells.interfaces().iter().enumerate() {
+            let signal: Real = faces.iter()
+                .map(|&face| {
+                    let vel_n = fs.vel_x[cell] * normals.x[face]
+                              + fs.vel_y[cell] * normals.y[face]
+                              + fs.vel_z[cell] * normals.z[face];
+                    (vel_n.abs() + fs.a[cell]) * areas[face]
+                })
+                .sum();
+            dt = dt.min(2.0 * self.cells.volumes()[cell] / signal);
+        }
+        cfl * dt
+    }
+
+    /// Advance the conserved quantities in each cell by `dt` using the
+    /// current residuals, then recover the flow states with the gas model
+    pub fn update_conserved_variables(&mut self, dt: Real, gm: &dyn GasModel<Real>) -> Result<(), GasModelError> {
+        self.cells.update_conserved_quantities(dt);
+        self.cells.decode_conserved_quantities(gm)
+    }
+
+    /// Compute the conserved quantities in each cell from its flow
+    /// state. This needs to be done after the flow states are set,
+    /// before [`FluidBlock::update_conserved_variables`]
+    pub fn encode_conserved_variables(&mut self) {
+        self.cells.encode_conserved_quantities();
+    }
+
+    /// Advance the conserved quantities in each cell by `dt` with the
+    /// explicit Euler method, using the fluxes currently on the interfaces.
+    /// With the `parallel` feature, the residuals are computed in parallel.
+    pub fn integrate_explicit_euler(&mut self, dt: Real) {
+        self.apply_flux_to_residuals(dt);
+        self.cells.update_conserved_quantities(dt);
+    }
+
+    /// The geometric source terms of the axisymmetric Euler equations, per
+    /// unit volume, in each cell. The x axis is the axis of symmetry and y
+    /// is the radius, so the grid must be in the y > 0 half plane. The
+    /// radius of each cell is taken to be the y coordinate of its centre.
+    /// The pressure in the energy term comes from the equation of state of
+    /// the gas model, so it's consistent with the density and temperature.
+    pub fn compute_axisymmetric_source_terms(&self, gm: &dyn GasModel<Real>) -> ConservativeSource {
+        let fs = self.cells.flow_states();
+        let radius = &self.cells.centres().y;
+        let mut source = ConservativeSource::zeros(self.cells.len());
+        for (i, radius) in radius.iter().enumerate() {
+            let (rho, vel_x, vel_r) = (fs.rho[i], fs.vel_x[i], fs.vel_y[i]);
+            let gas_state = GasState{p: fs.p[i], T: fs.t[i], rho, u: fs.u[i], a: fs.a[i]};
+            let p = rho * gm.R(&gas_state) * fs.t[i];
+            let ke = 0.5 * (vel_x.powi(2) + vel_r.powi(2) + fs.vel_z[i].powi(2));
+            let total_enthalpy = rho * (fs.u[i] + ke) + p;
+            let scale = -1.0 / radius;
+            source.mass[i] = scale * rho * vel_r;
+            source.momentum_x[i] = scale * rho * vel_r * vel_x;
+            source.momentum_y[i] = scale * rho * vel_r * vel_r;
+            source.energy[i] = scale * total_enthalpy * vel_r;
+        }
+        source
+    }
+
     pub fn dimensions(&self) -> u8 {
         self.dimensions
     }
+
+    /// Write the vertices, cells and cell flow states to an ASCII VTK
+    /// unstructured grid (`.vtu`) file, straight from the block's arrays.
+    /// This is meant for quick looks while debugging, rather than as
+    /// the simulation output.
+    pub fn write_vtk(&self, path: &Path) -> DynamicResult<()> {
+        let mut vtk = BufWriter::new(File::create(path)?);
+        let n_cells = self.cells.len();
+        writeln!(vtk, "<?xml version=\"1.0\"?>")?;
+        writeln!(vtk, "<VTKFile type=\"UnstructuredGrid\" version=\"0.1\" byte_order=\"LittleEndian\">")?;
+        writeln!(vtk, "<UnstructuredGrid>")?;
+        writeln!(vtk, "<Piece NumberOfPoints=\"{}\" NumberOfCells=\"{}\">", self.vertices.len(), n_cells)?;
+
+        writeln!(vtk, "<Points>")?;
+        writeln!(vtk, "<DataArray type=\"Float64\" NumberOfComponents=\"3\" format=\"ascii\">")?;
+        for i in 0 .. self.vertices.len() {
+            writeln!(vtk, "{} {} {}", self.vertices.x[i], self.vertices.y[i], self.vertices.z[i])?;
+        }
+        writeln!(vtk, "</DataArray>")?;
+        writeln!(vtk, "</Points>")?;
+
+        writeln!(vtk, "<Cells>")?;
+        writeln!(vtk, "<DataArray type=\"Int64\" Name=\"connectivity\" format=\"ascii\">")?;
+        for cell_vertices in self.cells.vertices().iter() {
+            let ids: Vec<String> = cell_vertices.iter().map(|id| id.to_string()).collect();
+            writeln!(vtk, "{}", ids.join(" "))?;
+        }
+        writeln!(vtk, "</DataArray>")?;
+        writeln!(vtk, "<DataArray type=\"Int64\" Name=\"offsets\" format=\"ascii\">")?;
+        let mut offset = 0;
+        for cell_vertices in self.cells.vertices().iter() {
+            offset += cell_vertices.len();
+            writeln!(vtk, "{}", offset)?;
+        }
+        writeln!(vtk, "</DataArray>")?;
+        // the su2 element types are the VTK cell types
+        writeln!(vtk, "<DataArray type=\"UInt8\" Name=\"types\" format=\"ascii\">")?;
+        for shape in self.cells.shapes().iter() {
+            writeln!(vtk, "{}", shape.to_su2_element_type())?;
+        }
+        writeln!(vtk, "</DataArray>")?;
+        writeln!(vtk, "</Cells>")?;
+
+        let flow_states = self.cells.flow_states();
+        writeln!(vtk, "<CellData Scalars=\"p\" Vectors=\"velocity\">")?;
+        for (name, values) in [("p", &flow_states.p), ("T", &flow_states.t), ("rho", &flow_states.rho), ("u", &flow_states.u)] {
+            writeln!(vtk, "<DataArray type=\"Float64\" Name=\"{}\" format=\"ascii\">", name)?;
+            for value in values.iter() {
+                writeln!(vtk, "{}", value)?;
+            }
+            writeln!(vtk, "</DataArray>")?;
+        }
+        writeln!(vtk, "<DataArray type=\"Float64\" Name=\"velocity\" NumberOfComponents=\"3\" format=\"ascii\">")?;
+        for i in 0 .. n_cells {
+            writeln!(vtk, "{} {} {}", flow_states.vel_x[i], flow_states.vel_y[i], flow_states.vel_z[i])?;
+        }
+        writeln!(vtk, "</DataArray>")?;
+        writeln!(vtk, "</CellData>")?;
+
+        writeln!(vtk, "</Piece>")?;
+        writeln!(vtk, "</UnstructuredGrid>")?;
+        writeln!(vtk, "</VTKFile>")?;
+        vtk.flush()?;
+        Ok(())
+    }
 }
 
 
-pub struct FluidBlockCollection<'a> {
+pub struct FluidBlockCollection {
     fluid_blocks: Vec<FluidBlock>,
-    fluid_block_io: Vec<FluidBlockIO<'a>>,
     time_index: usize,
+    physical_time: Real,
 }
 
+/// The file in each output directory recording the time index
+/// and the physical time the flow states were written at
+pub const TIME_FILE: &str = "time.toml";
+
+/// Read the physical time from the [`TIME_FILE`] in an output directory
+pub fn read_physical_time(time_dir: &Path) -> DynamicResult<Real> {
+    let path = time_dir.join(TIME_FILE);
+    let contents = fs::read_to_string(&path)?;
+    let value = contents
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .find(|(key, _)| key.trim() == "physical_time")
+        .map(|(_, value)| value.trim())
+        .ok_or_else(|| format!("No physical_time in {}", path.display()))?;
+    Ok(value.parse()?)
+}
+
+/// Flow state as a function of position, (x, y, z)
 pub type InitialCondition = fn(Real, Real, Real) -> FlowState<Real>;
 
-impl<'a> FluidBlockCollection<'a> {
+impl FluidBlockCollection {
+    /// Collect fluid blocks which have already been created, e.g. with
+    /// boundary conditions and an initial condition set
+    pub fn new(fluid_blocks: Vec<FluidBlock>) -> FluidBlockCollection {
+        FluidBlockCollection { fluid_blocks, time_index: 0, physical_time: 0.0 }
+    }
+
+    /// Create the fluid blocks, with the same flow state in every cell.
+    /// The thermodynamic properties of `initial_condition` should already
+    /// be filled in by the gas model.
     pub fn with_constant_initial_condition(block_collection: &BlockCollection, initial_condition: FlowState<Real>) -> FluidBlockCollection {
-        todo!()
+        let fluid_blocks = block_collection.blocks()
+            .iter()
+            .map(|grid_block| {
+                let flow_states = FlowStates::with_constant(grid_block.cells().len(), &initial_condition);
+                FluidBlock::new(grid_block, flow_states)
+            })
+            .collect();
+        FluidBlockCollection { fluid_blocks, time_index: 0, physical_time: 0.0 }
     }
 
-    pub fn with_variable_initial_condition(block_collection: &BlockCollection, initial_condition: InitialCondition) {
-        todo!()
+    /// Create the fluid blocks, evaluating `ic` at the centre of each cell
+    /// to set the initial flow state. The gas model fills in the thermodynamic
+    /// properties of the gas from the pressure and temperature.
+    pub fn with_variable_initial_condition(block_collection: &BlockCollection, ic: InitialCondition, gm: &dyn GasModel<Real>) -> Result<FluidBlockCollection, GasModelError> {
+        let mut fluid_blocks = Vec::with_capacity(block_collection.blocks().len());
+        for grid_block in block_collection.blocks().iter() {
+            let mut fluid_block = FluidBlock::new(grid_block, FlowStates::zeros(grid_block.cells().len()));
+            fluid_block.set_initial_condition(ic, gm)?;
+            fluid_blocks.push(fluid_block);
+        }
+        Ok(FluidBlockCollection { fluid_blocks, time_index: 0, physical_time: 0.0 })
     }
 
-    pub fn write_fluids_blocks(&mut self, path: &Path) -> DynamicResult<()> {
+    pub fn fluid_blocks(&self) -> &Vec<FluidBlock> {
+        &self.fluid_blocks
+    }
+
+    pub fn fluid_blocks_mut(&mut self) -> &mut [FluidBlock] {
+        &mut self.fluid_blocks
+    }
+
+    /// The number of times the fluid blocks have been written
+    pub fn time_index(&self) -> usize {
+        self.time_index
+    }
+
+    /// The simulation time, as of the last write
+    pub fn physical_time(&self) -> Real {
+        self.physical_time
+    }
+
+    /// Write the fluid blocks to a new time directory in `path`, e.g.
+    /// `t0003` for the third write, along with a [`TIME_FILE`]. `dt` is
+    /// the physical time which has passed since the last write.
+    pub fn write_fluids_blocks(&mut self, path: &Path, dt: Real) -> DynamicResult<()> {
         self.time_index += 1;
-        let mut block_path = path.to_path_buf();
-        block_path.push(format!("{:0>4}", self.time_index));
-        for block_io in self.fluid_block_io.iter_mut() {
-            block_path.set_file_name(format!("blk{:0>4}.fluid", block_io.id()));
-            block_io.write_fluid_block(&block_path)?;
+        self.physical_time += dt;
+        let time_dir = path.join(time_directory_name(self.time_index));
+        fs::create_dir_all(&time_dir)?;
+        for fluid_block in self.fluid_blocks.iter() {
+            let mut block_io = FluidBlockIO::new(fluid_block);
+            block_io.write_fluid_block(&time_dir.join("block"))?;
         } 
+        fs::write(
+            time_dir.join(TIME_FILE),
+            format!("time_index = {}\nphysical_time = {:e}\n", self.time_index, self.physical_time),
+        )?;
         Ok(())
     }
 }
@@ -84,15 +556,35 @@ impl<'a> FluidBlockCollection<'a> {
 mod tests {
     use std::path::PathBuf;
 
+    use common::number::Real;
+    use common::vector3::Vector3;
     use grid::block::BlockCollection;
     use grid::Block;
+    use gas::flow_state::FlowState;
+    use gas::gas_model::{GasModel, GasModelError};
+    use gas::gas_state::GasState;
+    use gas::ideal_gas::IdealGas;
+    use crate::boundary_conditions::spec::{BoundaryConditionKind, BoundaryConditionSpec};
     use crate::util::Ids;
+    use crate::flow::FlowStates;
+    use crate::reconstruction::ReconstructionOrder;
+    use super::{FluidBlock, FluidBlockCollection, FluidBlockError};
+
+    /// The 3x3 square grid, with each cell 1 m across
+    fn read_square() -> BlockCollection {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+        block_collection
+    }
+
+    /// A fluid block on the square grid, without any boundary conditions
+    fn square_fluid_block(gm: &IdealGas<Real>) -> FluidBlock {
+        FluidBlock::from_grid_block(read_square().get_block(0), &[], gm).unwrap()
+    }
 
     #[test]
     fn test_interface_ids() {
-        // read a block
-        let mut block_collection = BlockCollection::new(); 
-        block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+        let block_collection = read_square();
         let block = block_collection.get_block(0);
 
         let interface_ids = Ids::from_interfaces(block.interfaces());
@@ -108,9 +600,7 @@ mod tests {
 
     #[test]
     fn test_cell_ids() {
-        // read a block
-        let mut block_collection = BlockCollection::new(); 
-        block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+        let block_collection = read_square();
         let block = block_collection.get_block(0);
 
         let (vertex_ids, interface_ids) = Ids::from_cells(block.cells());
@@ -120,4 +610,336 @@ mod tests {
         assert_eq!(interface_ids[0], [0, 1, 2, 3]);
         assert_eq!(interface_ids[5], [9, 15, 16, 13]);
     }
+
+    fn linear_pressure(x: Real, _y: Real, _z: Real) -> FlowState<Real> {
+        let gas_state = GasState{p: 1000.0 * x, T: 300.0, ..GasState::default()};
+        FlowState::new(gas_state, Vector3{x: 100.0, y: 0.0, z: 0.0})
+    }
+
+    #[test]
+    fn from_grid_block() {
+        let block_collection = read_square();
+        let gm = IdealGas::new(287.0, 1.4);
+        let inflow = FlowState::new(
+            GasState{p: 1000.0, T: 300.0, ..GasState::default()}, Vector3{x: 100.0, y: 0.0, z: 0.0}
+        );
+        let bc_specs = [
+            BoundaryConditionSpec::supersonic_inflow("inflow", inflow),
+            BoundaryConditionSpec::supersonic_outflow("outflow"),
+            BoundaryConditionSpec::slip_wall("slip_wall_bottom"),
+        ];
+
+        let fluid_block = FluidBlock::from_grid_block(block_collection.get_block(0), &bc_specs, &gm).unwrap();
+
+        assert_eq!(fluid_block.cells().len(), 9);
+        assert_eq!(fluid_block.interfaces().len(), 24);
+        assert_eq!(fluid_block.cells().flow_states().p, vec![0.0; 9]);
+        let tags: Vec<&str> = fluid_block.boundaries().iter().map(|bc| bc.tag()).collect();
+        assert_eq!(tags, vec!["inflow", "outflow", "slip_wall_bottom"]);
+        assert_eq!(fluid_block.boundaries()[0].interfaces(), &[3, 12, 19]);
+        match fluid_block.boundaries()[0].kind() {
+            BoundaryConditionKind::SupersonicInflow(flow_state) => {
+                assert!((flow_state.gas_state().rho - 1000.0 / (287.0 * 300.0)).abs() < 1e-14);
+            }
+            kind => panic!("Expected a supersonic inflow, got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn isothermal_wall() {
+        let block_collection = read_square();
+        let gm = IdealGas::new(287.0, 1.4);
+        let bc_specs = [BoundaryConditionSpec::isothermal_wall("slip_wall_top", 500.0, 0.03)];
+        let mut fluid_block = FluidBlock::from_grid_block(block_collection.get_block(0), &bc_specs, &gm).unwrap();
+        fluid_block.interfaces_mut().left_flow_states_mut().t.fill(300.0);
+        fluid_block.interfaces_mut().right_flow_states_mut().t.fill(300.0);
+
+        fluid_block.apply_post_reconstruction_boundary_conditions();
+
+        let interfaces = fluid_block.interfaces();
+        for &face in fluid_block.boundaries()[0].interfaces() {
+            assert!((interfaces.heat_fluxes()[face] - 0.03 * 200.0 / 0.5).abs() < 1e-12);
+            assert_eq!(interfaces.left_flow_states().t[face], 500.0);
+        }
+        assert_eq!(interfaces.left_flow_states().t.iter().filter(|&&t| t == 300.0).count(), 21);
+    }
+
+    #[test]
+    fn interface_cells() {
+        let gm = IdealGas::new(287.0, 1.4);
+        let fluid_block = square_fluid_block(&gm);
+
+        // the normals of the first cell point out of it
+        assert_eq!(fluid_block.interface_cells()[1], [Some(0), Some(1)]);
+        assert_eq!(fluid_block.interface_cells()[3], [Some(0), None]);
+        let boundary_faces = fluid_block.interface_cells().iter().filter(|cells| cells.contains(&None)).count();
+        assert_eq!(boundary_faces, 12);
+    }
+
+    #[test]
+    fn reconstruct_boundaries() {
+        let block_collection = read_square();
+        let gm = IdealGas::new(287.0, 1.4);
+        let inflow = FlowState::new(
+            GasState{p: 1000.0, T: 400.0, ..GasState::default()}, Vector3{x: 800.0, y: 0.0, z: 0.0}
+        );
+        let bc_specs = [
+            BoundaryConditionSpec::supersonic_inflow("inflow", inflow),
+            BoundaryConditionSpec::pressure_outflow("outflow", 500.0),
+        ];
+        let mut fluid_block = FluidBlock::from_grid_block(block_collection.get_block(0), &bc_specs, &gm).unwrap();
+        let cell_state = FlowState::new(
+            GasState{p: 1000.0, T: 300.0, ..GasState::default()}, Vector3{x: 100.0, y: 50.0, z: 0.0}
+        );
+        fluid_block.set_initial_condition(|_, _, _| cell_state, &gm).unwrap();
+
+        fluid_block.reconstruct(ReconstructionOrder::Second, &gm).unwrap();
+
+        // the flow is uniform, so the interior states are the cell state
+        let interfaces = fluid_block.interfaces();
+        let (left, right) = (interfaces.left_flow_states(), interfaces.right_flow_states());
+        assert!((left.rho[1] - right.rho[1]).abs() < 1e-14);
+        assert_eq!(right.vel_y[1], 50.0);
+        // the inflow and outflow are on the west and east sides
+        assert_eq!(right.t[3], 400.0);
+        assert_eq!(right.vel_x[3], 800.0);
+        let outflow = fluid_block.boundaries()[1].interfaces()[0];
+        let outside = match fluid_block.interface_cells()[outflow] {
+            [Some(_), None] => right.flow_state(outflow),
+            _ => left.flow_state(outflow),
+        };
+        assert_eq!(outside.gas_state().p, 500.0);
+        assert_eq!(outside.gas_state().rho, left.rho[1]);
+        // the top and bottom don't have boundary conditions, so they reflect the flow
+        assert_eq!(left.vel_y[0], 50.0);
+        assert_eq!(right.vel_y[0], -50.0);
+        assert_eq!(right.vel_x[0], 100.0);
+    }
+
+    #[test]
+    fn stable_time_step() {
+        let gm = IdealGas::new(287.0, 1.4);
+        let mut fluid_block = square_fluid_block(&gm);
+        let state = FlowState::new(
+            GasState{p: 1000.0, T: 300.0, ..GasState::default()}, Vector3{x: 100.0, y: 0.0, z: 0.0}
+        );
+        fluid_block.set_initial_condition(|_, _, _| state, &gm).unwrap();
+        let a = fluid_block.cells().flow_states().a[0];
+
+        // the cells are 1 m squares, and the fastest wave crosses
+        // them at 100 + a in x, while sound crosses them in y
+        let dt = fluid_block.stable_time_step(0.5);
+        assert!((dt - 0.5 / (100.0 + 2.0 * a)).abs() < 1e-15);
+    }
+
+    #[test]
+    fn from_grid_block_unknown_tag() {
+        let block_collection = read_square();
+        let gm = IdealGas::new(287.0, 1.4);
+        let bc_specs = [
+            BoundaryConditionSpec::supersonic_outflow("outflow"),
+            BoundaryConditionSpec::slip_wall("wall"),
+        ];
+
+        let result = FluidBlock::from_grid_block(block_collection.get_block(0), &bc_specs, &gm);
+        assert_eq!(result.err(), Some(FluidBlockError::UnknownBoundaryTag("wall".to_string())));
+    }
+
+    #[test]
+    fn boundaries_without_conditions() {
+        let block_collection = read_square();
+        let bc_specs = [
+            BoundaryConditionSpec::supersonic_outflow("outflow"),
+            BoundaryConditionSpec::slip_wall("slip_wall_top"),
+        ];
+
+        let tags = FluidBlock::boundaries_without_conditions(block_collection.get_block(0), &bc_specs);
+        assert_eq!(tags, vec!["inflow", "slip_wall_bottom"]);
+    }
+
+    fn uniform_square_fluid_block(gm: &IdealGas<Real>, flow_state: &FlowState<Real>) -> FluidBlock {
+        let mut fluid_block = square_fluid_block(gm);
+        fluid_block.set_flow_states(FlowStates::with_constant(9, flow_state));
+        fluid_block
+    }
+
+    /// Air at 1 kPa and 300 K, moving with `velocity`
+    fn axisymmetric_flow_state(gm: &IdealGas<Real>, velocity: Vector3) -> FlowState<Real> {
+        let mut gas_state = GasState{p: 1000.0, T: 300.0, ..GasState::default()};
+        gm.update_from_pT(&mut gas_state).unwrap();
+        FlowState::new(gas_state, velocity)
+    }
+
+    #[test]
+    fn axisymmetric_source_axial_flow() {
+        // flow along a duct of constant radius has no radial velocity,
+        // so there are no geometric source terms
+        let gm = IdealGas::new(287.0, 1.4);
+        let flow_state = axisymmetric_flow_state(&gm, Vector3{x: 500.0, y: 0.0, z: 0.0});
+        let fluid_block = uniform_square_fluid_block(&gm, &flow_state);
+        let source = fluid_block.compute_axisymmetric_source_terms(&gm);
+
+        for quantity in [&source.mass, &source.momentum_x, &source.momentum_y, &source.momentum_z, &source.energy] {
+            assert_eq!(quantity, &vec![0.0; 9]);
+        }
+    }
+
+    #[test]
+    fn axisymmetric_source_radial_flow() {
+        let gm = IdealGas::new(287.0, 1.4);
+        let flow_state = axisymmetric_flow_state(&gm, Vector3{x: 30.0, y: 40.0, z: 0.0});
+        let fluid_block = uniform_square_fluid_block(&gm, &flow_state);
+        let source = fluid_block.compute_axisymmetric_source_terms(&gm);
+
+        let gas_state = flow_state.gas_state();
+        let rho = gas_state.rho;
+        let total_enthalpy = rho * (gas_state.u + 0.5 * 2500.0) + 1000.0;
+        for (i, radius) in [0.5, 0.5, 0.5, 1.5, 1.5, 1.5, 2.5, 2.5, 2.5].iter().enumerate() {
+            let close = |a: Real, b: Real| (a - b).abs() < 1e-12 * b.abs();
+            assert!(close(source.mass[i], -rho * 40.0 / radius));
+            assert!(close(source.momentum_x[i], -rho * 40.0 * 30.0 / radius));
+            assert!(close(source.momentum_y[i], -rho * 40.0 * 40.0 / radius));
+            assert_eq!(source.momentum_z[i], 0.0);
+            assert!(close(source.energy[i], -total_enthalpy * 40.0 / radius));
+        }
+    }
+
+    #[test]
+    fn variable_initial_condition() {
+        let block_collection = read_square();
+        let gm = IdealGas::new(287.0, 1.4);
+
+        let fluid_blocks = FluidBlockCollection::with_variable_initial_condition(
+            &block_collection, linear_pressure, &gm
+        ).unwrap();
+        let flow_states = fluid_blocks.fluid_blocks()[0].cells().flow_states();
+
+        assert_eq!(flow_states.p, vec![500., 1500., 2500., 500., 1500., 2500., 500., 1500., 2500.]);
+        assert_eq!(flow_states.t, vec![300.0; 9]);
+        assert_eq!(flow_states.vel_x, vec![100.0; 9]);
+        assert_eq!(flow_states.vel_y, vec![0.0; 9]);
+        assert!((flow_states.rho[4] - 1500. / (287.0 * 300.0)).abs() < 1e-14);
+        assert!((flow_states.u[4] - 287.0 / 0.4 * 300.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn set_initial_condition() {
+        let gm = IdealGas::new(287.0, 1.4);
+        let mut fluid_block = square_fluid_block(&gm);
+        let (p_0, dp_dy) = (2000.0, -100.0);
+
+        fluid_block.set_initial_condition(|_x, y, _z| {
+            let gas_state = GasState{p: p_0 + dp_dy * y, T: 300.0, ..GasState::default()};
+            FlowState::new(gas_state, Vector3{x: 0.0, y: 10.0, z: 0.0})
+        }, &gm).unwrap();
+
+        let flow_states = fluid_block.cells().flow_states();
+        assert_eq!(flow_states.p, vec![1950., 1950., 1950., 1850., 1850., 1850., 1750., 1750., 1750.]);
+        assert_eq!(flow_states.vel_y, vec![10.0; 9]);
+        assert!((flow_states.rho[3] - 1850. / (287.0 * 300.0)).abs() < 1e-14);
+    }
+
+    #[test]
+    fn set_invalid_initial_condition() {
+        let gm = IdealGas::new(287.0, 1.4);
+        let mut fluid_block = square_fluid_block(&gm);
+
+        // the pressure is negative in the right column of cells
+        let result = fluid_block.set_initial_condition(|x, _y, _z| {
+            let gas_state = GasState{p: 1000.0 * (2.0 - x), T: 300.0, ..GasState::default()};
+            FlowState::new(gas_state, Vector3{x: 0.0, y: 0.0, z: 0.0})
+        }, &gm);
+
+        assert!(matches!(result, Err(GasModelError::InvalidState(_))));
+    }
+
+    #[test]
+    fn constant_initial_condition() {
+        let block_collection = read_square();
+        let flow_state = FlowState::new(
+            GasState{p: 1000.0, T: 300.0, rho: 0.0116, u: 215250.0, a: 347.2}, Vector3{x: 5.0, y: 0.0, z: 0.0}
+        );
+
+        let fluid_blocks = FluidBlockCollection::with_constant_initial_condition(&block_collection, flow_state);
+
+        let flow_states = fluid_blocks.fluid_blocks()[0].cells().flow_states();
+        assert_eq!(flow_states.p, vec![1000.0; 9]);
+        assert_eq!(flow_states.rho, vec![0.0116; 9]);
+        assert_eq!(flow_states.vel_x, vec![5.0; 9]);
+    }
+
+    #[test]
+    fn update_conserved_variables() {
+        let block_collection = read_square();
+        let gm = IdealGas::new(287.0, 1.4);
+        let mut fluid_blocks = FluidBlockCollection::with_variable_initial_condition(
+            &block_collection, linear_pressure, &gm
+        ).unwrap();
+        let fluid_block = &mut fluid_blocks.fluid_blocks_mut()[0];
+        fluid_block.encode_conserved_variables();
+        let initial = fluid_block.cells().flow_states().clone();
+
+        // without any flux, nothing should change
+        fluid_block.apply_flux_to_residuals(0.1);
+        fluid_block.update_conserved_variables(0.1, &gm).unwrap();
+        let flow_states = fluid_block.cells().flow_states();
+        for i in 0 .. 9 {
+            assert!((flow_states.p[i] - initial.p[i]).abs() < 1e-9);
+            assert!((flow_states.t[i] - initial.t[i]).abs() < 1e-9);
+            assert!((flow_states.vel_x[i] - initial.vel_x[i]).abs() < 1e-9);
+        }
+
+        // mass flowing out of cell 0 (an outwards interface) and into cell 1
+        let mass_flux = 0.1 * initial.rho[0];
+        fluid_block.interfaces_mut().fluxes_mut().mass[1] = mass_flux;
+        fluid_block.apply_flux_to_residuals(0.5);
+        fluid_block.update_conserved_variables(0.5, &gm).unwrap();
+        let flow_states = fluid_block.cells().flow_states();
+        assert!((flow_states.rho[0] - (initial.rho[0] - 0.5 * mass_flux)).abs() < 1e-12);
+        assert!((flow_states.rho[1] - (initial.rho[1] + 0.5 * mass_flux)).abs() < 1e-12);
+        assert_eq!(flow_states.rho[2], initial.rho[2]);
+    }
+
+    #[test]
+    fn update_conserved_variables_without_encoding() {
+        let gm = IdealGas::new(287.0, 1.4);
+        let mut fluid_block = square_fluid_block(&gm);
+
+        fluid_block.apply_flux_to_residuals(0.1);
+        let result = fluid_block.update_conserved_variables(0.1, &gm);
+
+        assert!(matches!(result, Err(GasModelError::InvalidState(_))));
+    }
+
+    #[test]
+    #[should_panic(expected = "The time step must be positive")]
+    fn apply_flux_to_residuals_negative_time_step() {
+        let gm = IdealGas::new(287.0, 1.4);
+        let mut fluid_block = square_fluid_block(&gm);
+
+        fluid_block.apply_flux_to_residuals(-0.1);
+    }
+
+    #[test]
+    fn explicit_euler() {
+        let block_collection = read_square();
+        let gm = IdealGas::new(287.0, 1.4);
+        let mut fluid_blocks = FluidBlockCollection::with_variable_initial_condition(
+            &block_collection, linear_pressure, &gm
+        ).unwrap();
+        let fluid_block = &mut fluid_blocks.fluid_blocks_mut()[0];
+
+        // mass flowing out of interface 1 of cell 0, and into cell 1
+        fluid_block.interfaces_mut().fluxes_mut().mass[1] = 2.0;
+        fluid_block.integrate_explicit_euler(0.5);
+        let cells = fluid_block.cells();
+
+        let mut expected = vec![0.0; 9];
+        expected[0] = -2.0;
+        expected[1] = 2.0;
+        assert_eq!(cells.residuals().mass, expected);
+        assert_eq!(cells.residuals().energy, vec![0.0; 9]);
+        assert_eq!(cells.conserved_quantities().mass[0], -1.0);
+        assert_eq!(cells.conserved_quantities().mass[1], 1.0);
+    }
 }
@@ -1,8 +1,15 @@
 use std::{path::Path, collections::HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
 
 use crate::{fluid_block::FluidBlock, flow::FlowStates};
 use common::{DynamicResult, vector3::Vector3, number::Real};
-use grid::{cell::CellShape, interface::InterfaceShape, Vertex, Id, Interface, Cell, Block, block::{GridFileType, write_block}};
+use gas::gas_model::GasModel;
+use gas::gas_state::GasState;
+use grid::{cell::CellShape, interface::InterfaceShape, Vertex, Id, Interface, Cell, Block, block::{GridBlock, GridFileType, block_file_name, write_block}};
+
+/// The extension of the binary files the flow states are written to
+pub const FLOW_EXTENSION: &str = "flow";
 
 /// Light weight copy of vertex geometric data
 pub struct VertexIO {
@@ -87,12 +94,17 @@ impl Cell for CellIO {
 /// be on the GPU). Thus it copies the data.
 /// This is wastefull when the device can write to the file system. 
 /// But this is an optimisation for the future.
+///
+/// A [`FluidBlockIO`] read from a flow file with
+/// [`FluidBlockIO::from_flow_file`] has no fluid block, and
+/// writes the flow states it was read with.
 pub struct FluidBlockIO<'a> {
-    fluid_block: &'a FluidBlock,
+    fluid_block: Option<&'a FluidBlock>,
     flow_states: FlowStates,
     vertices: Vec<VertexIO>,
     interfaces: Vec<InterfaceIO>,
     cells: Vec<CellIO>,
+    boundaries: HashMap<String, Vec<usize>>,
     dimensions: u8,
     id: usize,
 }
@@ -103,19 +115,76 @@ impl<'a> FluidBlockIO<'a> {
         let interfaces = Vec::with_capacity(fluid_block.interfaces().len());
         let cells = Vec::with_capacity(fluid_block.cells().len());
         let flow_states = FlowStates::with_capacity(fluid_block.cells().len());
+        let boundaries = fluid_block.boundaries()
+            .iter()
+            .map(|boundary| (boundary.tag().to_string(), boundary.interfaces().to_vec()))
+            .collect();
         let dimensions = fluid_block.dimensions();
         let id = fluid_block.id();
         let mut fluid_block_io = FluidBlockIO{
-            fluid_block: &fluid_block, flow_states, vertices, interfaces, cells, dimensions, id
+            fluid_block: Some(fluid_block), flow_states, vertices, interfaces, cells, boundaries, dimensions, id
         };
-        fluid_block_io.copy_interfaces();
-        fluid_block_io.copy_cells();
+        fluid_block_io.copy_interfaces(fluid_block);
+        fluid_block_io.copy_cells(fluid_block);
         fluid_block_io
     }
 
+    /// Read the flow states written by [`FluidBlockIO::write_fluid_block`]
+    /// from `flow_path`, for the cells of `grid_block`. Only the density,
+    /// pressure and velocity are used; the gas model computes the rest of
+    /// the gas state from the density and pressure, so the flow states are
+    /// consistent with the gas model. The flow states can then be given to
+    /// a [`FluidBlock`] with [`FluidBlock::set_flow_states`] to restart a
+    /// simulation, or written again.
+    pub fn from_flow_file(grid_block: &GridBlock, flow_path: &Path, gm: &dyn GasModel<Real>) -> DynamicResult<FluidBlockIO<'a>> {
+        let n_cells = grid_block.cells().len();
+        let flow_file = File::open(flow_path)?;
+        let mut flow_states = FlowStates::read_binary(&mut BufReader::new(flow_file), n_cells)?;
+        for i in 0 .. n_cells {
+            let mut gas_state = GasState{rho: flow_states.rho[i], p: flow_states.p[i], ..GasState::default()};
+            gm.update_from_rhop(&mut gas_state)?;
+            flow_states.t[i] = gas_state.T;
+            flow_states.u[i] = gas_state.u;
+        }
+
+        let vertices = grid_block.vertices()
+            .iter()
+            .map(|vertex| VertexIO{pos: *vertex.pos(), id: vertex.id()})
+            .collect();
+        let interfaces = grid_block.interfaces()
+            .iter()
+            .map(|interface| InterfaceIO{
+                id: interface.id(),
+                shape: *interface.shape(),
+                vertex_ids: interface.vertex_ids().clone(),
+            })
+            .collect();
+        let cells = grid_block.cells()
+            .iter()
+            .map(|cell| CellIO{
+                id: cell.id(),
+                vertex_ids: cell.vertex_ids().clone(),
+                interface_ids: cell.interface_ids(),
+                shape: *cell.shape(),
+            })
+            .collect();
+        Ok(FluidBlockIO{
+            fluid_block: None,
+            flow_states,
+            vertices,
+            interfaces,
+            cells,
+            boundaries: grid_block.boundaries().clone(),
+            dimensions: grid_block.dimensions(),
+            id: grid_block.id(),
+        })
+    }
+
     pub fn write_fluid_block(&mut self, path: &Path) -> DynamicResult<()> {
-        self.copy_flow_state();
-        self.copy_vertex_positions();
+        if let Some(fluid_block) = self.fluid_block {
+            self.copy_flow_state(fluid_block);
+            self.copy_vertex_positions(fluid_block);
+        }
         self.write_to_file(path)?;
         Ok(())
     }
@@ -124,13 +193,18 @@ impl<'a> FluidBlockIO<'a> {
         self.id
     }
 
-    fn copy_flow_state(&mut self) {
-        self.flow_states = self.fluid_block.cells().flow_states().clone();
+    /// The flow states, as of the last write, or as read from a flow file
+    pub fn flow_states(&self) -> &FlowStates {
+        &self.flow_states
+    }
+
+    fn copy_flow_state(&mut self, fluid_block: &FluidBlock) {
+        self.flow_states.copy_from(fluid_block.cells().flow_states());
     }
 
-    fn copy_vertex_positions(&mut self) {
+    fn copy_vertex_positions(&mut self, fluid_block: &FluidBlock) {
         self.vertices.clear();
-        let vertices = self.fluid_block.vertices();
+        let vertices = fluid_block.vertices();
         for i_vtx in 0 .. vertices.len() {
             self.vertices.push(VertexIO{
                 pos: Vector3{
@@ -143,9 +217,9 @@ impl<'a> FluidBlockIO<'a> {
         }
     }
 
-    fn copy_interfaces(&mut self) {
+    fn copy_interfaces(&mut self, fluid_block: &FluidBlock) {
         self.interfaces.clear();
-        let interfaces = self.fluid_block.interfaces();
+        let interfaces = fluid_block.interfaces();
         for i_face in 0 .. interfaces.len() {
             self.interfaces.push(InterfaceIO{
                 id: i_face,
@@ -155,24 +229,32 @@ impl<'a> FluidBlockIO<'a> {
         }
     }
 
-    fn copy_cells(&mut self) {
+    fn copy_cells(&mut self, fluid_block: &FluidBlock) {
         self.cells.clear();
-        let cells = self.fluid_block.cells();
-        for i_cell in 0 .. cells.len() {
+        let cells = fluid_block.cells();
+        let cell_data = cells.vertices().iter()
+            .zip(cells.interfaces().iter())
+            .zip(cells.shapes().iter());
+        for (i_cell, ((vertex_ids, interface_ids), shape)) in cell_data.enumerate() {
             self.cells.push(CellIO{
                 id: i_cell,
-                vertex_ids: cells.vertices()[i_cell].to_vec(),
-                interface_ids: cells.interfaces()[i_cell].to_vec(),
-                shape: cells.shape()[i_cell],
+                vertex_ids: vertex_ids.clone(),
+                interface_ids: interface_ids.clone(),
+                shape: *shape,
             });
         }
     }
 
     fn write_to_file(&self, path: &Path) -> DynamicResult<()> {
         let mut file_path = path.to_path_buf();
-        let ext = GridFileType::Native.extension();
-        file_path.set_file_name(format!("blk{:0>4}.{}", self.id, ext));
+        file_path.set_file_name(block_file_name(self.id, GridFileType::Native.extension()));
         write_block(self, &file_path)?; 
+
+        // the flow states go in a companion binary file
+        file_path.set_file_name(block_file_name(self.id, FLOW_EXTENSION));
+        let mut writer = BufWriter::new(File::create(&file_path)?);
+        self.flow_states.write_binary(&mut writer)?;
+        writer.flush()?;
         Ok(())
     }
 }
@@ -191,7 +273,7 @@ impl<'a> Block<VertexIO, InterfaceIO, CellIO> for FluidBlockIO<'a> {
     }
 
     fn boundaries(&self) -> &HashMap<String, Vec<usize>> {
-        todo!()
+        &self.boundaries
     }
 
     fn dimensions(&self) -> u8 {
@@ -0,0 +1,174 @@
+use common::number::Real;
+use gas::flow_state::FlowState;
+
+/// Method of computing the convective flux through an interface
+/// from the flow states reconstructed either side of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FluxCalculator {
+    /// Hänel's variant of van Leer's flux vector splitting
+    Hanel,
+
+    /// The AUSMDV scheme of Wada and Liou, without the entropy fix
+    Ausmdv,
+}
+
+impl FluxCalculator {
+    /// The flux of [mass, x momentum, y momentum, z momentum, energy] per
+    /// unit area, from the `left` state to the `right` state. The velocities
+    /// are in the frame of the interface, so x is along the normal, which
+    /// points from the left state to the right state.
+    pub fn flux(&self, left: &FlowState<Real>, right: &FlowState<Real>) -> [Real; 5] {
+        let (left, right) = (SplitState::new(left), SplitState::new(right));
+        match self {
+            FluxCalculator::Hanel => hanel(&left, &right),
+            FluxCalculator::Ausmdv => ausmdv(&left, &right),
+        }
+    }
+}
+
+/// The parts of a flow state the flux calculators need
+struct SplitState {
+    rho: Real,
+    p: Real,
+    a: Real,
+    vel: [Real; 3],
+
+    // the total enthalpy per unit mass
+    h: Real,
+}
+
+impl SplitState {
+    fn new(flow_state: &FlowState<Real>) -> SplitState {
+        let gas_state = flow_state.gas_state();
+        let vel = flow_state.velocity();
+        let ke = 0.5 * (vel.x * vel.x + vel.y * vel.y + vel.z * vel.z);
+        SplitState {
+            rho: gas_state.rho,
+            p: gas_state.p,
+            a: gas_state.a,
+            vel: [vel.x, vel.y, vel.z],
+            h: gas_state.u + gas_state.p / gas_state.rho + ke,
+        }
+    }
+}
+
+fn hanel(left: &SplitState, right: &SplitState) -> [Real; 5] {
+    let (ul, ur) = (left.vel[0], right.vel[0]);
+    let (ml, mr) = (ul / left.a, ur / right.a);
+
+    let (ul_plus, pl_plus) = match ml.abs() <= 1.0 {
+        true => (0.25 * left.a * (ml + 1.0).powi(2), 0.25 * left.p * (ml + 1.0).powi(2) * (2.0 - ml)),
+        false if ul > 0.0 => (ul, left.p),
+        false => (0.0, 0.0),
+    };
+    let (ur_minus, pr_minus) = match mr.abs() <= 1.0 {
+        true => (-0.25 * right.a * (mr - 1.0).powi(2), 0.25 * right.p * (mr - 1.0).powi(2) * (2.0 + mr)),
+        false if ur < 0.0 => (ur, right.p),
+        false => (0.0, 0.0),
+    };
+
+    let (mass_l, mass_r) = (ul_plus * left.rho, ur_minus * right.rho);
+    [
+        mass_l + mass_r,
+        mass_l * ul + mass_r * ur + pl_plus + pr_minus,
+        mass_l * left.vel[1] + mass_r * right.vel[1],
+        mass_l * left.vel[2] + mass_r * right.vel[2],
+        mass_l * left.h + mass_r * right.h,
+    ]
+}
+
+/// How strongly the momentum flux switches from AUSMD
+/// to AUSMV across pressure differences
+const K_SWITCH: Real = 10.0;
+
+fn ausmdv(left: &SplitState, right: &SplitState) -> [Real; 5] {
+    let (ul, ur) = (left.vel[0], right.vel[0]);
+    let am = left.a.max(right.a);
+    let (ml, mr) = (ul / am, ur / am);
+
+    let (pl_rho, pr_rho) = (left.p / left.rho, right.p / right.rho);
+    let alpha_l = 2.0 * pl_rho / (pl_rho + pr_rho);
+    let alpha_r = 2.0 * pr_rho / (pl_rho + pr_rho);
+    let dul = 0.5 * (ul + ul.abs());
+    let dur = 0.5 * (ur - ur.abs());
+
+    let (pl_plus, ul_plus) = match ml.abs() <= 1.0 {
+        true => (
+            0.25 * left.p * (ml + 1.0).powi(2) * (2.0 - ml),
+            alpha_l * ((ul + am).powi(2) / (4.0 * am) - dul) + dul,
+        ),
+        false => (left.p * dul / ul, dul),
+    };
+    let (pr_minus, ur_minus) = match mr.abs() <= 1.0 {
+        true => (
+            0.25 * right.p * (mr - 1.0).powi(2) * (2.0 + mr),
+            alpha_r * (-(ur - am).powi(2) / (4.0 * am) - dur) + dur,
+        ),
+        false => (right.p * dur / ur, dur),
+    };
+
+    let mass = ul_plus * left.rho + ur_minus * right.rho;
+    let momentum_ausmv = ul_plus * left.rho * ul + ur_minus * right.rho * ur;
+    let momentum_ausmd = 0.5 * (mass * (ul + ur) - mass.abs() * (ur - ul));
+    let s = 0.5 * Real::min(1.0, K_SWITCH * (right.p - left.p).abs() / left.p.min(right.p));
+    let momentum = (0.5 + s) * momentum_ausmv + (0.5 - s) * momentum_ausmd;
+
+    let upwind = if mass >= 0.0 { left } else { right };
+    [
+        mass,
+        momentum + pl_plus + pr_minus,
+        mass * upwind.vel[1],
+        mass * upwind.vel[2],
+        mass * upwind.h,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use common::vector3::Vector3;
+    use gas::gas_model::GasModel;
+    use gas::gas_state::GasState;
+    use gas::ideal_gas::IdealGas;
+    use super::*;
+
+    fn flow_state(p: Real, temp: Real, vel: Vector3) -> FlowState<Real> {
+        let gm = IdealGas::new(287.0, 1.4);
+        let mut gas_state = GasState{p, T: temp, ..GasState::default()};
+        gm.update_from_pT(&mut gas_state).unwrap();
+        FlowState::new(gas_state, vel)
+    }
+
+    /// The exact Euler flux of a single state
+    fn euler_flux(state: &FlowState<Real>) -> [Real; 5] {
+        let split = SplitState::new(state);
+        let mass = split.rho * split.vel[0];
+        [mass, mass * split.vel[0] + split.p, mass * split.vel[1], mass * split.vel[2], mass * split.h]
+    }
+
+    fn assert_close(a: [Real; 5], b: [Real; 5]) {
+        for (a, b) in a.iter().zip(b.iter()) {
+            assert!((a - b).abs() <= 1e-9 * (1.0 + b.abs()), "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn consistent_with_euler_flux() {
+        for vel_x in [-800.0, -100.0, 0.0, 50.0, 300.0, 1000.0] {
+            let state = flow_state(101325.0, 300.0, Vector3{x: vel_x, y: 20.0, z: -5.0});
+            for calculator in [FluxCalculator::Hanel, FluxCalculator::Ausmdv] {
+                assert_close(calculator.flux(&state, &state), euler_flux(&state));
+            }
+        }
+    }
+
+    #[test]
+    fn supersonic_upwinding() {
+        let left = flow_state(101325.0, 300.0, Vector3{x: 1000.0, y: 0.0, z: 0.0});
+        let right = flow_state(50000.0, 400.0, Vector3{x: 800.0, y: 10.0, z: 0.0});
+
+        for calculator in [FluxCalculator::Hanel, FluxCalculator::Ausmdv] {
+            assert_close(calculator.flux(&left, &right), euler_flux(&left));
+            assert_close(calculator.flux(&right, &left), euler_flux(&right));
+        }
+    }
+}
@@ -1,8 +1,12 @@
 use common::number::Real;
-use common::vector3::ArrayVec3;
-use grid::interface::InterfaceShape;
+use common::vector3::{ArrayVec3, Vector3};
+use grid::interface::{GridInterface, InterfaceShape};
+use grid::Interface;
+use gas::flow_state::FlowState;
+use gas::gas_state::GasState;
 use crate::util::Ids;
-use crate::flow::FlowStates;
+use crate::flow::{FlowStates, ConservedQuantities};
+use crate::flux::FluxCalculator;
 
 pub struct Interfaces {
     vertex_ids: Ids,
@@ -21,42 +25,324 @@ pub struct Interfaces {
     left_flow_states: FlowStates,
     right_flow_states: FlowStates,
 
+    // the flux of each conserved quantity through the interface,
+    // per unit area, in the direction of the normal
+    flux: ConservedQuantities,
+
+    // the heat flux into the fluid through each wall, per unit area
+    heat_flux: Vec<Real>,
+
     // the centre of the interface
-    centre: Vec<Real>,
+    centre: ArrayVec3,
 
     length: usize,
 }
 
 impl Interfaces {
+    /// Copy the geometric data out of the interfaces of a grid
+    pub fn new(grid_interfaces: &[GridInterface]) -> Interfaces {
+        let length = grid_interfaces.len();
+        let vertex_ids = Ids::from_interfaces(grid_interfaces);
+        let area = grid_interfaces.iter().map(|iface| iface.area()).collect();
+        let shape = grid_interfaces.iter().map(|iface| *iface.shape()).collect();
+        let norm: Vec<Vector3> = grid_interfaces.iter().map(|iface| iface.norm()).collect();
+        let t1: Vec<Vector3> = grid_interfaces.iter().map(|iface| iface.t1()).collect();
+        let t2: Vec<Vector3> = grid_interfaces.iter().map(|iface| iface.t2()).collect();
+        let centre: Vec<Vector3> = grid_interfaces.iter().map(|iface| iface.centre()).collect();
+        Interfaces {
+            vertex_ids,
+            area,
+            shape,
+            norm: ArrayVec3::from_vector3s(&norm),
+            t1: ArrayVec3::from_vector3s(&t1),
+            t2: ArrayVec3::from_vector3s(&t2),
+            left_flow_states: FlowStates::zeros(length),
+            right_flow_states: FlowStates::zeros(length),
+            flux: ConservedQuantities::zeros(length),
+            heat_flux: vec![0.0; length],
+            centre: ArrayVec3::from_vector3s(&centre),
+            length,
+        }
+    }
+
+    /// No interfaces yet, with room for `capacity` interfaces
+    /// to be added with [`Interfaces::push_interface`]
+    pub fn with_capacity(capacity: usize) -> Interfaces {
+        Interfaces {
+            vertex_ids: Ids::with_capacity(capacity),
+            area: Vec::with_capacity(capacity),
+            shape: Vec::with_capacity(capacity),
+            norm: ArrayVec3::with_capacity(capacity),
+            t1: ArrayVec3::with_capacity(capacity),
+            t2: ArrayVec3::with_capacity(capacity),
+            left_flow_states: FlowStates::with_capacity(capacity),
+            right_flow_states: FlowStates::with_capacity(capacity),
+            flux: ConservedQuantities::with_capacity(capacity),
+            heat_flux: Vec::with_capacity(capacity),
+            centre: ArrayVec3::with_capacity(capacity),
+            length: 0,
+        }
+    }
+
+    /// Add an interface to the end. The reconstructed flow states,
+    /// flux and heat flux through the interface start at zero.
+    #[allow(clippy::too_many_arguments)]
+    pub fn push_interface(&mut self, shape: InterfaceShape, area: Real, n: Vector3, t1: Vector3,
+                          t2: Vector3, centre: Vector3, vertex_ids: Vec<usize>) {
+        self.vertex_ids.push(&vertex_ids);
+        self.area.push(area);
+        self.shape.push(shape);
+        self.norm.push(&n);
+        self.t1.push(&t1);
+        self.t2.push(&t2);
+        self.left_flow_states.push(&FlowState::new(GasState::default(), Vector3::default()));
+        self.right_flow_states.push(&FlowState::new(GasState::default(), Vector3::default()));
+        self.flux.push([0.0; 5]);
+        self.heat_flux.push(0.0);
+        self.centre.push(&centre);
+        self.length += 1;
+    }
+
     pub fn vertices(&self) -> &Ids {
         &self.vertex_ids
     }
 
-    pub fn area(&self) -> &[Real] {
+    /// The area of each interface
+    pub fn areas(&self) -> &[Real] {
         &self.area
     }
 
-    pub fn norm(&self) -> &ArrayVec3 {
+    /// The unit normal of each interface
+    pub fn normals(&self) -> &ArrayVec3 {
         &self.norm
     }
 
+    /// The first unit tangent of each interface
     pub fn t1(&self) -> &ArrayVec3 {
         &self.t1
     }
 
+    /// The second unit tangent of each interface
     pub fn t2(&self) -> &ArrayVec3 {
         &self.t2
     }
 
-    pub fn centre(&self) -> &[Real] {
+    /// The geometric centre of each interface
+    pub fn centres(&self) -> &ArrayVec3 {
         &self.centre
     }
 
+    #[deprecated(note = "use `Interfaces::areas` instead")]
+    pub fn area(&self) -> &[Real] {
+        self.areas()
+    }
+
+    #[deprecated(note = "use `Interfaces::normals` instead")]
+    pub fn norm(&self) -> &ArrayVec3 {
+        self.normals()
+    }
+
+    #[deprecated(note = "use `Interfaces::centres` instead")]
+    pub fn centre(&self) -> &ArrayVec3 {
+        self.centres()
+    }
+
+    /// The flux of each conserved quantity through each interface,
+    /// per unit area, in the direction of the normal
+    pub fn fluxes(&self) -> &ConservedQuantities {
+        &self.flux
+    }
+
+    pub fn fluxes_mut(&mut self) -> &mut ConservedQuantities {
+        &mut self.flux
+    }
+
+    /// Compute the flux through each interface from the flow states
+    /// reconstructed either side of it. The velocities are rotated into
+    /// the frame of the interface for the flux calculator, and the
+    /// momentum flux is rotated back.
+    pub fn compute_fluxes(&mut self, calculator: FluxCalculator) {
+        for i in 0 .. self.length {
+            let n = Vector3{x: self.norm.x[i], y: self.norm.y[i], z: self.norm.z[i]};
+            let t1 = Vector3{x: self.t1.x[i], y: self.t1.y[i], z: self.t1.z[i]};
+            let t2 = Vector3{x: self.t2.x[i], y: self.t2.y[i], z: self.t2.z[i]};
+            let to_local = |state: FlowState<Real>| {
+                let vel = state.velocity();
+                FlowState::new(*state.gas_state(), Vector3{x: vel.dot(&n), y: vel.dot(&t1), z: vel.dot(&t2)})
+            };
+            let left = to_local(self.left_flow_states.flow_state(i));
+            let right = to_local(self.right_flow_states.flow_state(i));
+            let [mass, momentum_n, momentum_t1, momentum_t2, energy] = calculator.flux(&left, &right);
+            self.flux.mass[i] = mass;
+            self.flux.momentum_x[i] = momentum_n * n.x + momentum_t1 * t1.x + momentum_t2 * t2.x;
+            self.flux.momentum_y[i] = momentum_n * n.y + momentum_t1 * t1.y + momentum_t2 * t2.y;
+            self.flux.momentum_z[i] = momentum_n * n.z + momentum_t1 * t1.z + momentum_t2 * t2.z;
+            self.flux.energy[i] = energy;
+        }
+    }
+
+    /// The flow state reconstructed on the side of each
+    /// interface the normal points away from
+    pub fn left_flow_states(&self) -> &FlowStates {
+        &self.left_flow_states
+    }
+
+    pub fn left_flow_states_mut(&mut self) -> &mut FlowStates {
+        &mut self.left_flow_states
+    }
+
+    /// The flow state reconstructed on the side of each
+    /// interface the normal points towards
+    pub fn right_flow_states(&self) -> &FlowStates {
+        &self.right_flow_states
+    }
+
+    pub fn right_flow_states_mut(&mut self) -> &mut FlowStates {
+        &mut self.right_flow_states
+    }
+
+    /// The heat flux into the fluid through each interface, per unit
+    /// area. This is only non-zero on walls with a set temperature.
+    pub fn heat_fluxes(&self) -> &[Real] {
+        &self.heat_flux
+    }
+
+    pub fn heat_fluxes_mut(&mut self) -> &mut [Real] {
+        &mut self.heat_flux
+    }
+
     pub fn len(&self) -> usize {
         self.length
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// The shape of each interface
     pub fn shape(&self) -> &[InterfaceShape] {
         &self.shape
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use grid::block::BlockCollection;
+    use grid::Block;
+    use super::*;
+
+    fn create_interfaces() -> Interfaces {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+        Interfaces::new(block_collection.get_block(0).interfaces())
+    }
+
+    #[test]
+    fn push_interfaces() {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+        let grid_interfaces = block_collection.get_block(0).interfaces();
+        let bulk = create_interfaces();
+        let mut interfaces = Interfaces::with_capacity(grid_interfaces.len());
+        assert!(interfaces.is_empty());
+        for iface in grid_interfaces.iter() {
+            interfaces.push_interface(*iface.shape(), iface.area(), iface.norm(), iface.t1(), iface.t2(),
+                                      iface.centre(), iface.vertex_ids().clone());
+        }
+
+        assert_eq!(interfaces.len(), bulk.len());
+        for i in 0 .. interfaces.len() {
+            assert_eq!(interfaces.vertices()[i], bulk.vertices()[i]);
+        }
+        assert_eq!(interfaces.areas(), bulk.areas());
+        assert_eq!(interfaces.shape(), bulk.shape());
+        assert_eq!(interfaces.normals().x, bulk.normals().x);
+        assert_eq!(interfaces.normals().y, bulk.normals().y);
+        assert_eq!(interfaces.t1().x, bulk.t1().x);
+        assert_eq!(interfaces.t2().z, bulk.t2().z);
+        assert_eq!(interfaces.centres().x, bulk.centres().x);
+        assert_eq!(interfaces.centres().y, bulk.centres().y);
+        assert_eq!(interfaces.left_flow_states().p, bulk.left_flow_states().p);
+        assert_eq!(interfaces.fluxes().mass, bulk.fluxes().mass);
+        assert_eq!(interfaces.heat_fluxes(), bulk.heat_fluxes());
+    }
+
+    #[test]
+    fn len() {
+        let interfaces = create_interfaces();
+
+        assert_eq!(interfaces.len(), 24);
+    }
+
+    #[test]
+    fn areas() {
+        let interfaces = create_interfaces();
+
+        assert_eq!(interfaces.areas(), &[1.0; 24]);
+    }
+
+    #[test]
+    fn normals() {
+        let interfaces = create_interfaces();
+        let normals = interfaces.normals();
+
+        // the first cell is traversed anti-clockwise, so the normals point outwards
+        assert_eq!(normals.x[0 .. 4], [0.0, 1.0, 0.0, -1.0]);
+        assert_eq!(normals.y[0 .. 4], [-1.0, 0.0, 1.0, 0.0]);
+        assert_eq!(normals.z[0 .. 4], [0.0; 4]);
+    }
+
+    #[test]
+    fn tangents() {
+        let interfaces = create_interfaces();
+
+        assert_eq!(interfaces.t1().x[0 .. 4], [1.0, 0.0, -1.0, 0.0]);
+        assert_eq!(interfaces.t1().y[0 .. 4], [0.0, 1.0, 0.0, -1.0]);
+        assert_eq!(interfaces.t2().z, vec![1.0; 24]);
+    }
+
+    #[test]
+    fn centres() {
+        let interfaces = create_interfaces();
+
+        assert_eq!(interfaces.centres().x[0 .. 4], [0.5, 1.0, 0.5, 0.0]);
+        assert_eq!(interfaces.centres().y[0 .. 4], [0.0, 0.5, 1.0, 0.5]);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_accessors() {
+        let interfaces = create_interfaces();
+
+        assert_eq!(interfaces.area(), interfaces.areas());
+        assert_eq!(interfaces.norm().x, interfaces.normals().x);
+        assert_eq!(interfaces.centre().y, interfaces.centres().y);
+    }
+
+    #[test]
+    fn uniform_flux() {
+        let mut interfaces = create_interfaces();
+        let gas_state = GasState{p: 1.0, T: 1.0, rho: 1.0, u: 2.5, a: Real::sqrt(1.4)};
+        let state = FlowState::new(gas_state, Vector3{x: 0.5, y: 0.0, z: 0.0});
+        interfaces.left_flow_states_mut().set_all(&state);
+        interfaces.right_flow_states_mut().set_all(&state);
+        interfaces.compute_fluxes(FluxCalculator::Ausmdv);
+
+        // the normal of interface 0 points in -y, and interface 1 in +x
+        let fluxes = interfaces.fluxes();
+        assert!(fluxes.mass[0].abs() < 1e-12);
+        assert!((fluxes.momentum_y[0] + 1.0).abs() < 1e-12);
+        assert!((fluxes.mass[1] - 0.5).abs() < 1e-12);
+        assert!((fluxes.momentum_x[1] - 1.25).abs() < 1e-12);
+        assert!((fluxes.energy[1] - 0.5 * (2.5 + 1.0 + 0.125)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn shape() {
+        let interfaces = create_interfaces();
+
+        assert_eq!(interfaces.shape(), &[InterfaceShape::Line; 24]);
+    }
+}
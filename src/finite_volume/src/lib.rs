@@ -8,4 +8,11 @@ pub mod interface;
 pub mod cells;
 pub mod util;
 pub mod flow;
+
+// compute the flow states either side of the interfaces
+pub mod reconstruction;
+
+// compute the convective fluxes through the interfaces
+pub mod flux;
+
 pub mod boundary_conditions;
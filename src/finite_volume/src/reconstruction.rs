@@ -0,0 +1,183 @@
+use common::number::Real;
+use common::vector3::Vector3;
+use gas::flow_state::{FlowState, FlowStateGrad};
+use gas::gas_model::{GasModel, GasModelError};
+
+use crate::cells::Cells;
+use crate::interface::Interfaces;
+
+/// The order of accuracy of the flow states reconstructed
+/// either side of each interface from the cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconstructionOrder {
+    /// Use the flow state in the cell
+    First,
+
+    /// Extrapolate the flow state in the cell to the interface with the
+    /// gradient of the flow in the cell. The gradient isn't limited, so
+    /// this is only suitable for smooth flows.
+    Second,
+}
+
+/// Reconstruct the flow states either side of each interface from the cells
+/// behind and in front of it, given by `interface_cells`. Interfaces on a
+/// boundary get the state of their cell on both sides, for the boundary
+/// conditions to replace on the outside.
+pub(crate) fn reconstruct(order: ReconstructionOrder, cells: &Cells, interfaces: &mut Interfaces,
+                          interface_cells: &[[Option<usize>; 2]], dimensions: u8, gm: &dyn GasModel<Real>)
+    -> Result<(), GasModelError>
+{
+    let gradients = match order {
+        ReconstructionOrder::First => None,
+        ReconstructionOrder::Second => Some(cell_gradients(cells, interface_cells, dimensions)),
+    };
+    for (face, face_cells) in interface_cells.iter().enumerate() {
+        let centres = interfaces.centres();
+        let face_centre = Vector3{x: centres.x[face], y: centres.y[face], z: centres.z[face]};
+        let state = |cell: usize| extrapolate(cells, cell, &face_centre, gradients.as_deref(), gm);
+        let (left, right) = match *face_cells {
+            [Some(behind), Some(in_front)] => (state(behind)?, state(in_front)?),
+            [Some(cell), None] | [None, Some(cell)] => {
+                let inside = state(cell)?;
+                (inside, inside)
+            }
+            [None, None] => continue,
+        };
+        interfaces.left_flow_states_mut().set_flow_state(face, &left);
+        interfaces.right_flow_states_mut().set_flow_state(face, &right);
+    }
+    Ok(())
+}
+
+/// The flow state in `cell` extrapolated to `point`, if there are gradients.
+/// If the extrapolated density or pressure isn't positive, the flow state
+/// in the cell is used instead.
+fn extrapolate(cells: &Cells, cell: usize, point: &Vector3, gradients: Option<&[FlowStateGrad]>,
+               gm: &dyn GasModel<Real>) -> Result<FlowState<Real>, GasModelError>
+{
+    let cell_state = cells.flow_states().flow_state(cell);
+    let gradients = match gradients {
+        Some(gradients) => gradients,
+        None => return Ok(cell_state),
+    };
+    let centres = cells.centres();
+    let delta = Vector3{
+        x: point.x - centres.x[cell],
+        y: point.y - centres.y[cell],
+        z: point.z - centres.z[cell],
+    };
+    let mut state = FlowState::extrapolate(&cell_state, &gradients[cell], &delta);
+    if state.gas_state().rho <= 0.0 || state.gas_state().p <= 0.0 {
+        return Ok(cell_state);
+    }
+    gm.update_from_rhop(state.gas_state_mut())?;
+    Ok(state)
+}
+
+/// The gradient of the density, pressure and velocity in each cell, from a
+/// least squares fit to the flow states in the cells sharing an interface
+/// with it, weighted by the inverse square of the distance to them. The
+/// gradients of the other fields are left at zero, since the reconstructed
+/// states get them from the gas model.
+fn cell_gradients(cells: &Cells, interface_cells: &[[Option<usize>; 2]], dimensions: u8) -> Vec<FlowStateGrad> {
+    let fs = cells.flow_states();
+    let centres = cells.centres();
+    let fields = [&fs.rho, &fs.p, &fs.vel_x, &fs.vel_y, &fs.vel_z];
+    (0 .. cells.len())
+        .map(|cell| {
+            let mut normal_matrix = [[0.0; 3]; 3];
+            let mut rhs = [[0.0; 3]; 5];
+            for &face in cells.interfaces()[cell].iter() {
+                let neighbour = match interface_cells[face] {
+                    [Some(a), Some(b)] => if a == cell { b } else { a },
+                    _ => continue,
+                };
+                let d = [
+                    centres.x[neighbour] - centres.x[cell],
+                    centres.y[neighbour] - centres.y[cell],
+                    centres.z[neighbour] - centres.z[cell],
+                ];
+                let weight = 1.0 / (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]);
+                for (row, d_row) in normal_matrix.iter_mut().zip(d.iter()) {
+                    for (entry, d_col) in row.iter_mut().zip(d.iter()) {
+                        *entry += weight * d_row * d_col;
+                    }
+                }
+                for (rhs, field) in rhs.iter_mut().zip(fields.iter()) {
+                    let difference = field[neighbour] - field[cell];
+                    for (entry, d) in rhs.iter_mut().zip(d.iter()) {
+                        *entry += weight * d * difference;
+                    }
+                }
+            }
+            let [rho, p, vel_x, vel_y, vel_z] = rhs.map(|rhs| solve_least_squares(&normal_matrix, &rhs, dimensions));
+            FlowStateGrad { rho, p, vel_x, vel_y, vel_z, ..FlowStateGrad::default() }
+        })
+        .collect()
+}
+
+/// Solve the normal equations of the least squares fit for the gradient,
+/// ignoring z in two dimensions. The gradient is zero if the cell doesn't
+/// have enough neighbours to fit it.
+fn solve_least_squares(m: &[[Real; 3]; 3], rhs: &[Real; 3], dimensions: u8) -> Vector3 {
+    // the weights make the matrix dimensionless, so an absolute tolerance is fine
+    const SINGULAR: Real = 1e-12;
+    if dimensions == 2 {
+        let det = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+        if det.abs() < SINGULAR {
+            return Vector3::default();
+        }
+        return Vector3{
+            x: (rhs[0] * m[1][1] - rhs[1] * m[0][1]) / det,
+            y: (m[0][0] * rhs[1] - m[1][0] * rhs[0]) / det,
+            z: 0.0,
+        };
+    }
+    let det3 = |c0: [Real; 3], c1: [Real; 3], c2: [Real; 3]| {
+        c0[0] * (c1[1] * c2[2] - c1[2] * c2[1])
+            - c1[0] * (c0[1] * c2[2] - c0[2] * c2[1])
+            + c2[0] * (c0[1] * c1[2] - c0[2] * c1[1])
+    };
+    let columns = [
+        [m[0][0], m[1][0], m[2][0]],
+        [m[0][1], m[1][1], m[2][1]],
+        [m[0][2], m[1][2], m[2][2]],
+    ];
+    let det = det3(columns[0], columns[1], columns[2]);
+    if det.abs() < SINGULAR {
+        return Vector3::default();
+    }
+    // Cramer's rule
+    Vector3{
+        x: det3(*rhs, columns[1], columns[2]) / det,
+        y: det3(columns[0], *rhs, columns[2]) / det,
+        z: det3(columns[0], columns[1], *rhs) / det,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn least_squares_two_dimensions() {
+        // z is ignored in two dimensions, so the matrix is only singular in three
+        let m = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.0]];
+        let rhs = [3.0, -1.0, 0.0];
+
+        assert_eq!(solve_least_squares(&m, &rhs, 2), Vector3{x: 3.0, y: -1.0, z: 0.0});
+        assert_eq!(solve_least_squares(&m, &rhs, 3), Vector3::default());
+    }
+
+    #[test]
+    fn least_squares_three_dimensions() {
+        let m = [[2.0, 0.0, 1.0], [0.0, 1.0, 0.0], [1.0, 0.0, 2.0]];
+        let gradient = [1.0, 2.0, -3.0];
+        let rhs = std::array::from_fn(|row| (0 .. 3).map(|col| m[row][col] * gradient[col]).sum());
+
+        let solution = solve_least_squares(&m, &rhs, 3);
+        assert!((solution.x - 1.0).abs() < 1e-12);
+        assert!((solution.y - 2.0).abs() < 1e-12);
+        assert!((solution.z + 3.0).abs() < 1e-12);
+    }
+}
@@ -1,9 +1,17 @@
 use std::ops::Index;
 
+use common::number::Real;
+use gas::flow_state::FlowState;
 use grid::cell::GridCell;
 use grid::interface::GridInterface;
 use grid::{Cell, Interface};
 
+use crate::cells::Cells;
+use crate::flow::FlowStates;
+
+/// An analytical solution, giving the flow state at (x, y, z)
+pub type AnalyticalSolution<'a> = &'a dyn Fn(Real, Real, Real) -> FlowState<Real>;
+
 /// Keep track of the ids of objects forming another object.
 /// For example, the id's of the interfaces surrounding a cell.
 /// We store it dynamically since we don't know how many interfaces
@@ -24,7 +32,20 @@ impl Index<usize> for Ids {
 }
 
 impl Ids {
-    pub fn from_interfaces(interfaces: &Vec<GridInterface>) -> Ids {
+    /// No objects yet, with room for the ids of `capacity` objects
+    pub fn with_capacity(capacity: usize) -> Ids {
+        let mut offsets = Vec::with_capacity(capacity + 1);
+        offsets.push(0);
+        Ids {ids: Vec::new(), offsets}
+    }
+
+    /// Add the ids of another object
+    pub fn push(&mut self, ids: &[usize]) {
+        self.ids.extend(ids);
+        self.offsets.push(self.ids.len());
+    }
+
+    pub fn from_interfaces(interfaces: &[GridInterface]) -> Ids {
         let capacity = interfaces.len();
         let mut offsets: Vec<usize> = Vec::with_capacity(capacity);
         let mut ids: Vec<usize> = Vec::new();
@@ -36,7 +57,7 @@ impl Ids {
         Ids {ids, offsets}
     }
 
-    pub fn from_cells(cells: &Vec<GridCell>) -> (Ids, Ids) {
+    pub fn from_cells(cells: &[GridCell]) -> (Ids, Ids) {
         let capacity = cells.len();
         let mut interface_offsets: Vec<usize> = Vec::with_capacity(capacity);
         let mut vertex_offsets: Vec<usize> = Vec::with_capacity(capacity);
@@ -56,3 +77,112 @@ impl Ids {
         )
     }
 }
+
+/// The volume weighted L2 norm of the error in `flow_states` compared to
+/// `analytical` evaluated at the centre of each cell. The squared errors
+/// in pressure, temperature, density and each component of velocity are
+/// summed, so the analytical flow states need all of these set.
+pub fn l2_error_norm(cells: &Cells, flow_states: &FlowStates, analytical: AnalyticalSolution) -> Real {
+    l2_error(cells, flow_states, analytical, |fs, i, exact| {
+        let gas_state = exact.gas_state();
+        let velocity = exact.velocity();
+        (fs.p[i] - gas_state.p).powi(2)
+            + (fs.t[i] - gas_state.T).powi(2)
+            + (fs.rho[i] - gas_state.rho).powi(2)
+            + (fs.vel_x[i] - velocity.x).powi(2)
+            + (fs.vel_y[i] - velocity.y).powi(2)
+            + (fs.vel_z[i] - velocity.z).powi(2)
+    })
+}
+
+/// The volume weighted L2 norm of the error in pressure
+pub fn l2_error_pressure(cells: &Cells, flow_states: &FlowStates, analytical: AnalyticalSolution) -> Real {
+    l2_error(cells, flow_states, analytical, |fs, i, exact| (fs.p[i] - exact.gas_state().p).powi(2))
+}
+
+/// The volume weighted L2 norm of the error in temperature
+pub fn l2_error_temperature(cells: &Cells, flow_states: &FlowStates, analytical: AnalyticalSolution) -> Real {
+    l2_error(cells, flow_states, analytical, |fs, i, exact| (fs.t[i] - exact.gas_state().T).powi(2))
+}
+
+/// The volume weighted L2 norm of the error in density
+pub fn l2_error_density(cells: &Cells, flow_states: &FlowStates, analytical: AnalyticalSolution) -> Real {
+    l2_error(cells, flow_states, analytical, |fs, i, exact| (fs.rho[i] - exact.gas_state().rho).powi(2))
+}
+
+/// The volume weighted L2 norm of the magnitude of the error in velocity
+pub fn l2_error_velocity(cells: &Cells, flow_states: &FlowStates, analytical: AnalyticalSolution) -> Real {
+    l2_error(cells, flow_states, analytical, |fs, i, exact| {
+        let velocity = exact.velocity();
+        (fs.vel_x[i] - velocity.x).powi(2)
+            + (fs.vel_y[i] - velocity.y).powi(2)
+            + (fs.vel_z[i] - velocity.z).powi(2)
+    })
+}
+
+/// Sum `squared_error` of each cell, weighted by the cell volume,
+/// and take the square root
+fn l2_error<F>(cells: &Cells, flow_states: &FlowStates, analytical: AnalyticalSolution, squared_error: F) -> Real
+    where F: Fn(&FlowStates, usize, &FlowState<Real>) -> Real
+{
+    assert_eq!(flow_states.len(), cells.len(), "Expected a flow state for each cell");
+    let centres = cells.centres();
+    let sum: Real = cells.volumes()
+        .iter()
+        .enumerate()
+        .map(|(i, volume)| {
+            let exact = analytical(centres.x[i], centres.y[i], centres.z[i]);
+            squared_error(flow_states, i, &exact) * volume
+        })
+        .sum();
+    sum.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use common::vector3::Vector3;
+    use gas::gas_state::GasState;
+    use grid::block::BlockCollection;
+    use grid::Block;
+    use super::*;
+
+    fn linear_flow(x: Real, _y: Real, _z: Real) -> FlowState<Real> {
+        let gas_state = GasState{p: 1000.0 * x, T: 300.0, rho: 1.0, ..GasState::default()};
+        FlowState::new(gas_state, Vector3{x: 100.0, y: 0.0, z: 0.0})
+    }
+
+    fn create_cells() -> Cells {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+        let block = block_collection.get_block(0);
+        let mut flow_states = FlowStates::with_capacity(block.cells().len());
+        for cell in block.cells().iter() {
+            let centre = cell.centre();
+            flow_states.push(&linear_flow(centre.x, centre.y, centre.z));
+        }
+        Cells::new(block.cells(), flow_states)
+    }
+
+    #[test]
+    fn exact_solution_has_no_error() {
+        let cells = create_cells();
+
+        assert_eq!(l2_error_norm(&cells, cells.flow_states(), &linear_flow), 0.0);
+    }
+
+    #[test]
+    fn l2_error() {
+        let cells = create_cells();
+        let mut flow_states = cells.flow_states().clone();
+        flow_states.p[4] += 3.0;
+        flow_states.vel_y[0] += 4.0;
+
+        assert_eq!(l2_error_pressure(&cells, &flow_states, &linear_flow), 3.0);
+        assert_eq!(l2_error_velocity(&cells, &flow_states, &linear_flow), 4.0);
+        assert_eq!(l2_error_temperature(&cells, &flow_states, &linear_flow), 0.0);
+        assert_eq!(l2_error_density(&cells, &flow_states, &linear_flow), 0.0);
+        assert_eq!(l2_error_norm(&cells, &flow_states, &linear_flow), 5.0);
+    }
+}
@@ -0,0 +1,82 @@
+//! Helpers for the tests which compare the solver with a known
+//! (rho, vx, vy, p) solution of the Euler equations on the unit square
+
+// each test uses a different subset of the helpers
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::PathBuf;
+
+use ::common::number::Real;
+use ::common::vector3::Vector3;
+use finite_volume::fluid_block::FluidBlock;
+use gas::flow_state::FlowState;
+use gas::gas_model::GasModel;
+use gas::gas_state::GasState;
+use gas::ideal_gas::IdealGas;
+use grid::structured::rectangle_su2;
+
+pub const GAMMA: Real = 1.4;
+
+/// Step for the central difference approximation of the divergence
+const H: Real = 1e-6;
+
+/// The flux of mass, momentum and energy of the state
+/// (rho, vx, vy, p) through a surface with unit normal (nx, ny)
+pub fn euler_flux([rho, vx, vy, p]: [Real; 4], nx: Real, ny: Real) -> [Real; 5] {
+    let vn = vx * nx + vy * ny;
+    let total_energy = p / ((GAMMA - 1.0) * rho) + 0.5 * (vx * vx + vy * vy);
+    [
+        rho * vn,
+        rho * vx * vn + p * nx,
+        rho * vy * vn + p * ny,
+        0.0,
+        (rho * total_energy + p) * vn,
+    ]
+}
+
+/// The divergence at (x, y) of the flux of the `exact`
+/// (rho, vx, vy, p) field, by central differences
+pub fn flux_divergence(exact: impl Fn(Real, Real) -> [Real; 4], x: Real, y: Real) -> [Real; 5] {
+    let flux = |x, y, nx, ny| euler_flux(exact(x, y), nx, ny);
+    let (east, west) = (flux(x + H, y, 1.0, 0.0), flux(x - H, y, 1.0, 0.0));
+    let (north, south) = (flux(x, y + H, 0.0, 1.0), flux(x, y - H, 0.0, 1.0));
+    std::array::from_fn(|i| (east[i] - west[i] + north[i] - south[i]) / (2.0 * H))
+}
+
+/// The state (rho, vx, vy, p) as a flow state, with the
+/// thermodynamic properties filled in by the gas model
+pub fn flow_state([rho, vx, vy, p]: [Real; 4], gm: &IdealGas<Real>) -> FlowState<Real> {
+    let mut gas_state = GasState{rho, p, ..GasState::default()};
+    gm.update_from_rhop(&mut gas_state).unwrap();
+    FlowState::new(gas_state, Vector3{x: vx, y: vy, z: 0.0})
+}
+
+/// Write an su2 grid of the unit square with `n` x `n` cells, with
+/// the file name starting with `name` so each test has its own grids
+pub fn write_unit_square(name: &str, n: usize) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{}_{}x{}.su2", name, n, n));
+    fs::write(&path, rectangle_su2(n, n, 1.0, 1.0)).unwrap();
+    path
+}
+
+/// Set the flow state outside each boundary interface to the
+/// `exact` (rho, vx, vy, p) at the centre of the interface
+pub fn set_boundary_states(fluid_block: &mut FluidBlock, gm: &IdealGas<Real>,
+                           exact: impl Fn(Real, Real) -> [Real; 4])
+{
+    for face in 0 .. fluid_block.interfaces().len() {
+        let outside_in_front = match fluid_block.interface_cells()[face] {
+            [Some(_), None] => true,
+            [None, Some(_)] => false,
+            _ => continue,
+        };
+        let centres = fluid_block.interfaces().centres();
+        let exact = flow_state(exact(centres.x[face], centres.y[face]), gm);
+        let interfaces = fluid_block.interfaces_mut();
+        match outside_in_front {
+            true => interfaces.right_flow_states_mut().set_flow_state(face, &exact),
+            false => interfaces.left_flow_states_mut().set_flow_state(face, &exact),
+        }
+    }
+}
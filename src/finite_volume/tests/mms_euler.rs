@@ -0,0 +1,185 @@
+//! Verify the finite volume discretisation of the Euler equations
+//! with the method of manufactured solutions.
+//!
+//! The divergence of the Euler flux of a smooth manufactured (rho, vx, vy, p)
+//! field is added to each cell as a source term, so the manufactured field is
+//! a steady solution of the modified equations. The solver is run to a steady
+//! state on the unit square, with the manufactured solution held outside the
+//! boundaries, and the error in the density is measured on successively
+//! refined grids. It should drop at first order with first order
+//! reconstruction, and second order with second order reconstruction,
+//! with either flux calculator.
+//!
+//! `second_order_residuals` checks the spatial operator on its own: the
+//! exact flux is put on each interface, and the residual of each cell is
+//! compared to the divergence of the flux at the cell centre. This catches
+//! mistakes in the interface areas, normals and directions directly.
+
+mod common;
+
+use std::f64::consts::PI;
+
+use ::common::number::Real;
+use finite_volume::flow::ConservativeSource;
+use finite_volume::fluid_block::FluidBlock;
+use finite_volume::flux::FluxCalculator;
+use finite_volume::reconstruction::ReconstructionOrder;
+use finite_volume::util::l2_error_density;
+use gas::flow_state::FlowState;
+use gas::ideal_gas::IdealGas;
+use grid::block::BlockCollection;
+
+use common::{euler_flux, flow_state, flux_divergence, set_boundary_states, write_unit_square, GAMMA};
+
+/// The Courant number of the time steps
+const CFL: Real = 0.5;
+
+/// The RMS rate of change of density at which the solution is steady
+const STEADY: Real = 1e-7;
+
+/// Give up if there's no steady state after this many steps
+const MAX_STEPS: usize = 100_000;
+
+/// The manufactured (rho, vx, vy, p) at (x, y)
+fn manufactured_solution(x: Real, y: Real) -> [Real; 4] {
+    let rho = 1.0 + 0.1 * Real::sin(PI * x) * Real::cos(PI * y);
+    let vx = 0.8 + 0.1 * Real::cos(PI * x) * Real::sin(PI * y);
+    let vy = 0.5 + 0.1 * Real::sin(PI * x) * Real::sin(PI * y);
+    let p = 1.0 + 0.2 * Real::cos(PI * x) * Real::cos(PI * y);
+    [rho, vx, vy, p]
+}
+
+/// The manufactured solution at (x, y) as a flow state, with the
+/// thermodynamic properties filled in by the gas model
+fn manufactured_flow_state(x: Real, y: Real, gm: &IdealGas<Real>) -> FlowState<Real> {
+    flow_state(manufactured_solution(x, y), gm)
+}
+
+/// Read the unit square with `n` x `n` cells, with the
+/// manufactured solution at the centre of each cell
+fn fluid_block(n: usize, gm: &IdealGas<Real>) -> FluidBlock {
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&write_unit_square("mms", n)).unwrap();
+    let mut fluid_block = FluidBlock::from_grid_block(block_collection.get_block(0), &[], gm).unwrap();
+    fluid_block.set_initial_condition(|x, y, _| manufactured_flow_state(x, y, gm), gm).unwrap();
+    fluid_block.encode_conserved_variables();
+    fluid_block
+}
+
+/// The divergence of the exact flux at the centre of each cell, which is
+/// added as a source term so the manufactured solution is steady
+fn manufactured_source(fluid_block: &FluidBlock) -> ConservativeSource {
+    let centres = fluid_block.cells().centres();
+    let mut source = ConservativeSource::with_capacity(fluid_block.cells().len());
+    for i in 0 .. fluid_block.cells().len() {
+        source.push(flux_divergence(manufactured_solution, centres.x[i], centres.y[i]));
+    }
+    source
+}
+
+/// Run the solver on the unit square with `n` x `n` cells until the
+/// residuals stop changing, and return the L2 norm of the error in
+/// density compared to the manufactured solution
+fn steady_state_error(n: usize, order: ReconstructionOrder, calculator: FluxCalculator) -> Real {
+    let gm = IdealGas::new(1.0, GAMMA);
+    let mut fluid_block = fluid_block(n, &gm);
+    let source = manufactured_source(&fluid_block);
+
+    for _ in 0 .. MAX_STEPS {
+        fluid_block.reconstruct(order, &gm).unwrap();
+        set_boundary_states(&mut fluid_block, &gm, manufactured_solution);
+        fluid_block.compute_fluxes(calculator);
+        let dt = fluid_block.stable_time_step(CFL);
+        fluid_block.apply_flux_to_residuals(dt);
+        fluid_block.add_source_terms(&source);
+        fluid_block.update_conserved_variables(dt, &gm).unwrap();
+
+        let residuals = &fluid_block.cells().residuals().mass;
+        let residual = Real::sqrt(residuals.iter().map(|r| r * r).sum::<Real>() / residuals.len() as Real);
+        if residual < STEADY {
+            let exact = |x, y, _| manufactured_flow_state(x, y, &gm);
+            let cells = fluid_block.cells();
+            return l2_error_density(cells, cells.flow_states(), &exact);
+        }
+    }
+    panic!("The {}x{} grid didn't reach a steady state in {} steps", n, n, MAX_STEPS);
+}
+
+/// The order of accuracy observed between grids with `n` x `n` and 2`n` x 2`n` cells
+fn observed_order(n: usize, order: ReconstructionOrder, calculator: FluxCalculator) -> Real {
+    let coarse = steady_state_error(n, order, calculator);
+    let fine = steady_state_error(2 * n, order, calculator);
+    (coarse / fine).log2()
+}
+
+#[test]
+fn first_order_reconstruction() {
+    // the error is still approaching the asymptotic range on these grids,
+    // and the order rises towards one as they are refined further
+    let order = observed_order(16, ReconstructionOrder::First, FluxCalculator::Ausmdv);
+    assert!(order > 0.7 && order < 1.3, "Converged at order {}", order);
+}
+
+#[test]
+fn second_order_reconstruction() {
+    for calculator in [FluxCalculator::Hanel, FluxCalculator::Ausmdv] {
+        let order = observed_order(8, ReconstructionOrder::Second, calculator);
+        assert!(order > 1.8, "{:?} converged at order {}", calculator, order);
+    }
+}
+
+/// The L2 norm of the difference between the residuals and the
+/// negative divergence of the flux, for each conserved quantity
+fn residual_error(n: usize) -> [Real; 5] {
+    let path = write_unit_square("mms", n);
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&path).unwrap();
+    let gm = IdealGas::new(287.0, GAMMA);
+    let mut fluid_block = FluidBlock::from_grid_block(block_collection.get_block(0), &[], &gm).unwrap();
+
+    let interfaces = fluid_block.interfaces_mut();
+    let mut fluxes = Vec::with_capacity(interfaces.len());
+    for i in 0 .. interfaces.len() {
+        let (centre, normal) = (interfaces.centres(), interfaces.normals());
+        fluxes.push(euler_flux(manufactured_solution(centre.x[i], centre.y[i]), normal.x[i], normal.y[i]));
+    }
+    let flux = interfaces.fluxes_mut();
+    for (i, interface_flux) in fluxes.iter().enumerate() {
+        flux.mass[i] = interface_flux[0];
+        flux.momentum_x[i] = interface_flux[1];
+        flux.momentum_y[i] = interface_flux[2];
+        flux.momentum_z[i] = interface_flux[3];
+        flux.energy[i] = interface_flux[4];
+    }
+    fluid_block.apply_flux_to_residuals(1.0);
+
+    let cells = fluid_block.cells();
+    let residuals = cells.residuals();
+    let residuals = [&residuals.mass, &residuals.momentum_x, &residuals.momentum_y,
+                     &residuals.momentum_z, &residuals.energy];
+    let mut error = [0.0; 5];
+    let total_volume: Real = cells.volumes().iter().sum();
+    for cell in 0 .. cells.len() {
+        let exact = flux_divergence(manufactured_solution, cells.centres().x[cell], cells.centres().y[cell]);
+        for ((error, residual), exact) in error.iter_mut().zip(residuals.iter()).zip(exact.iter()) {
+            // the residual is the rate of change, which is minus the divergence
+            *error += (residual[cell] + exact).powi(2) * cells.volumes()[cell];
+        }
+    }
+    error.map(|e| (e / total_volume).sqrt())
+}
+
+#[test]
+fn second_order_residuals() {
+    let errors: Vec<[Real; 5]> = [2, 4, 8, 16].iter().map(|n| residual_error(*n)).collect();
+
+    for i in [0, 1, 2, 4] {
+        for refinement in errors.windows(2) {
+            assert!(refinement[1][i] < refinement[0][i]);
+        }
+        let order = (errors[2][i] / errors[3][i]).log2();
+        assert!(order > 1.8, "Quantity {} converged at order {}", i, order);
+    }
+    // there's no z-momentum flux in two dimensions
+    assert!(errors.iter().all(|error| error[3] == 0.0));
+}
@@ -0,0 +1,111 @@
+//! Check the order of accuracy of first and second order reconstruction
+//! on the isentropic vortex, an exact solution of the Euler equations.
+//!
+//! The vortex starts at the centre of the unit square, and is carried
+//! along by the free stream, with the exact solution held outside the
+//! boundaries. The flow is advanced with the forward Euler method, the
+//! same way the solver advances it, and the L2 error in pressure is
+//! measured once the vortex has moved, on a coarse and a fine grid. With
+//! first order reconstruction the error should halve when the grid spacing
+//! is halved, and with second order reconstruction it should drop by a
+//! factor of four.
+
+mod common;
+
+use std::f64::consts::PI;
+
+use ::common::number::Real;
+use finite_volume::fluid_block::FluidBlock;
+use finite_volume::flux::FluxCalculator;
+use finite_volume::reconstruction::ReconstructionOrder;
+use finite_volume::util::l2_error_pressure;
+use gas::ideal_gas::IdealGas;
+use grid::block::BlockCollection;
+
+use common::{flow_state, set_boundary_states, write_unit_square, GAMMA};
+
+/// The strength of the vortex
+const BETA: Real = 1.0;
+
+/// The length scale of the vortex
+const RADIUS: Real = 0.2;
+
+/// The velocity the vortex is carried along by
+const FREE_STREAM: [Real; 2] = [0.5, 0.25];
+
+/// The time the vortex is advanced to
+const END_TIME: Real = 0.1;
+
+/// The Courant number of the time steps on the coarse grid
+const CFL: Real = 0.5;
+
+/// The number of cells along each side of the coarse grid
+const COARSE: usize = 64;
+
+/// The (rho, vx, vy, p) at (x, y) of the vortex at time `t`,
+/// which starts at the centre of the unit square
+fn isentropic_vortex(x: Real, y: Real, t: Real) -> [Real; 4] {
+    let centre = [0.5 + FREE_STREAM[0] * t, 0.5 + FREE_STREAM[1] * t];
+    let (dx, dy) = ((x - centre[0]) / RADIUS, (y - centre[1]) / RADIUS);
+    let r2 = dx * dx + dy * dy;
+    let swirl = BETA / (2.0 * PI) * Real::exp(0.5 * (1.0 - r2));
+    let temp = 1.0 - (GAMMA - 1.0) * BETA * BETA / (8.0 * GAMMA * PI * PI) * Real::exp(1.0 - r2);
+    let rho = temp.powf(1.0 / (GAMMA - 1.0));
+    [rho, FREE_STREAM[0] - swirl * dy, FREE_STREAM[1] + swirl * dx, rho * temp]
+}
+
+/// Advance the vortex to `END_TIME` on the unit square with `n` x `n`
+/// cells, and return the L2 norm of the error in pressure
+fn pressure_error(n: usize, order: ReconstructionOrder) -> Real {
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&write_unit_square("vortex", n)).unwrap();
+    let gm = IdealGas::new(1.0, GAMMA);
+    let mut fluid_block = FluidBlock::from_grid_block(block_collection.get_block(0), &[], &gm).unwrap();
+    fluid_block.set_initial_condition(|x, y, _| flow_state(isentropic_vortex(x, y, 0.0), &gm), &gm).unwrap();
+    fluid_block.encode_conserved_variables();
+
+    // forward Euler is first order in time, so with second order reconstruction
+    // the time step is refined with the square of the grid spacing, to keep the
+    // error in time dropping as fast as the error in space
+    let refinement = (n / COARSE) as Real;
+    let cfl = match order {
+        ReconstructionOrder::First => CFL,
+        ReconstructionOrder::Second => CFL / refinement,
+    };
+    let mut time = 0.0;
+    while time < END_TIME {
+        fluid_block.reconstruct(order, &gm).unwrap();
+        set_boundary_states(&mut fluid_block, &gm, |x, y| isentropic_vortex(x, y, time));
+        fluid_block.compute_fluxes(FluxCalculator::Ausmdv);
+        let dt = fluid_block.stable_time_step(cfl).min(END_TIME - time);
+        fluid_block.apply_flux_to_residuals(dt);
+        fluid_block.update_conserved_variables(dt, &gm).unwrap();
+        time += dt;
+    }
+
+    let exact = |x, y, _| flow_state(isentropic_vortex(x, y, END_TIME), &gm);
+    let cells = fluid_block.cells();
+    l2_error_pressure(cells, cells.flow_states(), &exact)
+}
+
+/// Check the error drops by `expected_ratio`, to within 10 %,
+/// when the grid spacing is halved
+fn check_order(order: ReconstructionOrder, expected_ratio: Real) {
+    let (coarse, fine) = (pressure_error(COARSE, order), pressure_error(2 * COARSE, order));
+
+    let ratio = coarse / fine;
+    assert!(
+        (ratio / expected_ratio - 1.0).abs() < 0.1,
+        "{:?} order: the error in pressure dropped by {}, not {}", order, ratio, expected_ratio
+    );
+}
+
+#[test]
+fn first_order_reconstruction() {
+    check_order(ReconstructionOrder::First, 2.0);
+}
+
+#[test]
+fn second_order_reconstruction() {
+    check_order(ReconstructionOrder::Second, 4.0);
+}
@@ -0,0 +1,112 @@
+//! Write the flow states of a fluid block, then read them back
+//! as if restarting the simulation from the flow file.
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::number::Real;
+use common::vector3::Vector3;
+use finite_volume::fluid_block::{read_physical_time, FluidBlock, FluidBlockCollection, TIME_FILE};
+use finite_volume::fluid_block_io::{FluidBlockIO, FLOW_EXTENSION};
+use gas::flow_state::FlowState;
+use gas::gas_state::GasState;
+use gas::ideal_gas::IdealGas;
+use grid::block::{block_file_name, BlockCollection};
+
+fn varying_flow(x: Real, y: Real, _z: Real) -> FlowState<Real> {
+    let gas_state = GasState{p: 1000.0 * (1.0 + x), T: 300.0 + 10.0 * y, ..GasState::default()};
+    FlowState::new(gas_state, Vector3{x: 100.0 * y, y: -50.0 * x, z: 0.0})
+}
+
+fn close(a: &[Real], b: &[Real]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() <= 1e-12 * a.abs().max(b.abs()))
+}
+
+#[test]
+fn write_then_read_flow_file() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("restart");
+    fs::create_dir_all(&dir).unwrap();
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+    let gm = IdealGas::new(287.0, 1.4);
+    let fluid_blocks = FluidBlockCollection::with_variable_initial_condition(&block_collection, varying_flow, &gm).unwrap();
+    let written = fluid_blocks.fluid_blocks()[0].cells().flow_states();
+    let mut block_io = FluidBlockIO::new(&fluid_blocks.fluid_blocks()[0]);
+    block_io.write_fluid_block(&dir.join("blk")).unwrap();
+
+    let grid_block = block_collection.get_block(0);
+    let read_io = FluidBlockIO::from_flow_file(grid_block, &dir.join(block_file_name(0, FLOW_EXTENSION)), &gm).unwrap();
+    let read = read_io.flow_states();
+
+    assert_eq!(read.len(), 9);
+    assert!(close(&read.p, &written.p));
+    assert!(close(&read.rho, &written.rho));
+    assert!(close(&read.t, &written.t));
+    assert!(close(&read.u, &written.u));
+    assert!(close(&read.vel_x, &written.vel_x));
+    assert!(close(&read.vel_y, &written.vel_y));
+    assert!(close(&read.vel_z, &written.vel_z));
+
+    // the flow states can be used to restart a fluid block
+    let mut fluid_block = FluidBlock::from_grid_block(grid_block, &[], &gm).unwrap();
+    fluid_block.set_flow_states(read.clone());
+    assert!(close(&fluid_block.cells().flow_states().p, &written.p));
+}
+
+#[test]
+fn rewrite_flow_file() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("restart_rewrite");
+    fs::create_dir_all(&dir).unwrap();
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+    let gm = IdealGas::new(287.0, 1.4);
+    let fluid_blocks = FluidBlockCollection::with_variable_initial_condition(&block_collection, varying_flow, &gm).unwrap();
+    FluidBlockIO::new(&fluid_blocks.fluid_blocks()[0]).write_fluid_block(&dir.join("blk")).unwrap();
+
+    // a block read from a flow file writes the flow states it was read with
+    let grid_block = block_collection.get_block(0);
+    let mut read_io = FluidBlockIO::from_flow_file(grid_block, &dir.join(block_file_name(0, FLOW_EXTENSION)), &gm).unwrap();
+    let copy_dir = dir.join("copy");
+    fs::create_dir_all(&copy_dir).unwrap();
+    read_io.write_fluid_block(&copy_dir.join("blk")).unwrap();
+
+    let reread = FluidBlockIO::from_flow_file(grid_block, &copy_dir.join(block_file_name(0, FLOW_EXTENSION)), &gm).unwrap();
+    assert!(close(&reread.flow_states().p, &read_io.flow_states().p));
+    assert!(close(&reread.flow_states().t, &read_io.flow_states().t));
+    assert!(close(&reread.flow_states().vel_x, &read_io.flow_states().vel_x));
+}
+
+#[test]
+fn read_short_flow_file() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("restart_short");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(block_file_name(0, FLOW_EXTENSION));
+    fs::write(&path, [0u8; 16]).unwrap();
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+    let gm = IdealGas::new(287.0, 1.4);
+
+    assert!(FluidBlockIO::from_flow_file(block_collection.get_block(0), &path, &gm).is_err());
+}
+
+#[test]
+fn write_time_directories() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("restart_time_directories");
+    let _ = fs::remove_dir_all(&dir);
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+    let gm = IdealGas::new(287.0, 1.4);
+    let mut fluid_blocks = FluidBlockCollection::with_variable_initial_condition(&block_collection, varying_flow, &gm).unwrap();
+    fluid_blocks.write_fluids_blocks(&dir, 1.5e-4).unwrap();
+    fluid_blocks.write_fluids_blocks(&dir, 2.5e-4).unwrap();
+
+    assert_eq!(fluid_blocks.time_index(), 2);
+    assert!((fluid_blocks.physical_time() - 4e-4).abs() < 1e-18);
+    for (time_dir, time) in [("t0001", 1.5e-4), ("t0002", 4e-4)] {
+        let time_dir = dir.join(time_dir);
+        assert!(time_dir.join(block_file_name(0, FLOW_EXTENSION)).is_file());
+        assert!(time_dir.join(TIME_FILE).is_file());
+        assert!((read_physical_time(&time_dir).unwrap() - time).abs() < 1e-18);
+    }
+    assert!(read_physical_time(&dir).is_err());
+}
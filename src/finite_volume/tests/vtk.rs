@@ -0,0 +1,50 @@
+//! Write a fluid block to a VTK file, and check the
+//! file describes the grid and flow states.
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::number::Real;
+use common::vector3::Vector3;
+use finite_volume::fluid_block::FluidBlockCollection;
+use gas::flow_state::FlowState;
+use gas::gas_state::GasState;
+use gas::ideal_gas::IdealGas;
+use grid::block::BlockCollection;
+
+fn varying_flow(x: Real, _y: Real, _z: Real) -> FlowState<Real> {
+    let gas_state = GasState{p: 1000.0 * x, T: 300.0, ..GasState::default()};
+    FlowState::new(gas_state, Vector3{x: 10.0, y: 0.0, z: 0.0})
+}
+
+/// The lines of the data array called `name`
+fn data_array<'a>(vtk: &'a str, name: &str) -> Vec<&'a str> {
+    vtk.lines()
+        .skip_while(|line| !line.contains(&format!("Name=\"{}\"", name)))
+        .skip(1)
+        .take_while(|line| !line.starts_with("</DataArray>"))
+        .collect()
+}
+
+#[test]
+fn write_square_vtk() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("vtk");
+    fs::create_dir_all(&dir).unwrap();
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("../grid/tests/data/square.su2")).unwrap();
+    let gm = IdealGas::new(287.0, 1.4);
+    let fluid_blocks = FluidBlockCollection::with_variable_initial_condition(&block_collection, varying_flow, &gm).unwrap();
+    let path = dir.join("square.vtu");
+    fluid_blocks.fluid_blocks()[0].write_vtk(&path).unwrap();
+
+    let vtk = fs::read_to_string(&path).unwrap();
+    assert!(vtk.contains("<Piece NumberOfPoints=\"16\" NumberOfCells=\"9\">"));
+    assert_eq!(data_array(&vtk, "connectivity")[0], "0 1 5 4");
+    assert_eq!(data_array(&vtk, "offsets").last(), Some(&"36"));
+    assert!(data_array(&vtk, "types").iter().all(|cell_type| *cell_type == "9"));
+    assert_eq!(data_array(&vtk, "p")[..3], ["500", "1500", "2500"]);
+    assert_eq!(data_array(&vtk, "velocity").len(), 9);
+    assert_eq!(data_array(&vtk, "velocity")[0], "10 0 0");
+    assert_eq!(data_array(&vtk, "T").len(), 9);
+    assert!(vtk.trim_end().ends_with("</VTKFile>"));
+}
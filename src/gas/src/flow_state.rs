@@ -1,14 +1,20 @@
+use common::number::Real;
 use common::vector3::Vector3;
 use num_complex::ComplexFloat as Number;
 
 use crate::gas_state::GasState;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct FlowState<Num: Number> {
     gas_state: GasState<Num>,
     velocity: Vector3,
 }
 
 impl<Num: Number> FlowState<Num> {
+    pub fn new(gas_state: GasState<Num>, velocity: Vector3) -> FlowState<Num> {
+        FlowState { gas_state, velocity }
+    }
+
     pub fn gas_state(&self) -> &GasState<Num> {
         &self.gas_state
     }
@@ -25,3 +31,101 @@ impl<Num: Number> FlowState<Num> {
         &mut self.velocity
     }
 }
+
+/// The gradient of each field of a flow state
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FlowStateGrad {
+    pub p: Vector3,
+    pub T: Vector3,
+    pub rho: Vector3,
+    pub u: Vector3,
+    pub a: Vector3,
+    pub vel_x: Vector3,
+    pub vel_y: Vector3,
+    pub vel_z: Vector3,
+}
+
+impl FlowState<Real> {
+    /// Linearly interpolate every field between `a` (at t = 0) and `b` (at t = 1)
+    pub fn interpolate(a: &FlowState<Real>, b: &FlowState<Real>, t: Real) -> FlowState<Real> {
+        let lerp = |a: Real, b: Real| a + t * (b - a);
+        let (gs_a, gs_b) = (a.gas_state(), b.gas_state());
+        let gas_state = GasState {
+            p: lerp(gs_a.p, gs_b.p),
+            T: lerp(gs_a.T, gs_b.T),
+            rho: lerp(gs_a.rho, gs_b.rho),
+            u: lerp(gs_a.u, gs_b.u),
+            a: lerp(gs_a.a, gs_b.a),
+        };
+        let (vel_a, vel_b) = (a.velocity(), b.velocity());
+        let velocity = Vector3 {
+            x: lerp(vel_a.x, vel_b.x),
+            y: lerp(vel_a.y, vel_b.y),
+            z: lerp(vel_a.z, vel_b.z),
+        };
+        FlowState::new(gas_state, velocity)
+    }
+
+    /// Extrapolate the flow state in `cell` a distance `delta`
+    /// away, by adding `grad` · `delta` to each field
+    pub fn extrapolate(cell: &FlowState<Real>, grad: &FlowStateGrad, delta: &Vector3) -> FlowState<Real> {
+        let gs = cell.gas_state();
+        let gas_state = GasState {
+            p: gs.p + grad.p.dot(delta),
+            T: gs.T + grad.T.dot(delta),
+            rho: gs.rho + grad.rho.dot(delta),
+            u: gs.u + grad.u.dot(delta),
+            a: gs.a + grad.a.dot(delta),
+        };
+        let vel = cell.velocity();
+        let velocity = Vector3 {
+            x: vel.x + grad.vel_x.dot(delta),
+            y: vel.y + grad.vel_y.dot(delta),
+            z: vel.z + grad.vel_z.dot(delta),
+        };
+        FlowState::new(gas_state, velocity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow_state(p: Real, temp: Real, vel_x: Real) -> FlowState<Real> {
+        let gas_state = GasState{p, T: temp, rho: p / (287.0 * temp), u: 717.5 * temp, a: 0.0};
+        FlowState::new(gas_state, Vector3{x: vel_x, y: -vel_x, z: 0.0})
+    }
+
+    #[test]
+    fn interpolate() {
+        let a = flow_state(1000.0, 300.0, 10.0);
+        let b = flow_state(2000.0, 400.0, 30.0);
+
+        assert_eq!(FlowState::interpolate(&a, &b, 0.0), a);
+        assert_eq!(FlowState::interpolate(&a, &b, 1.0), b);
+        let middle = FlowState::interpolate(&a, &b, 0.5);
+        assert_eq!(middle.gas_state().p, 1500.0);
+        assert_eq!(middle.gas_state().T, 350.0);
+        assert_eq!(middle.gas_state().u, 717.5 * 350.0);
+        assert_eq!(middle.velocity(), &Vector3{x: 20.0, y: -20.0, z: 0.0});
+    }
+
+    #[test]
+    fn extrapolate() {
+        let cell = flow_state(1000.0, 300.0, 10.0);
+        let grad = FlowStateGrad {
+            p: Vector3{x: 100.0, y: 0.0, z: 0.0},
+            T: Vector3{x: 0.0, y: 10.0, z: 0.0},
+            vel_x: Vector3{x: 1.0, y: 1.0, z: 1.0},
+            ..FlowStateGrad::default()
+        };
+        let delta = Vector3{x: 0.5, y: -0.5, z: 2.0};
+
+        let face = FlowState::extrapolate(&cell, &grad, &delta);
+        assert_eq!(face.gas_state().p, 1050.0);
+        assert_eq!(face.gas_state().T, 295.0);
+        assert_eq!(face.gas_state().rho, cell.gas_state().rho);
+        assert_eq!(face.velocity(), &Vector3{x: 12.0, y: -10.0, z: 0.0});
+    }
+}
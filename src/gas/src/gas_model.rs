@@ -1,18 +1,36 @@
+use std::collections::HashMap;
 use std::str::FromStr;
 
 use crate::{gas_state::GasState, ideal_gas::IdealGas};
+use common::DynamicResult;
 use common::number::Real;
 
 use num_complex::ComplexFloat as Number;
+use rlua::FromLua;
+use serde::de::DeserializeOwned;
 use serde_derive::{Serialize, Deserialize};
+use thiserror::Error;
+
+/// Errors that can occur updating the state of a gas
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum GasModelError {
+    #[error("Gas model failed to converge after {iterations} iterations")]
+    NonConvergence { iterations: usize },
+
+    #[error("Invalid gas state: {0}")]
+    InvalidState(String),
+
+    #[error("Gas state is incompatible with the species in the gas model")]
+    IncompatibleSpecies,
+}
 
 #[allow(non_snake_case)]
 pub trait GasModel<Num: Number + Clone>: std::fmt::Debug{
     // thermodyanmics methods
-    fn update_from_pT(&self, gs: &mut GasState<Num>);
-    fn update_from_rhoT(&self, gs: &mut GasState<Num>);
-    fn update_from_rhou(&self, gs: &mut GasState<Num>);
-    fn update_from_rhop(&self, gs: &mut GasState<Num>);
+    fn update_from_pT(&self, gs: &mut GasState<Num>) -> Result<(), GasModelError>;
+    fn update_from_rhoT(&self, gs: &mut GasState<Num>) -> Result<(), GasModelError>;
+    fn update_from_rhou(&self, gs: &mut GasState<Num>) -> Result<(), GasModelError>;
+    fn update_from_rhop(&self, gs: &mut GasState<Num>) -> Result<(), GasModelError>;
     fn Cv(&self, gs: &GasState<Num>) -> Num;
     fn Cp(&self, gs: &GasState<Num>) -> Num;
     fn R(&self, gs: &GasState<Num>) -> Num;
@@ -20,6 +38,113 @@ pub trait GasModel<Num: Number + Clone>: std::fmt::Debug{
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
+/// Properties of a gas needed to compute viscous fluxes
+pub trait TransportProperties<Num: Number> {
+    /// The dynamic viscosity (Pa s) of the gas
+    fn viscosity(&self, gs: &GasState<Num>) -> Num;
+}
+
+impl<'a, Num: Number + Clone> dyn GasModel<Num> + 'a {
+    /// The gas model as its concrete type, or `None`
+    /// if it is a different type of gas model
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+}
+
+
+type FromLuaFn = for<'lua> fn(rlua::Value<'lua>, rlua::Context<'lua>) -> rlua::Result<Box<dyn GasModel<Real>>>;
+
+/// How to create and save one type of gas model
+struct RegisteredGasModel {
+    from_toml: fn(toml::Value) -> DynamicResult<Box<dyn GasModel<Real>>>,
+    from_lua: FromLuaFn,
+    to_toml: fn(&dyn GasModel<Real>) -> Option<DynamicResult<toml::Value>>,
+}
+
+fn gas_model_from_toml<T>(config: toml::Value) -> DynamicResult<Box<dyn GasModel<Real>>>
+    where T: GasModel<Real> + DeserializeOwned + 'static
+{
+    Ok(Box::new(config.try_into::<T>()?))
+}
+
+fn gas_model_from_lua<'lua, T>(value: rlua::Value<'lua>, lua: rlua::Context<'lua>) -> rlua::Result<Box<dyn GasModel<Real>>>
+    where T: GasModel<Real> + for<'a> FromLua<'a> + 'static
+{
+    Ok(Box::new(T::from_lua(value, lua)?))
+}
+
+fn gas_model_to_toml<T>(gas_model: &dyn GasModel<Real>) -> Option<DynamicResult<toml::Value>>
+    where T: GasModel<Real> + serde::Serialize + 'static
+{
+    let gas_model = gas_model.downcast_ref::<T>()?;
+    Some(toml::Value::try_from(gas_model).map_err(|err| err.into()))
+}
+
+/// The types of gas model which can be chosen by name at run time, e.g.
+/// from the `gas_model_type` of a simulation. A new type of gas model only
+/// needs registering to be read from a simulation and written to file.
+pub struct GasModelRegistry {
+    gas_models: HashMap<String, RegisteredGasModel>,
+}
+
+impl Default for GasModelRegistry {
+    /// A registry of the gas models which come with aeolus
+    fn default() -> Self {
+        let mut registry = GasModelRegistry{gas_models: HashMap::new()};
+        registry.register::<IdealGas<Real>>("ideal_gas");
+        registry
+    }
+}
+
+impl GasModelRegistry {
+    pub fn new() -> GasModelRegistry {
+        GasModelRegistry::default()
+    }
+
+    /// Register a type of gas model under `name`, replacing any
+    /// gas model already registered with the same name
+    pub fn register<T>(&mut self, name: &str)
+        where T: GasModel<Real> + serde::Serialize + DeserializeOwned + for<'lua> FromLua<'lua> + 'static
+    {
+        let gas_model = RegisteredGasModel {
+            from_toml: gas_model_from_toml::<T>,
+            from_lua: gas_model_from_lua::<T>,
+            to_toml: gas_model_to_toml::<T>,
+        };
+        self.gas_models.insert(name.to_string(), gas_model);
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.gas_models.contains_key(name)
+    }
+
+    /// Create the gas model registered as `name` from its configuration
+    pub fn build(&self, name: &str, config: &toml::Value) -> DynamicResult<Box<dyn GasModel<Real>>> {
+        (self.registered(name)?.from_toml)(config.clone())
+    }
+
+    /// Create the gas model registered as `name` from a lua value
+    pub fn build_from_lua<'lua>(&self, name: &str, value: rlua::Value<'lua>, lua: rlua::Context<'lua>)
+        -> DynamicResult<Box<dyn GasModel<Real>>>
+    {
+        Ok((self.registered(name)?.from_lua)(value, lua)?)
+    }
+
+    /// The configuration of a gas model, which [`GasModelRegistry::build`]
+    /// creates the gas model from. It is an error if `gas_model` isn't the
+    /// type registered as `name`.
+    pub fn to_toml(&self, name: &str, gas_model: &dyn GasModel<Real>) -> DynamicResult<toml::Value> {
+        (self.registered(name)?.to_toml)(gas_model)
+            .unwrap_or_else(|| Err(format!("The gas model isn't a '{}' gas model", name).into()))
+    }
+
+    fn registered(&self, name: &str) -> DynamicResult<&RegisteredGasModel> {
+        self.gas_models
+            .get(name)
+            .ok_or_else(|| format!("Unknown gas model '{}'", name).into())
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -50,3 +175,43 @@ impl Default for Box<dyn GasModel<Real>> {
         Box::new(IdealGas::new(287.1, 1.4))  
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downcast_ideal_gas() {
+        let gas_model: Box<dyn GasModel<Real>> = Box::new(IdealGas::new(287.0, 1.4));
+
+        let ideal_gas = gas_model.downcast_ref::<IdealGas<Real>>().unwrap();
+        assert_eq!(ideal_gas.R(&GasState::default()), 287.0);
+        assert!(gas_model.downcast_ref::<IdealGas<f32>>().is_none());
+    }
+
+    #[test]
+    fn registry_build() {
+        let registry = GasModelRegistry::new();
+        let config: toml::Value = toml::from_str("R = 287.0\nCv = 717.5\ngamma = 1.4").unwrap();
+
+        let gas_model = registry.build("ideal_gas", &config).unwrap();
+        assert_eq!(gas_model.R(&GasState::default()), 287.0);
+        assert_eq!(registry.to_toml("ideal_gas", gas_model.as_ref()).unwrap(), config);
+        assert!(registry.build("real_gas", &config).is_err());
+    }
+
+    #[test]
+    fn registry_from_lua() {
+        let registry = GasModelRegistry::new();
+        let lua = rlua::Lua::new();
+
+        lua.context(|lua_ctx| {
+            let value = rlua::ToLua::to_lua(IdealGas::new(287.0, 1.4), lua_ctx).unwrap();
+            let gas_model = registry.build_from_lua("ideal_gas", value, lua_ctx).unwrap();
+            assert_eq!(gas_model.R(&GasState::default()), 287.0);
+
+            let number = rlua::Value::Number(1.0);
+            assert!(registry.build_from_lua("ideal_gas", number, lua_ctx).is_err());
+        });
+    }
+}
@@ -2,7 +2,11 @@ use std::fmt::Display;
 
 use num_complex::ComplexFloat as Number;
 use rlua::UserData;
+use thiserror::Error;
 use common::number::Real;
+use common::vector3::Vector3;
+
+use crate::gas_model::{GasModel, GasModelError};
 
 #[allow(non_snake_case)]
 #[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
@@ -32,13 +36,178 @@ impl<Num: Number + Default> GasState<Num> {
     }
 }
 
+/// The names and units of the fields of a gas state, in the order they're displayed
+const FIELDS: [(&str, &str); 5] = [("p", "Pa"), ("T", "K"), ("rho", "kg/m³"), ("u", "J/kg"), ("a", "m/s")];
+
+/// Displays each field with its SI unit, e.g.
+/// `p = 101325 Pa, T = 300 K, rho = 1.177 kg/m³, u = 215288 J/kg, a = 347.2 m/s`
 impl<Num> std::fmt::Display for GasState<Num>
     where Num: Number + Display
 {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let string = format!("GasState(p={}, T={}, rho={}, u={}, a={})", 
-                           self.p, self.T, self.rho, self.u, self.a);
-        write!(f, "{}", string)
+        let values = [self.p, self.T, self.rho, self.u, self.a];
+        for (i, ((name, unit), value)) in FIELDS.iter().zip(values).enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} = {} {}", name, value, unit)?;
+        }
+        Ok(())
     }
 }
 
+/// The string couldn't be read as a gas state
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("Unable to read gas state from '{string}': {reason}")]
+pub struct GasStateParseError {
+    string: String,
+    reason: String,
+}
+
+impl GasState<Real> {
+    /// Read a gas state from the string written by its `Display`
+    /// implementation, e.g. for test fixtures
+    pub fn from_display_str(s: &str) -> Result<GasState<Real>, GasStateParseError> {
+        let error = |reason: String| GasStateParseError{string: s.to_string(), reason};
+        let fields: Vec<&str> = s.trim().split(", ").collect();
+        if fields.len() != FIELDS.len() {
+            return Err(error(format!("expected {} fields, found {}", FIELDS.len(), fields.len())));
+        }
+
+        let mut values = [0.0; 5];
+        for ((field, (name, unit)), value) in fields.iter().zip(FIELDS).zip(values.iter_mut()) {
+            let number = field
+                .strip_prefix(name)
+                .and_then(|field| field.strip_prefix(" = "))
+                .and_then(|field| field.strip_suffix(unit))
+                .ok_or_else(|| error(format!("expected '{} = <value> {}', found '{}'", name, unit, field)))?;
+            *value = number.trim().parse().map_err(|_| error(format!("invalid value for {}", name)))?;
+        }
+        let [p, temp, rho, u, a] = values;
+        Ok(GasState{p, T: temp, rho, u, a})
+    }
+
+    /// Whether every field differs from the same field of `other` by less
+    /// than `tol`. The tolerance is absolute, so should be chosen with the
+    /// size of the largest field (usually the internal energy) in mind.
+    pub fn approx_eq(&self, other: &GasState<Real>, tol: Real) -> bool {
+        self.max_diff(other) < tol
+    }
+
+    /// The largest absolute difference between a field and the same
+    /// field of `other`
+    pub fn max_diff(&self, other: &GasState<Real>) -> Real {
+        [
+            self.p - other.p,
+            self.T - other.T,
+            self.rho - other.rho,
+            self.u - other.u,
+            self.a - other.a,
+        ].iter().fold(0.0, |max, diff| max.max(diff.abs()))
+    }
+
+    /// Check the pressure and temperature are positive and finite, so
+    /// the gas model can compute the rest of the state from them
+    pub fn validate(&self) -> Result<(), GasModelError> {
+        for (name, value) in [("pressure", self.p), ("temperature", self.T)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(GasModelError::InvalidState(format!("The {} must be positive, but it is {}", name, value)));
+            }
+        }
+        Ok(())
+    }
+
+    /// The two dimensional conserved quantities per unit volume,
+    /// [rho, rho * vx, rho * vy, rho * E], of the gas moving at `vel`.
+    /// The z component of `vel` is ignored.
+    pub fn to_conserved(&self, vel: &Vector3) -> [Real; 4] {
+        let total_energy = self.u + 0.5 * (vel.x * vel.x + vel.y * vel.y);
+        [self.rho, self.rho * vel.x, self.rho * vel.y, self.rho * total_energy]
+    }
+
+    /// Recover the gas state from the two dimensional conserved quantities
+    /// [rho, rho * vx, rho * vy, rho * E], using the gas model to compute
+    /// the rest of the state from the density and internal energy
+    pub fn from_conserved(u: &[Real; 4], gm: &dyn GasModel<Real>) -> Result<GasState<Real>, GasModelError> {
+        let rho = u[0];
+        if rho <= 0.0 {
+            return Err(GasModelError::InvalidState(format!("Non-positive density {}", rho)));
+        }
+        let (vel_x, vel_y) = (u[1] / rho, u[2] / rho);
+        let mut gas_state = GasState{rho, u: u[3] / rho - 0.5 * (vel_x * vel_x + vel_y * vel_y), ..GasState::default()};
+        gm.update_from_rhou(&mut gas_state)?;
+        Ok(gas_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let gs = GasState{p: 101325.0, T: 300.0, rho: 1.177, u: 215288.0, a: 347.2};
+
+        assert_eq!(gs.to_string(), "p = 101325 Pa, T = 300 K, rho = 1.177 kg/m³, u = 215288 J/kg, a = 347.2 m/s");
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let gs = GasState{p: 101325.5, T: 1.0 / 3.0, rho: 1.177e-5, u: 215288.0, a: -0.0};
+
+        assert_eq!(GasState::from_display_str(&gs.to_string()), Ok(gs));
+    }
+
+    #[test]
+    fn from_display_str_invalid() {
+        assert!(GasState::from_display_str("p = 101325 Pa, T = 300 K").is_err());
+        assert!(GasState::from_display_str("p = 101325 kPa, T = 300 K, rho = 1 kg/m³, u = 1 J/kg, a = 1 m/s").is_err());
+        assert!(GasState::from_display_str("p = high Pa, T = 300 K, rho = 1 kg/m³, u = 1 J/kg, a = 1 m/s").is_err());
+    }
+
+    #[test]
+    fn max_diff() {
+        let gs = GasState{p: 101325.0, T: 300.0, rho: 1.177, u: 215288.0, a: 347.2};
+        let other = GasState{T: 299.5, u: 215288.25, ..gs};
+
+        assert_eq!(gs.max_diff(&other), 0.5);
+        assert_eq!(gs.max_diff(&gs), 0.0);
+        assert!(gs.approx_eq(&other, 0.6));
+        assert!(!gs.approx_eq(&other, 0.5));
+    }
+
+    #[test]
+    fn validate() {
+        assert!(GasState{p: 101325.0, T: 300.0, ..GasState::default()}.validate().is_ok());
+        assert!(GasState{p: 0.0, T: 300.0, ..GasState::default()}.validate().is_err());
+        assert!(GasState{p: 101325.0, T: -1.0, ..GasState::default()}.validate().is_err());
+        assert!(GasState{p: Real::NAN, T: 300.0, ..GasState::default()}.validate().is_err());
+    }
+
+    #[test]
+    fn conserved_round_trip() {
+        let gm = crate::ideal_gas::IdealGas::new(287.0, 1.4);
+        for (mach, p, temp) in [(0.0, 101325.0, 300.0), (0.3, 5000.0, 220.0), (2.0, 1e6, 1500.0), (8.0, 100.0, 60.0)] {
+            let mut gs = GasState{p, T: temp, ..GasState::default()};
+            gm.update_from_pT(&mut gs).unwrap();
+            let speed = mach * gs.a;
+            let vel = Vector3{x: 0.8 * speed, y: -0.6 * speed, z: 0.0};
+
+            let conserved = gs.to_conserved(&vel);
+            assert_eq!(conserved[0], gs.rho);
+            assert_eq!(conserved[1], gs.rho * vel.x);
+            let recovered = GasState::from_conserved(&conserved, &gm).unwrap();
+            for (a, b) in [(recovered.p, gs.p), (recovered.T, gs.T), (recovered.rho, gs.rho),
+                           (recovered.u, gs.u), (recovered.a, gs.a)] {
+                assert!((a - b).abs() <= 1e-10 * b.abs(), "Mach {}: {} != {}", mach, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn from_conserved_negative_density() {
+        let gm = crate::ideal_gas::IdealGas::new(287.0, 1.4);
+
+        assert!(GasState::from_conserved(&[-1.0, 0.0, 0.0, 1e5], &gm).is_err());
+    }
+}
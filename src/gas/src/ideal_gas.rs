@@ -1,10 +1,28 @@
 use crate::gas_state::GasState;
-use crate::gas_model::GasModel;
+use crate::gas_model::{GasModel, GasModelError, TransportProperties};
 use num_complex::ComplexFloat as Number;
 use rlua::{UserData, UserDataMethods};
 use common::number::Real;
 use serde_derive::{Serialize, Deserialize};
+use thiserror::Error;
 
+/// The gas constant (J / kg / K) and ratio of specific
+/// heats of the species `IdealGas::from_species_name` knows
+const SPECIES: [(&str, Real, Real); 6] = [
+    ("air", 287.05, 1.4),
+    ("nitrogen", 296.8, 1.4),
+    ("oxygen", 259.8, 1.4),
+    ("argon", 208.0, 5.0 / 3.0),
+    ("helium", 2077.0, 5.0 / 3.0),
+    ("co2", 188.9, 1.289),
+];
+
+/// There are no ideal gas properties for the species
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("Unknown species '{species}', expected one of: {}", SPECIES.map(|(name, _, _)| name).join(", "))]
+pub struct UnknownSpeciesError {
+    species: String,
+}
 
 #[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -12,13 +30,28 @@ pub struct IdealGas<Num: Number> {
     R: Num, // J / kg / K
     Cv: Num, // J / K
     gamma: Num,
+
+    /// Only viscous gases have a viscosity law
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sutherland: Option<Sutherland<Num>>,
+}
+
+/// The constants of Sutherland's law for viscosity,
+/// mu = mu_ref (T / T_ref)^(3/2) (T_ref + S) / (T + S)
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct Sutherland<Num: Number> {
+    mu_ref: Num, // Pa s
+    T_ref: Num, // K
+    S: Num, // K
 }
 
 impl UserData for IdealGas<Real>{
     fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
         methods.add_method("update_from_pT", |_, gas_model, gas_state: &GasState<Real>| {
             let mut gas_state_update = *gas_state;
-            gas_model.update_from_pT(&mut gas_state_update);
+            gas_model.update_from_pT(&mut gas_state_update)
+                .map_err(|err| rlua::Error::RuntimeError(err.to_string()))?;
             Ok(gas_state_update)
         })
     }
@@ -27,7 +60,29 @@ impl UserData for IdealGas<Real>{
 #[allow(non_snake_case)]
 impl<Num: Number> IdealGas<Num> {
     pub fn new(R: Num, gamma: Num) -> IdealGas<Num> {
-        IdealGas{R, Cv: R/(gamma-Num::one()), gamma}
+        IdealGas{R, Cv: R/(gamma-Num::one()), gamma, sutherland: None}
+    }
+
+    /// A viscous ideal gas, with the viscosity given by Sutherland's law
+    /// with the reference viscosity `mu_ref` (Pa s) at the reference
+    /// temperature `T_ref` (K), and the Sutherland temperature `S` (K)
+    pub fn with_sutherland(R: Num, gamma: Num, mu_ref: Num, T_ref: Num, S: Num) -> IdealGas<Num> {
+        IdealGas{sutherland: Some(Sutherland{mu_ref, T_ref, S}), ..IdealGas::new(R, gamma)}
+    }
+
+    /// Whether the gas has a viscosity law
+    pub fn is_viscous(&self) -> bool {
+        self.sutherland.is_some()
+    }
+
+    /// The specific gas constant (J / kg / K)
+    pub fn gas_constant(&self) -> Num {
+        self.R
+    }
+
+    /// The ratio of specific heats
+    pub fn gamma(&self) -> Num {
+        self.gamma
     }
 
     fn update_sound_speed(&self, gs: &mut GasState<Num>) {
@@ -35,30 +90,44 @@ impl<Num: Number> IdealGas<Num> {
     }
 }
 
+impl IdealGas<Real> {
+    /// An ideal gas with the properties of a common gas, e.g. "air" or "co2"
+    pub fn from_species_name(species: &str) -> Result<IdealGas<Real>, UnknownSpeciesError> {
+        SPECIES.iter()
+            .find(|(name, _, _)| *name == species)
+            .map(|&(_, r, gamma)| IdealGas::new(r, gamma))
+            .ok_or_else(|| UnknownSpeciesError{species: species.to_string()})
+    }
+}
+
 #[allow(non_snake_case)]
 impl <Num: Number + 'static + std::fmt::Debug + Default> GasModel<Num> for IdealGas<Num> {
-    fn update_from_pT(&self, gs: &mut GasState<Num>) {
+    fn update_from_pT(&self, gs: &mut GasState<Num>) -> Result<(), GasModelError> {
         gs.rho = gs.p / (self.R * gs.T);
         gs.u = self.Cv * gs.T;
         self.update_sound_speed(gs);
+        Ok(())
     }
 
-    fn update_from_rhoT(&self, gs: &mut GasState<Num>) {
+    fn update_from_rhoT(&self, gs: &mut GasState<Num>) -> Result<(), GasModelError> {
         gs.p = gs.rho * self.R * gs.T;
         gs.u = self.Cv * gs.T;
         self.update_sound_speed(gs);
+        Ok(())
     }
 
-    fn update_from_rhou(&self, gs: &mut GasState<Num>) {
+    fn update_from_rhou(&self, gs: &mut GasState<Num>) -> Result<(), GasModelError> {
         gs.T = gs.u / self.Cv;
         gs.p = gs.rho * self.R * gs.T;
         self.update_sound_speed(gs);
+        Ok(())
     }
 
-    fn update_from_rhop(&self, gs: &mut GasState<Num>) {
+    fn update_from_rhop(&self, gs: &mut GasState<Num>) -> Result<(), GasModelError> {
         gs.T = gs.p / (gs.rho * self.R);
         gs.u = self.Cv * gs.T;
         self.update_sound_speed(gs);
+        Ok(())
     }
 
     fn Cv(&self, _gs: &GasState<Num>) -> Num {
@@ -78,6 +147,18 @@ impl <Num: Number + 'static + std::fmt::Debug + Default> GasModel<Num> for Ideal
     }
 }
 
+/// An inviscid ideal gas has no viscosity
+impl<Num: Number> TransportProperties<Num> for IdealGas<Num> {
+    fn viscosity(&self, gs: &GasState<Num>) -> Num {
+        match &self.sutherland {
+            Some(Sutherland{mu_ref, T_ref, S}) => {
+                let ratio = gs.T / *T_ref;
+                *mu_ref * ratio * Num::sqrt(ratio) * (*T_ref + *S) / (gs.T + *S)
+            }
+            None => Num::zero(),
+        }
+    }
+}
 
 #[cfg(test)]
 mod test {
@@ -90,7 +171,7 @@ mod test {
         let mut gs = GasState::default();
         gs.p = 101325.0;
         gs.T = 300.0;
-        gm.update_from_pT(&mut gs);
+        gm.update_from_pT(&mut gs).unwrap();
 
         let result = GasState{
             p: 101325., 
@@ -100,7 +181,7 @@ mod test {
             a: 347.2189510957027,
         }; 
 
-        assert_eq!(gs, result);
+        assert!(gs.approx_eq(&result, 1e-10), "{} != {}", gs, result);
     }
 
     #[test]
@@ -111,7 +192,7 @@ mod test {
         gs.rho = 1.176624281484062;
         gs.T = 300.0;
 
-        gm.update_from_rhoT(&mut gs);
+        gm.update_from_rhoT(&mut gs).unwrap();
         let result = GasState{
             p: 101325., 
             T: 300.0, 
@@ -120,7 +201,7 @@ mod test {
             a: 347.2189510957027,
         }; 
 
-        assert_eq!(gs, result);
+        assert!(gs.approx_eq(&result, 1e-10), "{} != {}", gs, result);
     }
 
     #[test]
@@ -130,7 +211,7 @@ mod test {
         gs.rho = 1.176624281484062;
         gs.u = 215287.50000000006;
 
-        gm.update_from_rhou(&mut gs);
+        gm.update_from_rhou(&mut gs).unwrap();
         let result = GasState{
             p: 101325., 
             T: 300.0, 
@@ -139,7 +220,7 @@ mod test {
             a: 347.2189510957027,
         }; 
 
-        assert_eq!(gs, result);
+        assert!(gs.approx_eq(&result, 1e-10), "{} != {}", gs, result);
     }
 
     #[test]
@@ -149,7 +230,7 @@ mod test {
         gs.rho = 1.176624281484062;
         gs.p = 101325.0;
 
-        gm.update_from_rhop(&mut gs);
+        gm.update_from_rhop(&mut gs).unwrap();
         let result = GasState{
             p: 101325., 
             T: 300.0, 
@@ -158,6 +239,64 @@ mod test {
             a: 347.2189510957027,
         }; 
 
-        assert_eq!(gs, result);
+        assert!(gs.approx_eq(&result, 1e-10), "{} != {}", gs, result);
+    }
+
+    #[test]
+    fn sutherland_viscosity() {
+        // the constants for air, and the viscosities tabulated
+        // by White, Viscous Fluid Flow, to 0.1 %
+        let gm = IdealGas::with_sutherland(287.05, 1.4, 1.716e-5, 273.15, 110.4);
+        assert!(gm.is_viscous());
+        for (temp, expected) in [(300.0, 1.846e-5), (1000.0, 4.152e-5)] {
+            let gs = GasState{p: 101325.0, T: temp, ..GasState::default()};
+            let mu = gm.viscosity(&gs);
+            assert!((mu - expected).abs() < 1e-3 * expected, "{} K: {}", temp, mu);
+        }
+    }
+
+    #[test]
+    fn inviscid() {
+        let gm = IdealGas::new(287.05, 1.4);
+        let gs = GasState{p: 101325.0, T: 300.0, ..GasState::default()};
+
+        assert!(!gm.is_viscous());
+        assert_eq!(gm.viscosity(&gs), 0.0);
+    }
+
+    #[test]
+    fn sutherland_toml_round_trip() {
+        let gm = IdealGas::with_sutherland(287.05, 1.4, 1.716e-5, 273.15, 110.4);
+        let read: IdealGas<Real> = toml::from_str(&toml::to_string(&gm).unwrap()).unwrap();
+        assert!(read.is_viscous());
+
+        // gases written before viscosity was added are inviscid
+        let read: IdealGas<Real> = toml::from_str(&toml::to_string(&IdealGas::new(287.05, 1.4)).unwrap()).unwrap();
+        assert!(!read.is_viscous());
+    }
+
+    #[test]
+    fn from_species_name() {
+        let expected = [
+            ("air", 287.05, 1.4),
+            ("nitrogen", 296.8, 1.4),
+            ("oxygen", 259.8, 1.4),
+            ("argon", 208.0, 5.0 / 3.0),
+            ("helium", 2077.0, 5.0 / 3.0),
+            ("co2", 188.9, 1.289),
+        ];
+        for (species, r, gamma) in expected {
+            let gm = IdealGas::from_species_name(species).unwrap();
+            assert_eq!(gm.gas_constant(), r, "{}", species);
+            assert_eq!(gm.gamma(), gamma, "{}", species);
+        }
+    }
+
+    #[test]
+    fn unknown_species() {
+        let err = IdealGas::from_species_name("xenon").unwrap_err();
+
+        assert_eq!(err, UnknownSpeciesError{species: "xenon".to_string()});
+        assert!(err.to_string().contains("air, nitrogen"));
     }
 }
@@ -0,0 +1,45 @@
+//! A global allocator which keeps track of the current and peak heap
+//! usage, so the benchmarks can report how much memory they use
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Keeps track of the current and peak heap usage
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// The number of bytes currently allocated
+pub fn allocated() -> usize {
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+/// Start measuring the peak from the current usage,
+/// which is returned to compare the peak against
+pub fn reset_peak() -> usize {
+    let allocated = allocated();
+    PEAK.store(allocated, Ordering::Relaxed);
+    allocated
+}
+
+/// The most bytes allocated at once since [`reset_peak`]
+pub fn peak() -> usize {
+    PEAK.load(Ordering::Relaxed)
+}
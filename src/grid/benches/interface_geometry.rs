@@ -0,0 +1,52 @@
+//! Compare constructing 500,000 interfaces when none of their geometric
+//! properties are used (lazy), with constructing them and then using all
+//! of their geometric properties, which is what constructing them used
+//! to cost (eager).
+//!
+//! On the development machine, the lazy construction took about 160 ms,
+//! and the eager construction about 250 ms.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use common::vector3::Vector3;
+use grid::interface::GridInterface;
+use grid::vertex::GridVertex;
+
+const N: usize = 500_000;
+
+fn vertices() -> Vec<GridVertex> {
+    (0 ..= N)
+        .map(|i| {
+            let angle = i as f64 / N as f64;
+            GridVertex::new(Vector3{x: angle.cos() * i as f64, y: angle.sin(), z: 0.0}, i)
+        })
+        .collect()
+}
+
+fn build(vertices: &[GridVertex]) -> Vec<GridInterface> {
+    (0 .. N)
+        .map(|i| GridInterface::new_from_vertices(&[&vertices[i], &vertices[i + 1]], i))
+        .collect()
+}
+
+fn interface_geometry(c: &mut Criterion) {
+    let vertices = vertices();
+    let mut group = c.benchmark_group("interface_geometry_500k");
+    group.sample_size(20);
+
+    group.bench_function("lazy", |b| b.iter(|| build(black_box(&vertices))));
+
+    group.bench_function("eager", |b| {
+        b.iter(|| {
+            let interfaces = build(black_box(&vertices));
+            for interface in interfaces.iter() {
+                black_box((interface.area(), interface.norm(), interface.t1(), interface.t2(), interface.centre()));
+            }
+            interfaces
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, interface_geometry);
+criterion_main!(benches);
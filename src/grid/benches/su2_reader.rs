@@ -0,0 +1,66 @@
+//! Compare reading an su2 file of a square mesh with one million cells
+//! with `read_su2`, which keeps every line of the file until the end,
+//! and `read_su2_streaming`, which reads the file twice and builds the
+//! vertices and cells straight from the lines. The peak memory used
+//! while reading is printed before the timings.
+//!
+//! On the development machine, `read_su2` peaked at 2414 MB and took
+//! about 11.5 s, while `read_su2_streaming` peaked at 1948 MB and took
+//! about 9.8 s. The finished block is 833 MB. With `--features
+//! flat_interfaces` the peaks were 2089 MB and 904 MB, since the
+//! streaming reader moves the interfaces into the block rather than
+//! copying them, and most of the rest of the peak is the hash map
+//! storage of the interfaces.
+
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+mod counting_allocator;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use common::number::Real;
+use grid::block::GridBlock;
+use grid::error::GridError;
+use grid::structured::rectangle_su2;
+use grid::su2::{read_su2, read_su2_streaming};
+
+/// The number of cells along each side of the square mesh
+const N: usize = 1000;
+
+/// Write the su2 file for the mesh
+fn write_mesh() -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("square_1M.su2");
+    fs::write(&path, rectangle_su2(N, N, N as Real, N as Real)).unwrap();
+    path
+}
+
+fn open(path: &Path) -> BufReader<File> {
+    BufReader::new(File::open(path).unwrap())
+}
+
+fn peak_memory(name: &str, read: fn(BufReader<File>, usize) -> Result<GridBlock, GridError>, path: &Path) {
+    let before = counting_allocator::reset_peak();
+    let block = read(open(path), 0).unwrap();
+    let peak = counting_allocator::peak() - before;
+    let retained = counting_allocator::allocated() - before;
+    drop(block);
+    println!("{}: peak memory {:.1} MB, block {:.1} MB", name, peak as f64 / 1e6, retained as f64 / 1e6);
+}
+
+fn su2_reader(c: &mut Criterion) {
+    let path = write_mesh();
+    let mut group = c.benchmark_group("su2_reader_1M");
+    group.sample_size(10);
+
+    peak_memory("read_su2", read_su2, &path);
+    group.bench_function("read_su2", |b| b.iter(|| read_su2(open(&path), 0).unwrap()));
+
+    peak_memory("read_su2_streaming", read_su2_streaming, &path);
+    group.bench_function("read_su2_streaming", |b| b.iter(|| read_su2_streaming(open(&path), 0).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, su2_reader);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+//! Feed arbitrary bytes to the su2 readers. Malformed files should give
+//! an error, so any panic is a bug.
+//!
+//! Run from `src/grid` with `cargo +nightly fuzz run fuzz_su2`. Seeding
+//! the corpus with `tests/data/*.su2` helps the fuzzer find its way past
+//! the section headings.
+
+#![no_main]
+
+use std::io::Cursor;
+
+use grid::su2::{read_su2, read_su2_streaming};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = read_su2(data, 0);
+    let _ = read_su2_streaming(Cursor::new(data), 0);
+});
@@ -1,23 +1,33 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
+use std::fmt::Write;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek};
 use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
 
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use rlua::{UserData, UserDataMethods};
 use serde_derive::{Serialize, Deserialize};
 
+use common::number::Real;
+use common::vector3::Vector3;
+
 use crate::Block;
 use crate::Cell;
+use crate::Id;
 use crate::Interface;
 use crate::Vertex;
 
-use super::cell::GridCell;
+use super::cell::{CellShape, GridCell};
+use super::geom_calc::{polygon_signed_area, quad_signed_area, triangle_signed_area};
 use super::su2::write_su2;
 use super::vertex::GridVertex;
-use super::interface::GridInterface;
-use common::DynamicResult;
-use super::su2::read_su2;
+use super::interface::{Direction, GridInterface, InterfaceCollection};
+use crate::error::GridError;
+use super::su2::{read_su2, with_path};
 
 
 #[derive(Debug, Clone)]
@@ -41,6 +51,457 @@ impl GridBlock {
         }
         GridBlock{vertices, interfaces, cells, boundaries, dimensions, id}
     }
+
+    /// The interfaces on the boundary with the given tag, or `None`
+    /// if the block has no such boundary
+    pub fn boundary_interfaces(&self, tag: &str) -> Option<Vec<&GridInterface>> {
+        let interface_ids = self.boundaries.get(tag)?;
+        Some(interface_ids.iter().map(|id| &self.interfaces[*id]).collect())
+    }
+
+    /// The id's of the cells with a face on the boundary with the given tag,
+    /// in order of id, or `None` if the block has no such boundary
+    pub fn cells_adjacent_to_boundary(&self, tag: &str) -> Option<Vec<usize>> {
+        let boundary_interfaces: HashSet<usize> = self.boundaries.get(tag)?.iter().copied().collect();
+        let cells = self.cells
+            .iter()
+            .filter(|cell| {
+                cell.cell_faces()
+                    .iter()
+                    .any(|face| boundary_interfaces.contains(&face.interface()))
+            })
+            .map(|cell| cell.id())
+            .collect();
+        Some(cells)
+    }
+
+    /// The distance from the centre of each cell to the nearest centre of
+    /// an interface on the boundaries with the given tags, indexed by cell id.
+    /// Tags the block doesn't have are ignored, and if there are no wall
+    /// interfaces at all every distance is infinite.
+    ///
+    /// This checks every cell against every wall interface. If it becomes
+    /// a bottleneck on large grids, the wall interface centres could be put
+    /// in a k-d tree instead.
+    pub fn compute_wall_distances(&self, wall_tags: &[&str]) -> Vec<Real> {
+        let wall_centres: Vec<Vector3> = wall_tags
+            .iter()
+            .filter_map(|tag| self.boundaries.get(*tag))
+            .flatten()
+            .map(|id| self.interfaces[*id].centre())
+            .collect();
+        self.cells
+            .iter()
+            .map(|cell| {
+                wall_centres
+                    .iter()
+                    .map(|centre| centre.dist_to(cell.centre()))
+                    .fold(Real::INFINITY, Real::min)
+            })
+            .collect()
+    }
+
+    /// The (min, max) corners of the smallest axis aligned box
+    /// containing every vertex of the block
+    pub fn bounding_box(&self) -> (Vector3, Vector3) {
+        let mut min = Vector3{x: Real::INFINITY, y: Real::INFINITY, z: Real::INFINITY};
+        let mut max = Vector3{x: Real::NEG_INFINITY, y: Real::NEG_INFINITY, z: Real::NEG_INFINITY};
+        for vertex in self.vertices.iter() {
+            let pos = vertex.pos();
+            min = Vector3{x: min.x.min(pos.x), y: min.y.min(pos.y), z: min.z.min(pos.z)};
+            max = Vector3{x: max.x.max(pos.x), y: max.y.max(pos.y), z: max.z.max(pos.z)};
+        }
+        (min, max)
+    }
+
+    /// The average position of the vertices
+    pub fn centroid(&self) -> Vector3 {
+        let mut sum = Vector3::default();
+        for vertex in self.vertices.iter() {
+            sum.add_in_place(vertex.pos());
+        }
+        sum / self.vertices.len() as Real
+    }
+
+    /// The length of the diagonal of the bounding box
+    pub fn characteristic_length(&self) -> Real {
+        let (min, max) = self.bounding_box();
+        min.dist_to(&max)
+    }
+
+    /// The total length (area in 3D) of the interfaces on the
+    /// boundary `tag`, or zero if there is no such boundary
+    pub fn boundary_length(&self, tag: &str) -> Real {
+        self.boundaries
+            .get(tag)
+            .map_or(0.0, |ids| ids.iter().map(|id| self.interfaces[*id].area()).sum())
+    }
+
+    pub fn has_boundary(&self, tag: &str) -> bool {
+        self.boundaries.contains_key(tag)
+    }
+
+    /// The tags of the boundaries of the block, in alphabetical order
+    pub fn boundary_tags(&self) -> Vec<&str> {
+        let mut tags: Vec<&str> = self.boundaries.keys().map(|tag| tag.as_str()).collect();
+        tags.sort();
+        tags
+    }
+
+    /// Check the vertices of every 2D cell are ordered counter-clockwise,
+    /// returning the id's of the cells which are ordered clockwise. Mirrored
+    /// cells like these give interface normals pointing the wrong way.
+    pub fn check_cell_orientation(&self) -> Result<(), Vec<usize>> {
+        let mut clockwise_cells = Vec::new();
+        for cell in self.cells.iter() {
+            let vertices: Vec<&GridVertex> = cell.vertex_ids()
+                .iter()
+                .map(|id| &self.vertices[*id])
+                .collect();
+            let signed_area = match cell.shape() {
+                CellShape::Triangle => triangle_signed_area(&vertices),
+                CellShape::Quadrilateral => quad_signed_area(&vertices),
+                CellShape::Polygon(_) => polygon_signed_area(&vertices),
+                // the winding of the vertices only matters in two dimensions
+                CellShape::Tetrahedron | CellShape::Hexahedron | CellShape::Prism | CellShape::Pyramid => continue,
+            };
+            if signed_area < 0.0 {
+                clockwise_cells.push(cell.id());
+            }
+        }
+
+        match clockwise_cells.is_empty() {
+            true => Ok(()),
+            false => Err(clockwise_cells),
+        }
+    }
+
+    /// The id's of the interfaces whose normal points into the cell with
+    /// the lowest id of the cells either side of it, rather than out of it.
+    /// On the boundaries, this is every normal pointing into the grid.
+    pub fn verify_interface_normals(&self) -> Vec<usize> {
+        let mut checked = vec![false; self.interfaces.len()];
+        let mut inward = Vec::new();
+        // the cells are in order of id, so the first cell an
+        // interface is found in is the one with the lowest id
+        for cell in self.cells.iter() {
+            for face in cell.cell_faces().iter() {
+                let interface = face.interface();
+                if !checked[interface] {
+                    checked[interface] = true;
+                    if face.direction() == &Direction::Inwards {
+                        inward.push(interface);
+                    }
+                }
+            }
+        }
+        inward.sort();
+        inward
+    }
+
+    /// Flip the interfaces found by [`GridBlock::verify_interface_normals`],
+    /// so every normal points out of the lowest id cell beside it. Returns
+    /// the id's of the interfaces flipped.
+    pub fn fix_interface_normals(&mut self) -> Vec<usize> {
+        let inward = self.verify_interface_normals();
+        for &interface in inward.iter() {
+            self.interfaces[interface].flip_normal();
+        }
+        for cell in self.cells.iter_mut() {
+            for &interface in inward.iter() {
+                cell.flip_face(interface);
+            }
+        }
+        inward
+    }
+
+    /// Compute the dual of a 2D block, where each vertex becomes a cell,
+    /// and each cell becomes a vertex. The dual cell around an interior
+    /// vertex joins the centres of the cells around it. The dual cell around
+    /// a boundary vertex is closed off by the vertex itself and the centres
+    /// of the boundary interfaces beside it, so the dual covers the same
+    /// region as the original block. The boundaries keep their tags.
+    pub fn compute_dual_mesh(&self) -> Result<GridBlock, GridError> {
+        if self.dimensions != 2 {
+            return Err(GridError::InvalidMesh(format!(
+                "Can only compute the dual of 2D blocks, but block {} has {} dimensions",
+                self.id, self.dimensions
+            )));
+        }
+
+        let mut interface_cells: Vec<Vec<usize>> = vec![Vec::new(); self.interfaces.len()];
+        for cell in self.cells.iter() {
+            for interface in cell.interface_ids() {
+                interface_cells[interface].push(cell.id());
+            }
+        }
+        let on_boundary = |interface: usize| interface_cells[interface].len() == 1;
+        let mut vertex_interfaces: Vec<Vec<usize>> = vec![Vec::new(); self.vertices.len()];
+        for interface in self.interfaces.iter() {
+            for vertex in interface.vertex_ids() {
+                vertex_interfaces[*vertex].push(interface.id());
+            }
+        }
+
+        // the vertices of the dual are the cell centres, followed by the centre
+        // of each boundary interface and the vertices on the boundary
+        let mut positions: Vec<Vector3> = self.cells.iter().map(|cell| *cell.centre()).collect();
+        let mut boundary_interface_vertex: HashMap<usize, usize> = HashMap::new();
+        let mut boundary_vertex: HashMap<usize, usize> = HashMap::new();
+        for interface in self.interfaces.iter().filter(|interface| on_boundary(interface.id())) {
+            boundary_interface_vertex.insert(interface.id(), positions.len());
+            positions.push(interface.centre());
+            for vertex in interface.vertex_ids() {
+                if !boundary_vertex.contains_key(vertex) {
+                    boundary_vertex.insert(*vertex, positions.len());
+                    positions.push(*self.vertices[*vertex].pos());
+                }
+            }
+        }
+        let vertices: Vec<GridVertex> = positions
+            .into_iter()
+            .enumerate()
+            .map(|(id, pos)| GridVertex::new(pos, id))
+            .collect();
+
+        // walk around each vertex, from cell to cell through the interfaces
+        // sharing the vertex, collecting the vertices of the dual cell
+        let mut dual_cells: Vec<Vec<usize>> = Vec::with_capacity(self.vertices.len());
+        for (vertex, interfaces) in vertex_interfaces.iter().enumerate() {
+            if interfaces.is_empty() {
+                continue;
+            }
+            let start = interfaces.iter().copied().find(|id| on_boundary(*id)).unwrap_or(interfaces[0]);
+            let mut dual_cell = Vec::new();
+            if on_boundary(start) {
+                dual_cell.push(boundary_vertex[&vertex]);
+                dual_cell.push(boundary_interface_vertex[&start]);
+            }
+            let mut interface = start;
+            let mut cell = interface_cells[start][0];
+            for _ in 0 .. interfaces.len() {
+                dual_cell.push(cell);
+                interface = self.cells[cell]
+                    .interface_ids()
+                    .into_iter()
+                    .find(|id| *id != interface && interfaces.contains(id))
+                    .ok_or_else(|| GridError::InvalidMesh(format!(
+                        "Cell {} has only one interface touching vertex {}", cell, vertex
+                    )))?;
+                if on_boundary(interface) {
+                    dual_cell.push(boundary_interface_vertex[&interface]);
+                    break;
+                }
+                if interface == start {
+                    break;
+                }
+                cell = interface_cells[interface].iter().copied().find(|id| *id != cell).unwrap();
+            }
+
+            let dual_vertices: Vec<&GridVertex> = dual_cell.iter().map(|id| &vertices[*id]).collect();
+            if polygon_signed_area(&dual_vertices) < 0.0 {
+                dual_cell.reverse();
+            }
+            dual_cells.push(dual_cell);
+        }
+
+        #[cfg(not(feature = "flat_interfaces"))]
+        let mut interfaces = InterfaceCollection::with_capacity(dual_cells.len());
+        #[cfg(feature = "flat_interfaces")]
+        let mut interfaces = InterfaceCollection::new_flat(dual_cells.len());
+        let mut cells: Vec<GridCell> = Vec::with_capacity(dual_cells.len());
+        for dual_cell in dual_cells.iter() {
+            let n_vertices = u8::try_from(dual_cell.len()).map_err(|_| GridError::InvalidMesh(format!(
+                "Dual cell {} would have {} vertices", cells.len(), dual_cell.len()
+            )))?;
+            let shape = CellShape::from_number_of_vertices(n_vertices);
+            let interface_ids: Vec<usize> = shape
+                .interfaces(dual_cell)
+                .iter()
+                .map(|interface| {
+                    let interface_vertices: Vec<&GridVertex> = interface.iter().map(|id| &vertices[*id]).collect();
+                    interfaces.add_or_retrieve(&interface_vertices)
+                })
+                .collect();
+            let cell_interfaces: Vec<&GridInterface> = interface_ids
+                .iter()
+                .map(|id| interfaces.interface_with_id(*id))
+                .collect();
+            let cell_vertices: Vec<&GridVertex> = dual_cell.iter().map(|id| &vertices[*id]).collect();
+            cells.push(GridCell::try_new_with_shape(shape, &cell_interfaces, &cell_vertices, cells.len())?);
+        }
+
+        // each boundary interface is split in two at its centre
+        let mut boundaries: HashMap<String, Vec<usize>> = HashMap::new();
+        for (tag, boundary_interfaces) in self.boundaries.iter() {
+            let dual_boundary = boundaries.entry(tag.clone()).or_default();
+            for interface in boundary_interfaces.iter() {
+                let centre = &vertices[boundary_interface_vertex[interface]];
+                for vertex in self.interfaces[*interface].vertex_ids() {
+                    let end = &vertices[boundary_vertex[vertex]];
+                    dual_boundary.push(interfaces.find_interface(&[end, centre]));
+                }
+            }
+        }
+
+        Ok(GridBlock::new(vertices, interfaces.interfaces(), cells, boundaries, self.dimensions, self.id))
+    }
+
+    /// Whether each vertex is used by a cell or an interface
+    fn referenced_vertices(&self) -> Vec<bool> {
+        let mut referenced = vec![false; self.vertices.len()];
+        let cell_vertices = self.cells.iter().flat_map(|cell| cell.vertex_ids());
+        let interface_vertices = self.interfaces.iter().flat_map(|interface| interface.vertex_ids());
+        for &vertex_id in cell_vertices.chain(interface_vertices) {
+            referenced[vertex_id] = true;
+        }
+        referenced
+    }
+
+    /// The id's of the vertices which aren't part of any cell or interface
+    fn orphan_vertices(&self) -> Vec<usize> {
+        self.referenced_vertices()
+            .iter()
+            .enumerate()
+            .filter(|(_, &referenced)| !referenced)
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Remove the vertices which aren't part of any cell or interface,
+    /// renumbering the remaining vertices so their id's are contiguous.
+    /// Returns the number of vertices removed.
+    pub fn remove_orphan_vertices(&mut self) -> usize {
+        let referenced = self.referenced_vertices();
+        let mut new_ids = vec![usize::MAX; self.vertices.len()];
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+        for (vertex, _) in self.vertices.iter().zip(referenced).filter(|(_, referenced)| *referenced) {
+            new_ids[vertex.id()] = vertices.len();
+            vertices.push(GridVertex::new(*vertex.pos(), vertices.len()));
+        }
+
+        let n_removed = self.vertices.len() - vertices.len();
+        if n_removed > 0 {
+            for interface in self.interfaces.iter_mut() {
+                interface.renumber_vertices(&new_ids);
+            }
+            for cell in self.cells.iter_mut() {
+                cell.renumber_vertices(&new_ids);
+            }
+            self.vertices = vertices;
+        }
+        n_removed
+    }
+
+    /// Check the block is a sensible grid, returning every problem found
+    pub fn validate(&self) -> Result<(), Vec<GridValidationError>> {
+        let mut errors = Vec::new();
+
+        for cell in self.cells.iter() {
+            if cell.volume() <= 0.0 {
+                errors.push(GridValidationError::NonPositiveVolume(cell.id()));
+            }
+        }
+
+        let mut interface_tags: Vec<Option<&str>> = vec![None; self.interfaces.len()];
+        let mut tags: Vec<&String> = self.boundaries.keys().collect();
+        tags.sort();
+        for tag in tags {
+            let boundary_interfaces = &self.boundaries[tag];
+            if boundary_interfaces.is_empty() {
+                errors.push(GridValidationError::EmptyBoundary(tag.clone()));
+            }
+            for &interface_id in boundary_interfaces.iter() {
+                if self.interfaces[interface_id].number_of_attached_cells() == 2 {
+                    errors.push(GridValidationError::InteriorBoundaryInterface(tag.clone(), interface_id));
+                }
+                if let Some(other_tag) = interface_tags[interface_id] {
+                    errors.push(GridValidationError::MultipleBoundaries(interface_id, other_tag.to_string(), tag.clone()));
+                }
+                interface_tags[interface_id] = Some(tag);
+            }
+        }
+
+        for (interface, tag) in self.interfaces.iter().zip(interface_tags.iter()) {
+            match interface.number_of_attached_cells() {
+                0 => errors.push(GridValidationError::DetachedInterface(interface.id())),
+                1 if tag.is_none() => errors.push(GridValidationError::UntaggedBoundaryInterface(interface.id())),
+                _ => {}
+            }
+        }
+
+        let orphan_vertices = self.orphan_vertices();
+        if !orphan_vertices.is_empty() {
+            errors.push(GridValidationError::OrphanVertices(orphan_vertices));
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+}
+
+/// Problems that can be found with a grid
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridValidationError {
+    /// The cell with the given id has zero or negative volume
+    NonPositiveVolume(usize),
+
+    /// The interface with the given id isn't attached to any cells
+    DetachedInterface(usize),
+
+    /// The interface is on the edge of the grid, but not part of any boundary
+    UntaggedBoundaryInterface(usize),
+
+    /// The boundary (tag, interface id) has cells on both sides
+    InteriorBoundaryInterface(String, usize),
+
+    /// The interface is part of more than one boundary (id, tag, tag)
+    MultipleBoundaries(usize, String, String),
+
+    /// The boundary with the given tag has no interfaces
+    EmptyBoundary(String),
+
+    /// The vertices with the given id's aren't part of any cell or interface
+    OrphanVertices(Vec<usize>),
+}
+
+impl GridValidationError {
+    /// Whether the grid can still be used, despite the problem
+    pub fn is_warning(&self) -> bool {
+        matches!(self, GridValidationError::EmptyBoundary(_) | GridValidationError::OrphanVertices(_))
+    }
+}
+
+impl std::error::Error for GridValidationError {}
+
+impl std::fmt::Display for GridValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self {
+            GridValidationError::NonPositiveVolume(id) => write!(
+                f, "Cell {} has non-positive volume", id
+            ),
+            GridValidationError::DetachedInterface(id) => write!(
+                f, "Interface {} isn't attached to any cells", id
+            ),
+            GridValidationError::UntaggedBoundaryInterface(id) => write!(
+                f, "Interface {} is on the edge of the grid, but isn't part of a boundary", id
+            ),
+            GridValidationError::InteriorBoundaryInterface(tag, id) => write!(
+                f, "Interface {} on boundary '{}' has cells on both sides", id, tag
+            ),
+            GridValidationError::MultipleBoundaries(id, first, second) => write!(
+                f, "Interface {} is on boundaries '{}' and '{}'", id, first, second
+            ),
+            GridValidationError::EmptyBoundary(tag) => write!(
+                f, "Boundary '{}' has no interfaces", tag
+            ),
+            GridValidationError::OrphanVertices(ids) => write!(
+                f, "Vertices {:?} aren't part of any cell or interface", ids
+            ),
+        }
+    }
 }
 
 impl Block<GridVertex, GridInterface, GridCell> for GridBlock  {
@@ -73,62 +534,384 @@ impl Block<GridVertex, GridInterface, GridCell> for GridBlock  {
 #[derive(Default, Debug, Clone)]
 pub struct BlockCollection {
     blocks: Vec<GridBlock>,
+    tags: HashMap<String, usize>,
 }
 
 impl BlockCollection {
     pub fn new() -> BlockCollection {
-        BlockCollection { blocks: Vec::new() }
+        BlockCollection { blocks: Vec::new(), tags: HashMap::new() }
+    }
+
+    /// Read a block from file, giving it the smallest id not already in use
+    pub fn add_block(&mut self, file_path: &Path) -> Result<(), GridError> {
+        self.read_and_insert_block(file_path)?;
+        Ok(())
+    }
+
+    /// Read a block of the given type from `reader`, e.g. a
+    /// `Cursor` holding the contents of a grid file, giving
+    /// it the id `id`, which mustn't already be in use
+    pub fn add_block_from_reader<R: BufRead + Seek>(&mut self, reader: R, file_type: GridFileType, id: usize)
+        -> Result<(), GridError>
+    {
+        if self.find_block(id).is_some() {
+            return Err(GridError::DuplicateBlockId(id));
+        }
+        let block = read_block_from_reader(reader, file_type, id)?;
+        self.insert_block(block);
+        Ok(())
+    }
+
+    fn read_and_insert_block(&mut self, file_path: &Path) -> Result<usize, GridError> {
+        let id = self.free_ids(1)[0];
+        let block = read_block(file_path, id)?;
+        self.insert_block(block);
+        Ok(id)
     }
 
-    pub fn add_block(&mut self, file_path: &Path) -> DynamicResult<()> {
-        let ext = GridFileType::from_file_name(file_path)?;
-        let number_blocks = self.blocks.len();
-        let block = match ext {
-            GridFileType::Native | GridFileType::Su2 => read_su2(file_path, number_blocks)?,
-        };
-        self.blocks.push(block);
+    /// Read several blocks one after the other. The blocks are added in the
+    /// same order as `paths`, and given the smallest unused id's in that
+    /// order. If any of the blocks can't be read, none of them are added.
+    #[cfg(not(feature = "parallel"))]
+    pub fn add_blocks_parallel(&mut self, paths: &[PathBuf]) -> Result<(), GridError> {
+        let ids = self.free_ids(paths.len());
+        let blocks = paths
+            .iter()
+            .zip(ids.iter())
+            .map(|(path, id)| read_block(path, *id))
+            .collect::<Result<Vec<GridBlock>, GridError>>()?;
+        for block in blocks {
+            self.insert_block(block);
+        }
         Ok(())
     }
 
+    /// Read several blocks concurrently. The blocks are added in the
+    /// same order as `paths`, and given the smallest unused id's in that
+    /// order. If any of the blocks can't be read, none of them are added.
+    #[cfg(feature = "parallel")]
+    pub fn add_blocks_parallel(&mut self, paths: &[PathBuf]) -> Result<(), GridError> {
+        let ids = self.free_ids(paths.len());
+        let blocks = paths
+            .par_iter()
+            .zip(ids.par_iter())
+            .map(|(path, id)| read_block(path, *id))
+            .collect::<Result<Vec<GridBlock>, GridError>>()?;
+        for block in blocks {
+            self.insert_block(block);
+        }
+        Ok(())
+    }
+
+    /// The `n` smallest id's which no block has, in increasing order
+    fn free_ids(&self, n: usize) -> Vec<usize> {
+        let mut used = self.blocks.iter().map(|block| block.id).peekable();
+        let mut ids = Vec::with_capacity(n);
+        let mut id = 0;
+        while ids.len() < n {
+            if used.peek() == Some(&id) {
+                used.next();
+            } else {
+                ids.push(id);
+            }
+            id += 1;
+        }
+        ids
+    }
+
+    /// Insert a block, keeping the blocks in order of id
+    fn insert_block(&mut self, block: GridBlock) {
+        let index = self.blocks.partition_point(|other| other.id < block.id);
+        self.blocks.insert(index, block);
+    }
+
+    /// The block with the given id, or `None` if there isn't one
+    pub fn find_block(&self, id: usize) -> Option<&GridBlock> {
+        self.blocks
+            .binary_search_by_key(&id, |block| block.id)
+            .ok()
+            .map(|index| &self.blocks[index])
+    }
+
+    /// # Panics
+    ///
+    /// If there is no block with the given id
     pub fn get_block(&self, id: usize) -> &GridBlock {
-        &self.blocks[id]
+        self.find_block(id).unwrap_or_else(|| panic!("There is no block with id {}", id))
     }
 
+    /// The blocks, in order of id
     pub fn blocks(&self) -> &Vec<GridBlock> {
         &self.blocks
     }
 
+    /// Remove the block with the given id, along with its tags. The
+    /// id is given to the next block added. Returns `None` if there
+    /// is no block with the id.
+    pub fn remove_block(&mut self, id: usize) -> Option<GridBlock> {
+        let index = self.blocks.binary_search_by_key(&id, |block| block.id).ok()?;
+        self.tags.retain(|_, tagged_id| *tagged_id != id);
+        Some(self.blocks.remove(index))
+    }
+
+    /// Number the blocks 0, 1, 2, ... in their current order, closing
+    /// any gaps left by removed blocks. The tags move with their blocks.
+    pub fn reassign_ids(&mut self) {
+        let new_ids: HashMap<usize, usize> = self.blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| (block.id, index))
+            .collect();
+        for (index, block) in self.blocks.iter_mut().enumerate() {
+            block.id = index;
+        }
+        for id in self.tags.values_mut() {
+            *id = new_ids[id];
+        }
+    }
+
+    /// Give the block with the given id a name, so it can be found
+    /// without relying on the order the blocks were added in. A block
+    /// may have more than one tag, but if the tag is already in use it
+    /// is moved to this block.
+    ///
+    /// # Panics
+    ///
+    /// If there is no block with the given id
+    pub fn tag_block(&mut self, id: usize, tag: &str) {
+        assert!(self.find_block(id).is_some(), "Can't tag block {}, there is no block with that id", id);
+        self.tags.insert(tag.to_string(), id);
+    }
+
+    /// The block with the given tag, or `None` if no block has that tag
+    pub fn get_block_by_tag(&self, tag: &str) -> Option<&GridBlock> {
+        self.tags.get(tag).and_then(|id| self.find_block(*id))
+    }
+
+    /// The tags of the blocks, in alphabetical order
+    pub fn tags(&self) -> Vec<&str> {
+        let mut tags: Vec<&str> = self.tags.keys().map(|tag| tag.as_str()).collect();
+        tags.sort();
+        tags
+    }
+
+    /// The number of cells in all the blocks
+    pub fn total_cell_count(&self) -> usize {
+        self.blocks.iter().map(|block| block.cells().len()).sum()
+    }
+
+    /// The number of vertices in all the blocks. Vertices shared
+    /// between blocks are counted once for each block.
+    pub fn total_vertex_count(&self) -> usize {
+        self.blocks.iter().map(|block| block.vertices().len()).sum()
+    }
+
+    /// The number of interfaces in all the blocks
+    pub fn total_interface_count(&self) -> usize {
+        self.blocks.iter().map(|block| block.interfaces().len()).sum()
+    }
+
+    /// The tags of the boundaries of any of the blocks, in alphabetical order
+    pub fn boundary_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self.blocks
+            .iter()
+            .flat_map(|block| block.boundary_tags())
+            .map(|tag| tag.to_string())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// A table of the number of cells, interfaces and
+    /// vertices in each block, and in total
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{:<8} {:>10} {:>12} {:>10}\n", "Block", "Cells", "Interfaces", "Vertices");
+        for block in self.blocks.iter() {
+            writeln!(summary, "{:<8} {:>10} {:>12} {:>10}", block.id(), block.cells().len(),
+                     block.interfaces().len(), block.vertices().len()).unwrap();
+        }
+        writeln!(summary, "{:<8} {:>10} {:>12} {:>10}", "Total", self.total_cell_count(),
+                 self.total_interface_count(), self.total_vertex_count()).unwrap();
+        summary
+    }
+
     /// write the blocks out in native format
-    pub fn write_blocks(&self, grid_dir: &Path) -> DynamicResult<()> {
-        let mut file_name = grid_dir.to_path_buf();
-        let ext = GridFileType::Native.extension();
-        file_name.push("block");
+    pub fn write_blocks(&self, grid_dir: &Path) -> Result<(), GridError> {
+        self.write_blocks_as(grid_dir, GridFileType::Native)
+    }
+
+    /// Write the blocks to `grid_dir` in the given format, e.g. su2
+    /// to read them with other tools, as `block_0000.su2` and so on
+    pub fn write_blocks_as(&self, grid_dir: &Path, file_type: GridFileType) -> Result<(), GridError> {
+        let ext = file_type.extension();
         for block in self.blocks.iter() {
-            file_name.set_file_name(format!("block_{:04}.{}", block.id(), ext));
-            write_block(block, &file_name)?;
+            write_block(block, &grid_dir.join(block_file_name(block.id(), ext)))?;
         }
         Ok(())
     }
 }
 
+/// The name of the file the block with id `id` is written to,
+/// with the extension `ext`, e.g. `block_0003.su2`
+pub fn block_file_name(id: usize, ext: &str) -> String {
+    format!("block_{:04}.{}", id, ext)
+}
+
 impl UserData for BlockCollection {
     fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
         methods.add_method_mut("add_block", |_, block_collection, file_name: String| {
             let path = PathBuf::from_str(&file_name).unwrap();
-            block_collection.add_block(&path).unwrap(); 
+            block_collection.add_block(&path)
+                .map_err(|err| rlua::Error::RuntimeError(err.to_string()))?;
+            Ok(())
+        });
+        methods.add_method_mut("add_block_with_tag", |_, block_collection, (file_name, tag): (String, String)| {
+            let path = PathBuf::from_str(&file_name).unwrap();
+            let id = block_collection.read_and_insert_block(&path)
+                .map_err(|err| rlua::Error::RuntimeError(err.to_string()))?;
+            block_collection.tag_block(id, &tag);
             Ok(())
         });
     }
 }
 
-pub fn write_block<V, I, C, B>(block: &B, file_name: &Path) -> DynamicResult<()> 
+/// Vertices of different blocks closer than this are taken to be the same vertex
+const MERGE_TOLERANCE: Real = 1e-10;
+
+/// Combine several blocks into a single block, with id 0.
+///
+/// Vertices of different blocks at the same position (within
+/// `MERGE_TOLERANCE`) become a single vertex, so the interfaces the blocks
+/// share become interior interfaces of the merged block. These shared
+/// interfaces are removed from the boundaries, and any boundary left
+/// without interfaces is dropped. Boundaries with the same tag in
+/// different blocks are combined. Vertices, interfaces and cells are
+/// given new id's, in the order of the blocks.
+pub fn merge_blocks(blocks: &[GridBlock]) -> Result<GridBlock, GridError> {
+    let dimensions = match blocks.first() {
+        Some(block) => block.dimensions,
+        None => return Err(GridError::InvalidMesh("No blocks to merge".to_string())),
+    };
+    if let Some(block) = blocks.iter().find(|block| block.dimensions != dimensions) {
+        return Err(GridError::InvalidMesh(format!(
+            "Can't merge blocks with different dimensions: block {} has {}, but block {} has {}",
+            blocks[0].id, dimensions, block.id, block.dimensions
+        )));
+    }
+
+    // merge the vertices, keeping track of the new id of each vertex in each block
+    let mut vertices: Vec<GridVertex> = Vec::new();
+    let mut vertex_bins: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+    let mut vertex_ids: Vec<Vec<usize>> = Vec::with_capacity(blocks.len());
+    for block in blocks.iter() {
+        let mut block_vertex_ids = Vec::with_capacity(block.vertices.len());
+        for vertex in block.vertices.iter() {
+            let pos = vertex.pos();
+            let bin = [pos.x, pos.y, pos.z].map(|x| (x / MERGE_TOLERANCE).floor() as i64);
+            let existing = neighbouring_bins(bin)
+                .filter_map(|bin| vertex_bins.get(&bin))
+                .flatten()
+                .find(|id| vertices[**id].pos().dist_to(pos) < MERGE_TOLERANCE)
+                .copied();
+            let id = existing.unwrap_or_else(|| {
+                let id = vertices.len();
+                vertices.push(GridVertex::new(*pos, id));
+                vertex_bins.entry(bin).or_default().push(id);
+                id
+            });
+            block_vertex_ids.push(id);
+        }
+        vertex_ids.push(block_vertex_ids);
+    }
+
+    // rebuild the interfaces and cells from the new vertex id's
+    let n_cells = blocks.iter().map(|block| block.cells.len()).sum();
+    #[cfg(not(feature = "flat_interfaces"))]
+    let mut interfaces = InterfaceCollection::with_capacity(n_cells);
+    #[cfg(feature = "flat_interfaces")]
+    let mut interfaces = InterfaceCollection::new_flat(n_cells);
+    let mut cells: Vec<GridCell> = Vec::with_capacity(n_cells);
+    for (block, block_vertex_ids) in blocks.iter().zip(vertex_ids.iter()) {
+        for cell in block.cells.iter() {
+            let cell_vertex_ids: Vec<usize> = cell.vertex_ids()
+                .iter()
+                .map(|id| block_vertex_ids[*id])
+                .collect();
+            let interface_ids: Vec<usize> = cell.shape()
+                .interfaces(&cell_vertex_ids)
+                .iter()
+                .map(|interface| {
+                    let interface_vertices: Vec<&GridVertex> = interface.iter().map(|id| &vertices[*id]).collect();
+                    interfaces.add_or_retrieve(&interface_vertices)
+                })
+                .collect();
+            let cell_interfaces: Vec<&GridInterface> = interface_ids
+                .iter()
+                .map(|id| interfaces.interface_with_id(*id))
+                .collect();
+            let cell_vertices: Vec<&GridVertex> = cell_vertex_ids.iter().map(|id| &vertices[*id]).collect();
+            cells.push(GridCell::try_new_with_shape(*cell.shape(), &cell_interfaces, &cell_vertices, cells.len())?);
+        }
+    }
+
+    // find the interfaces on the boundaries, in the merged collection
+    let mut boundaries: HashMap<String, Vec<usize>> = HashMap::new();
+    for (block, block_vertex_ids) in blocks.iter().zip(vertex_ids.iter()) {
+        for (tag, boundary_interfaces) in block.boundaries.iter() {
+            let merged_boundary = boundaries.entry(tag.clone()).or_default();
+            for interface_id in boundary_interfaces.iter() {
+                let interface_vertices: Vec<&GridVertex> = block.interfaces[*interface_id]
+                    .vertex_ids()
+                    .iter()
+                    .map(|id| &vertices[block_vertex_ids[*id]])
+                    .collect();
+                let merged_id = interfaces.find_interface(&interface_vertices);
+                if !merged_boundary.contains(&merged_id) {
+                    merged_boundary.push(merged_id);
+                }
+            }
+        }
+    }
+
+    let mut block = GridBlock::new(vertices, interfaces.interfaces(), cells, boundaries, dimensions, 0);
+    let interior = |id: &usize| block.interfaces[*id].number_of_attached_cells() == 2;
+    for boundary_interfaces in block.boundaries.values_mut() {
+        boundary_interfaces.retain(|id| !interior(id));
+    }
+    block.boundaries.retain(|_, boundary_interfaces| !boundary_interfaces.is_empty());
+    Ok(block)
+}
+
+/// The bin containing a point, and the bins around it
+fn neighbouring_bins(bin: [i64; 3]) -> impl Iterator<Item = [i64; 3]> {
+    (-1 ..= 1).flat_map(move |i| {
+        (-1 ..= 1).flat_map(move |j| {
+            (-1 ..= 1).map(move |k| [bin[0] + i, bin[1] + j, bin[2] + k])
+        })
+    })
+}
+
+/// Read a block from a file, choosing the reader based on the extension
+fn read_block(file_path: &Path, id: usize) -> Result<GridBlock, GridError> {
+    let file_type = GridFileType::from_file_name(file_path)?;
+    let file = File::open(file_path).map_err(|err| with_path(err, file_path))?;
+    read_block_from_reader(BufReader::new(file), file_type, id)
+}
+
+fn read_block_from_reader<R: BufRead + Seek>(reader: R, file_type: GridFileType, id: usize) -> Result<GridBlock, GridError> {
+    match file_type {
+        GridFileType::Native | GridFileType::Su2 => read_su2(reader, id),
+    }
+}
+
+pub fn write_block<V, I, C, B>(block: &B, file_name: &Path) -> Result<(), GridError>
     where B: Block<V, I, C>, V: Vertex, I: Interface + Clone, C: Cell
 {
     let file_type = GridFileType::from_file_name(file_name)?; 
     match file_type {
         GridFileType::Native | GridFileType::Su2 => write_su2(file_name, block),
     }
-    Ok(())
 }
 
 /// For handling errors associated with file types we don't know how to read
@@ -157,7 +940,7 @@ impl UnknownFileType {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum GridFileType {
     Native, Su2,
@@ -186,6 +969,7 @@ impl GridFileType {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::vector3::Vector3;
 
 
     #[test]
@@ -195,6 +979,322 @@ mod tests {
         assert_eq!(file_type, Ok(GridFileType::Su2));
     }
 
+    fn two_squares() -> BlockCollection {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+        block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+        block_collection
+    }
+
+    #[test]
+    fn collection_counts() {
+        let block_collection = two_squares();
+
+        assert_eq!(block_collection.total_cell_count(), 18);
+        assert_eq!(block_collection.total_interface_count(), 48);
+        assert_eq!(block_collection.total_vertex_count(), 32);
+        assert_eq!(BlockCollection::new().total_cell_count(), 0);
+    }
+
+    #[test]
+    fn collection_boundary_tags() {
+        let block_collection = two_squares();
+
+        assert_eq!(block_collection.boundary_tags(), vec!["inflow", "outflow", "slip_wall_bottom", "slip_wall_top"]);
+    }
+
+    #[test]
+    fn collection_summary() {
+        let summary = two_squares().summary();
+        let lines: Vec<Vec<&str>> = summary.lines().map(|line| line.split_whitespace().collect()).collect();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1], vec!["0", "9", "24", "16"]);
+        assert_eq!(lines[2], vec!["1", "9", "24", "16"]);
+        assert_eq!(lines[3], vec!["Total", "18", "48", "32"]);
+    }
+
+    fn read_square() -> GridBlock {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+        block_collection.get_block(0).clone()
+    }
+
+    #[test]
+    fn boundary_interfaces() {
+        let block = read_square();
+
+        for tag in ["inflow", "outflow", "slip_wall_bottom", "slip_wall_top"] {
+            assert!(block.has_boundary(tag));
+            assert_eq!(block.boundary_interfaces(tag).unwrap().len(), 3);
+        }
+        let inflow_vertices: Vec<&Vec<usize>> = block.boundary_interfaces("inflow")
+            .unwrap()
+            .iter()
+            .map(|interface| interface.vertex_ids())
+            .collect();
+        assert_eq!(inflow_vertices, vec![&vec![4, 0], &vec![8, 4], &vec![12, 8]]);
+        assert!(!block.has_boundary("wall"));
+        assert!(block.boundary_interfaces("wall").is_none());
+    }
+
+    #[test]
+    fn cells_adjacent_to_boundary() {
+        let block = read_square();
+
+        assert_eq!(block.cells_adjacent_to_boundary("inflow"), Some(vec![0, 3, 6]));
+        assert_eq!(block.cells_adjacent_to_boundary("outflow"), Some(vec![2, 5, 8]));
+        assert_eq!(block.cells_adjacent_to_boundary("slip_wall_bottom"), Some(vec![0, 1, 2]));
+        assert_eq!(block.cells_adjacent_to_boundary("wall"), None);
+    }
+
+    #[test]
+    fn wall_distances() {
+        let block = read_square();
+
+        let distances = block.compute_wall_distances(&["slip_wall_bottom", "slip_wall_top"]);
+        assert_eq!(distances, vec![0.5, 0.5, 0.5, 1.5, 1.5, 1.5, 0.5, 0.5, 0.5]);
+        let distances = block.compute_wall_distances(&["slip_wall_bottom", "wall"]);
+        assert_eq!(distances, vec![0.5, 0.5, 0.5, 1.5, 1.5, 1.5, 2.5, 2.5, 2.5]);
+        let distances = block.compute_wall_distances(&["wall"]);
+        assert!(distances.iter().all(|distance| distance.is_infinite()));
+    }
+
+    #[test]
+    fn interface_neighbours() {
+        let block = read_square();
+        let boundary_ids: HashSet<usize> = block.boundaries().values().flatten().cloned().collect();
+
+        for interface in block.interfaces().iter() {
+            match boundary_ids.contains(&interface.id()) {
+                true => assert!(interface.left_cell().is_some() ^ interface.right_cell().is_some()),
+                false => assert!(interface.left_cell().is_some() && interface.right_cell().is_some()),
+            }
+        }
+        // interface 1 is between the first two cells, and its normal points
+        // in +x, so into cell 1
+        assert_eq!(block.interfaces()[1].left_cell(), Some(1));
+        assert_eq!(block.interfaces()[1].right_cell(), Some(0));
+    }
+
+    #[test]
+    fn boundary_length() {
+        let block = read_square();
+
+        for tag in ["inflow", "outflow", "slip_wall_bottom", "slip_wall_top"] {
+            assert_eq!(block.boundary_length(tag), 3.0, "{}", tag);
+        }
+        assert_eq!(block.boundary_length("wall"), 0.0);
+    }
+
+    #[test]
+    fn extent() {
+        let block = read_square();
+
+        assert_eq!(block.bounding_box(), (Vector3{x: 0.0, y: 0.0, z: 0.0}, Vector3{x: 3.0, y: 3.0, z: 0.0}));
+        assert_eq!(block.centroid(), Vector3{x: 1.5, y: 1.5, z: 0.0});
+        assert_eq!(block.characteristic_length(), Real::sqrt(18.0));
+    }
+
+    #[test]
+    fn boundary_cell_ids() {
+        let block = read_square();
+
+        assert_eq!(block.n_cells(), 9);
+        assert_eq!(block.n_interfaces(), 24);
+        assert_eq!(block.boundary_cell_ids("inflow"), vec![0, 3, 6]);
+        assert_eq!(block.boundary_cell_ids("slip_wall_top"), vec![6, 7, 8]);
+        assert!(block.boundary_cell_ids("wall").is_empty());
+    }
+
+    #[test]
+    fn boundary_tags() {
+        let block = read_square();
+
+        assert_eq!(block.boundary_tags(), vec!["inflow", "outflow", "slip_wall_bottom", "slip_wall_top"]);
+    }
+
+    #[test]
+    fn validate_square() {
+        let block = read_square();
+
+        assert_eq!(block.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_untagged_boundary() {
+        let mut block = read_square();
+        block.boundaries.remove("inflow");
+        let errors = block.validate().unwrap_err();
+
+        assert_eq!(errors, vec![
+            GridValidationError::UntaggedBoundaryInterface(3),
+            GridValidationError::UntaggedBoundaryInterface(12),
+            GridValidationError::UntaggedBoundaryInterface(19),
+        ]);
+    }
+
+    #[test]
+    fn validate_bad_boundaries() {
+        let mut block = read_square();
+        block.boundaries.insert("interior".to_string(), vec![1]);
+        block.boundaries.insert("duplicate".to_string(), vec![0]);
+        block.boundaries.insert("empty".to_string(), vec![]);
+        let errors = block.validate().unwrap_err();
+
+        assert_eq!(errors, vec![
+            GridValidationError::EmptyBoundary("empty".to_string()),
+            GridValidationError::InteriorBoundaryInterface("interior".to_string(), 1),
+            GridValidationError::MultipleBoundaries(0, "duplicate".to_string(), "slip_wall_bottom".to_string()),
+        ]);
+        assert!(errors[0].is_warning());
+        assert!(!errors[1].is_warning());
+    }
+
+    #[test]
+    fn validate_orphan_vertex() {
+        let mut block = read_square();
+        block.vertices.push(GridVertex::new(Vector3{x: 5.0, y: 5.0, z: 0.0}, 16));
+        let errors = block.validate().unwrap_err();
+
+        assert_eq!(errors, vec![GridValidationError::OrphanVertices(vec![16])]);
+        assert!(errors[0].is_warning());
+    }
+
+    #[test]
+    fn boundary_normals_point_outwards() {
+        let block = read_square();
+
+        assert!(block.verify_interface_normals().is_empty());
+        let centre = Vector3{x: 1.5, y: 1.5, z: 0.0};
+        for tag in block.boundary_tags() {
+            for interface in block.boundary_interfaces(tag).unwrap() {
+                let outwards = &interface.centre() - &centre;
+                assert!(outwards.dot(&interface.norm()) > 0.0, "Interface {} points inwards", interface.id());
+            }
+        }
+    }
+
+    #[test]
+    fn fix_interface_normals() {
+        let mut block = read_square();
+        let original = block.clone();
+        // the interface between cells 0 and 1, and the bottom of cell 0
+        for interface in [0, 1] {
+            block.interfaces[interface].flip_normal();
+            for cell in block.cells.iter_mut() {
+                cell.flip_face(interface);
+            }
+        }
+
+        assert_eq!(block.verify_interface_normals(), vec![0, 1]);
+        assert_eq!(block.fix_interface_normals(), vec![0, 1]);
+        assert!(block.verify_interface_normals().is_empty());
+        assert_eq!(block.interfaces[1].norm(), Vector3{x: 1.0, y: 0.0, z: 0.0});
+        assert_eq!(block.interfaces, original.interfaces);
+        assert_eq!(block.cells, original.cells);
+    }
+
+    #[test]
+    fn cell_orientation() {
+        let mut block = read_square();
+
+        assert_eq!(block.check_cell_orientation(), Ok(()));
+
+        // mirroring the grid in the y axis turns every cell clockwise
+        for vertex in block.vertices.iter_mut() {
+            let mut pos = *vertex.pos();
+            pos.x = -pos.x;
+            *vertex = GridVertex::new(pos, vertex.id());
+        }
+        assert_eq!(block.check_cell_orientation(), Err((0 .. 9).collect()));
+    }
+
+    #[test]
+    fn block_tags() {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+        block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+        block_collection.tag_block(1, "outer");
+        block_collection.tag_block(0, "inner");
+
+        assert_eq!(block_collection.get_block_by_tag("inner").unwrap().id(), 0);
+        assert_eq!(block_collection.get_block_by_tag("outer").unwrap().id(), 1);
+        assert!(block_collection.get_block_by_tag("missing").is_none());
+        assert_eq!(block_collection.tags(), vec!["inner", "outer"]);
+
+        block_collection.tag_block(1, "inner");
+        assert_eq!(block_collection.get_block_by_tag("inner").unwrap().id(), 1);
+    }
+
+    #[test]
+    fn remove_then_add_block() {
+        let mut block_collection = BlockCollection::new();
+        for _ in 0 .. 3 {
+            block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+        }
+        block_collection.tag_block(1, "middle");
+        block_collection.tag_block(2, "last");
+
+        assert_eq!(block_collection.remove_block(1).unwrap().id(), 1);
+        assert!(block_collection.remove_block(1).is_none());
+        assert!(block_collection.find_block(1).is_none());
+        assert_eq!(block_collection.tags(), vec!["last"]);
+
+        // the gap is filled before any new id's are used
+        let paths = vec![PathBuf::from("./tests/data/square.su2"); 2];
+        block_collection.add_blocks_parallel(&paths).unwrap();
+        let ids: Vec<usize> = block_collection.blocks().iter().map(|block| block.id()).collect();
+        assert_eq!(ids, vec![0, 1, 2, 3]);
+        assert_eq!(block_collection.get_block(3).id(), 3);
+        assert_eq!(block_collection.get_block_by_tag("last").unwrap().id(), 2);
+    }
+
+    #[test]
+    fn reassign_ids() {
+        let mut block_collection = BlockCollection::new();
+        for _ in 0 .. 4 {
+            block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+        }
+        block_collection.tag_block(3, "last");
+        block_collection.remove_block(0);
+        block_collection.remove_block(2);
+
+        block_collection.reassign_ids();
+
+        let ids: Vec<usize> = block_collection.blocks().iter().map(|block| block.id()).collect();
+        assert_eq!(ids, vec![0, 1]);
+        assert_eq!(block_collection.get_block_by_tag("last").unwrap().id(), 1);
+        block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+        assert_eq!(block_collection.get_block(2).id(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn tag_missing_block() {
+        let mut block_collection = BlockCollection::new();
+        block_collection.tag_block(0, "inner");
+    }
+
+    #[test]
+    fn add_block_with_tag_from_lua() {
+        let lua = rlua::Lua::new();
+        let block_collection = lua.context(|lua_ctx| {
+            lua_ctx.globals().set("blocks", BlockCollection::new()).unwrap();
+            lua_ctx.load(r#"blocks:add_block_with_tag("./tests/data/square.su2", "square")"#)
+                .exec()
+                .unwrap();
+            lua_ctx.globals().get::<_, rlua::AnyUserData>("blocks")
+                .unwrap()
+                .borrow::<BlockCollection>()
+                .unwrap()
+                .clone()
+        });
+
+        assert_eq!(block_collection.tags(), vec!["square"]);
+        assert_eq!(block_collection.get_block_by_tag("square").unwrap().cells().len(), 9);
+    }
+
     #[test]
     fn grid_file_type_unknown() {
         let file_type = GridFileType::from_file_name(&PathBuf::from("grid.su3")); 
@@ -4,24 +4,34 @@ use common::vector3::Vector3;
 use crate::interface::{GridInterface, InterfaceCollection};
 use crate::vertex::GridVertex;
 use crate::interface::Direction;
-use crate::geom_calc::{compute_centre_of_vertices, quad_area, triangle_area};
+use crate::geom_calc::{compute_cell_centroid_weighted, polygon_area, polyhedron_volume, quad_area, triangle_area};
 use crate::{Cell, Id};
+use crate::error::GridError;
 
 /// The shape of the cell
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum CellShape {
     Triangle,
     Quadrilateral,
+    Tetrahedron,
+    Hexahedron,
+    Prism,
+    Pyramid,
+
+    /// A two dimensional polygon with the given number of vertices
+    Polygon(u8),
 }
 
 impl CellShape {
-    /// Convert number of vertices to cell shape
+    /// Convert number of vertices to the shape of a two dimensional cell.
+    /// Four vertices are assumed to be a quadrilateral, rather than a
+    /// tetrahedron, and more than four a polygon.
     pub fn from_number_of_vertices(n_vertices: u8) -> CellShape {
         match n_vertices {
             0 | 1 | 2 => panic!("Not enough vertices to form a cell: {n_vertices}"),
             3 => CellShape::Triangle,
             4 => CellShape::Quadrilateral,
-            _ => panic!("Unsupported number of vertices for cell: {n_vertices}"),
+            n => CellShape::Polygon(n),
         }
     }
 
@@ -30,15 +40,73 @@ impl CellShape {
         match &self {
             CellShape::Triangle => 3,
             CellShape::Quadrilateral => 4,
+            CellShape::Tetrahedron => 4,
+            CellShape::Hexahedron => 8,
+            CellShape::Prism => 6,
+            CellShape::Pyramid => 5,
+            CellShape::Polygon(n) => *n as usize,
+        }
+    }
+
+    /// The number of interfaces around the shape
+    pub fn number_of_interfaces(&self) -> usize {
+        match &self {
+            CellShape::Triangle => 3,
+            CellShape::Quadrilateral => 4,
+            CellShape::Tetrahedron => 4,
+            CellShape::Hexahedron => 6,
+            CellShape::Prism => 5,
+            CellShape::Pyramid => 5,
+            CellShape::Polygon(n) => *n as usize,
+        }
+    }
+
+    /// The number of spatial dimensions the shape fills
+    pub fn dimensions(&self) -> u8 {
+        match &self {
+            CellShape::Triangle | CellShape::Quadrilateral | CellShape::Polygon(_) => 2,
+            CellShape::Tetrahedron | CellShape::Hexahedron | CellShape::Prism | CellShape::Pyramid => 3,
         }
     }
 
     /// Convert SU2 element type to cell shape
     pub fn from_su2_element_type(elem_type: usize) -> CellShape {
+        CellShape::try_from_su2_element_type(elem_type)
+            .expect("Invalid, or unsupported su2 element type")
+    }
+
+    /// Convert SU2 element type to cell shape, or `None` if the
+    /// element type isn't supported. Polygons (element type 7) can
+    /// have any number of vertices, so they aren't converted here.
+    pub fn try_from_su2_element_type(elem_type: usize) -> Option<CellShape> {
+        match elem_type {
+            5 => Some(CellShape::Triangle),
+            9 => Some(CellShape::Quadrilateral),
+            10 => Some(CellShape::Tetrahedron),
+            12 => Some(CellShape::Hexahedron),
+            13 => Some(CellShape::Prism),
+            14 => Some(CellShape::Pyramid),
+            _ => None,
+        }
+    }
+
+    /// Convert Gmsh element type to cell shape
+    pub fn from_gmsh_element_type(elem_type: usize) -> CellShape {
+        CellShape::try_from_gmsh_element_type(elem_type)
+            .expect("Invalid, or unsupported gmsh element type")
+    }
+
+    /// Convert Gmsh element type to cell shape, or `None` if the
+    /// element type isn't a supported (first order) cell
+    pub fn try_from_gmsh_element_type(elem_type: usize) -> Option<CellShape> {
         match elem_type {
-            5 => CellShape::Triangle,
-            9 => CellShape::Quadrilateral,
-            _ => panic!("Invalid, or unsupported su2 element type"),
+            2 => Some(CellShape::Triangle),
+            3 => Some(CellShape::Quadrilateral),
+            4 => Some(CellShape::Tetrahedron),
+            5 => Some(CellShape::Hexahedron),
+            6 => Some(CellShape::Prism),
+            7 => Some(CellShape::Pyramid),
+            _ => None,
         }
     }
 
@@ -46,11 +114,22 @@ impl CellShape {
         match &self {
             CellShape::Triangle => 5,
             CellShape::Quadrilateral => 9,
+            CellShape::Tetrahedron => 10,
+            CellShape::Hexahedron => 12,
+            CellShape::Prism => 13,
+            CellShape::Pyramid => 14,
+            CellShape::Polygon(_) => 7,
         }
     }
 
-    /// Determine the id's of each of the vertices in each interface
+    /// Determine the id's of each of the vertices in each interface.
+    /// The vertices of 3D cells are in the VTK order, which su2 also uses.
     pub fn interfaces(&self, vertices: &[usize]) -> Vec<Vec<usize>> {
+        let faces = |faces: &[&[usize]]| -> Vec<Vec<usize>> {
+            faces.iter()
+                .map(|face| face.iter().map(|i| vertices[*i]).collect())
+                .collect()
+        };
         match &self {
             CellShape::Triangle => {
                 vec![
@@ -67,6 +146,20 @@ impl CellShape {
                     vec![vertices[3], vertices[0]],
                 ]
             }
+            CellShape::Tetrahedron => faces(&[&[0, 2, 1], &[0, 1, 3], &[1, 2, 3], &[2, 0, 3]]),
+            CellShape::Hexahedron => faces(&[
+                &[0, 3, 2, 1], &[4, 5, 6, 7],
+                &[0, 1, 5, 4], &[1, 2, 6, 5], &[2, 3, 7, 6], &[3, 0, 4, 7],
+            ]),
+            CellShape::Prism => faces(&[
+                &[0, 2, 1], &[3, 4, 5],
+                &[0, 1, 4, 3], &[1, 2, 5, 4], &[2, 0, 3, 5],
+            ]),
+            CellShape::Pyramid => faces(&[&[0, 3, 2, 1], &[0, 1, 4], &[1, 2, 4], &[2, 3, 4], &[3, 0, 4]]),
+            CellShape::Polygon(n) => {
+                let n = *n as usize;
+                (0 .. n).map(|i| vec![vertices[i], vertices[(i + 1) % n]]).collect()
+            }
         }
     }
 
@@ -75,6 +168,11 @@ impl CellShape {
         match &self {
             CellShape::Triangle => triangle_area(vertices),
             CellShape::Quadrilateral => quad_area(vertices),
+            CellShape::Polygon(_) => polygon_area(vertices),
+            CellShape::Tetrahedron | CellShape::Hexahedron | CellShape::Prism | CellShape::Pyramid => {
+                let local_ids: Vec<usize> = (0 .. vertices.len()).collect();
+                polyhedron_volume(vertices, &self.interfaces(&local_ids))
+            }
         }
     }
 }
@@ -119,8 +217,22 @@ impl GridCell {
     ///
     /// * `id`: The id of the cell
     pub fn new(interfaces: &[&GridInterface], vertices: &[&GridVertex], id: usize) -> GridCell {
+        GridCell::try_new(interfaces, vertices, id)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
 
+    /// Create a cell from the surrounding interfaces vertices, or an
+    /// error if the centre of the cell is on one of its interfaces
+    pub fn try_new(interfaces: &[&GridInterface], vertices: &[&GridVertex], id: usize) -> Result<GridCell, GridError> {
         let shape = CellShape::from_number_of_vertices(vertices.len() as u8);
+        GridCell::try_new_with_shape(shape, interfaces, vertices, id)
+    }
+
+    /// Create a cell of a given shape, for when the shape can't be told
+    /// from the number of vertices (e.g. a tetrahedron)
+    pub fn try_new_with_shape(shape: CellShape, interfaces: &[&GridInterface], vertices: &[&GridVertex], id: usize)
+        -> Result<GridCell, GridError>
+    {
         let mut cell_faces = Vec::with_capacity(interfaces.len());
 
         // temporary vector of references to the actual vertices
@@ -128,25 +240,27 @@ impl GridCell {
         for vertex in vertices.iter() {
             vertex_ids.push(vertex.id());
         }
-        let centre = compute_centre_of_vertices(vertices);
+        let centre = compute_cell_centroid_weighted(vertices, &shape);
 
         // create the cell faces
         for interface in interfaces.iter() {
             let face_id = interface.id();
-            let direction = interface.compute_direction(&centre);
+            let direction = interface.try_compute_direction(&centre).ok_or_else(|| GridError::InvalidMesh(
+                format!("The centre of cell {} is on interface {}", id, face_id)
+            ))?;
             cell_faces.push(CellFace{interface: face_id, direction});
         }
 
         let volume = shape.volume(vertices);
     
-        GridCell {
+        Ok(GridCell {
             vertex_ids,
             interfaces: cell_faces,
             shape,
             volume,
             centre,
             id,
-        }
+        })
     }
     
     /// Access the interfaces surrounding the cell
@@ -163,6 +277,24 @@ impl GridCell {
         &self.centre
     }
 
+    /// Change the id's of the vertices, where the vertex with id `i`
+    /// becomes the vertex with id `new_ids[i]`
+    pub(crate) fn renumber_vertices(&mut self, new_ids: &[usize]) {
+        for vertex_id in self.vertex_ids.iter_mut() {
+            *vertex_id = new_ids[*vertex_id];
+        }
+    }
+
+    /// Swap the direction of the face for an interface which has been flipped
+    pub(crate) fn flip_face(&mut self, interface_id: usize) {
+        for face in self.interfaces.iter_mut().filter(|face| face.interface == interface_id) {
+            face.direction = match face.direction {
+                Direction::Inwards => Direction::Outwards,
+                Direction::Outwards => Direction::Inwards,
+            };
+        }
+    }
+
     pub fn attach_cell_to_interfaces(&self, interfaces: &mut Vec<GridInterface>) {
         for cell_face in self.interfaces.iter() {
             interfaces[cell_face.interface()].attach_cell(self);
@@ -310,4 +442,56 @@ mod tests {
         
         assert_eq!(cell.centre(), &Vector3{x: 0.5, y: 0.5, z: 0.0});
     }
+
+    #[test]
+    fn regular_hexagon() {
+        let vertices: Vec<GridVertex> = (0 .. 6)
+            .map(|i| {
+                let angle = i as Real * std::f64::consts::PI / 3.0;
+                GridVertex::new(Vector3{x: angle.cos(), y: angle.sin(), z: 0.0}, i)
+            })
+            .collect();
+        let vertex_refs: Vec<&GridVertex> = vertices.iter().collect();
+        let interfaces: Vec<GridInterface> = (0 .. 6)
+            .map(|i| GridInterface::new_from_vertices(&[&vertices[i], &vertices[(i + 1) % 6]], i))
+            .collect();
+        let interface_refs: Vec<&GridInterface> = interfaces.iter().collect();
+        let cell = GridCell::new(&interface_refs, &vertex_refs, 0);
+
+        assert_eq!(cell.shape(), &CellShape::Polygon(6));
+        assert_eq!(cell.shape().dimensions(), 2);
+        assert!((cell.volume() - 1.5 * Real::sqrt(3.0)).abs() < 1e-14);
+        assert!(cell.centre().length() < 1e-15);
+        assert_eq!(cell.interface_ids(), vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn polygon_interfaces() {
+        let shape = CellShape::from_number_of_vertices(5);
+
+        assert_eq!(shape, CellShape::Polygon(5));
+        assert_eq!(shape.interfaces(&[4, 7, 1, 2, 9]), vec![
+            vec![4, 7], vec![7, 1], vec![1, 2], vec![2, 9], vec![9, 4],
+        ]);
+        assert_eq!(shape.to_su2_element_type(), 7);
+    }
+
+    #[test]
+    fn gmsh_element_types() {
+        let expected = [
+            (2, CellShape::Triangle),
+            (3, CellShape::Quadrilateral),
+            (4, CellShape::Tetrahedron),
+            (5, CellShape::Hexahedron),
+            (6, CellShape::Prism),
+            (7, CellShape::Pyramid),
+        ];
+        for (gmsh_type, shape) in expected {
+            assert_eq!(CellShape::from_gmsh_element_type(gmsh_type), shape);
+            let su2_type = shape.to_su2_element_type();
+            assert_eq!(CellShape::from_su2_element_type(su2_type), shape);
+        }
+        assert_eq!(CellShape::try_from_gmsh_element_type(1), None);
+        assert_eq!(CellShape::try_from_gmsh_element_type(9), None);
+    }
 }
@@ -0,0 +1,33 @@
+use thiserror::Error;
+
+use crate::block::UnknownFileType;
+
+/// Errors that can occur reading or writing grids
+#[derive(Debug, Error)]
+pub enum GridError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    UnknownFileType(#[from] UnknownFileType),
+
+    #[error("Failed to parse line {line_number} '{line}': {reason}")]
+    ParseError { line_number: usize, line: String, reason: String },
+
+    #[error("Invalid mesh: {0}")]
+    InvalidMesh(String),
+
+    #[error("There is already a block with id {0}")]
+    DuplicateBlockId(usize),
+}
+
+impl GridError {
+    /// A problem with line `line_number` (counting from one) of a file
+    pub fn parse_error(line_number: usize, line: &str, reason: impl ToString) -> GridError {
+        GridError::ParseError {
+            line_number,
+            line: line.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
@@ -1,10 +1,18 @@
 use common::number::Real;
 use common::vector3::Vector3;
+use crate::cell::CellShape;
 use crate::vertex::GridVertex;
 use crate::Vertex;
 
 /// Compute the area of a triangle with given vertices
 pub fn triangle_area(vertices: &[&GridVertex]) -> Real  {
+    triangle_signed_area(vertices).abs()
+}
+
+/// Compute the area of a triangle with given vertices in the x-y plane,
+/// which is positive if the vertices are ordered counter-clockwise,
+/// and negative if they are ordered clockwise
+pub fn triangle_signed_area(vertices: &[&GridVertex]) -> Real  {
     debug_assert!(vertices.len() == 3, "Expected 3 points in triangle");
 
     let a = vertices[0].pos();
@@ -12,11 +20,18 @@ pub fn triangle_area(vertices: &[&GridVertex]) -> Real  {
     let c = vertices[2].pos();
     let tmp = a.x * (b.y - c.y) + b.x*(c.y - a.y) + c.x*(a.y - b.y);
 
-    0.5 * tmp.abs()
+    0.5 * tmp
 }
 
 /// Compute the area of a quadrilateral with given vertices
 pub fn quad_area(vertices: &[&GridVertex]) -> Real {
+    quad_signed_area(vertices).abs()
+}
+
+/// Compute the area of a quadrilateral with given vertices in the x-y plane,
+/// which is positive if the vertices are ordered counter-clockwise,
+/// and negative if they are ordered clockwise
+pub fn quad_signed_area(vertices: &[&GridVertex]) -> Real {
     assert!(vertices.len() == 4, "Expected 4 points in quadralateral");
 
     // use the shoelace formula applied to a quad
@@ -28,7 +43,7 @@ pub fn quad_area(vertices: &[&GridVertex]) -> Real {
     let tmp_plus = a.x*b.y + b.x*c.y + c.x*d.y + d.x*a.y;
     let tmp_minus = b.x*a.y + c.x*b.y + d.x*c.y + a.x*d.y;
 
-    0.5 * (tmp_plus - tmp_minus).abs()
+    0.5 * (tmp_plus - tmp_minus)
 }
 
 pub fn compute_centre_of_vertices(vertices: &[&GridVertex]) -> Vector3 {
@@ -39,3 +54,225 @@ pub fn compute_centre_of_vertices(vertices: &[&GridVertex]) -> Vector3 {
     centre.scale_in_place(1./vertices.len() as Real);
     centre
 }
+
+/// Compute the volume of a tetrahedron with given vertices, from the
+/// determinant of the edge vectors from the first vertex
+pub fn tetrahedron_volume(vertices: &[&GridVertex]) -> Real {
+    assert!(vertices.len() == 4, "Expected 4 points in tetrahedron");
+
+    let a = vertices[0].vector_to(vertices[1]);
+    let b = vertices[0].vector_to(vertices[2]);
+    let c = vertices[0].vector_to(vertices[3]);
+
+    a.dot(&b.cross(&c)).abs() / 6.0
+}
+
+/// Compute the volume of a polyhedron with the given vertices and faces,
+/// where each face is a list of indices into `vertices`. The polyhedron is
+/// split into tetrahedra from the average of its vertices to each triangle
+/// of each face, so it must be star shaped about that point.
+pub fn polyhedron_volume(vertices: &[&GridVertex], faces: &[Vec<usize>]) -> Real {
+    let centre = GridVertex::new(compute_centre_of_vertices(vertices), usize::MAX);
+    let mut volume = 0.0;
+    for face in faces.iter() {
+        for edge in face[1..].windows(2) {
+            let tetrahedron = [&centre, vertices[face[0]], vertices[edge[0]], vertices[edge[1]]];
+            volume += tetrahedron_volume(&tetrahedron);
+        }
+    }
+    volume
+}
+
+/// Compute the area of a planar polygon with given vertices. In two
+/// dimensions this is the shoelace formula; in three dimensions it is half
+/// the magnitude of the sum of the cross products of consecutive vertices.
+pub fn polygon_area(vertices: &[&GridVertex]) -> Real {
+    assert!(vertices.len() >= 3, "Expected at least 3 points in polygon");
+
+    let n = vertices.len();
+    if vertices.iter().all(|vertex| vertex.pos().z == 0.0) {
+        return polygon_signed_area(vertices).abs();
+    }
+
+    let mut sum = Vector3{x: 0.0, y: 0.0, z: 0.0};
+    for i in 0 .. n {
+        sum += vertices[i].pos().cross(vertices[(i + 1) % n].pos());
+    }
+    0.5 * sum.length()
+}
+
+/// Compute the area of a polygon with given vertices in the x-y plane, with the
+/// shoelace formula. This is positive if the vertices are ordered
+/// counter-clockwise, and negative if they are ordered clockwise.
+pub fn polygon_signed_area(vertices: &[&GridVertex]) -> Real {
+    let n = vertices.len();
+    let mut tmp = 0.0;
+    for i in 0 .. n {
+        let a = vertices[i].pos();
+        let b = vertices[(i + 1) % n].pos();
+        tmp += a.x * b.y - b.x * a.y;
+    }
+    0.5 * tmp
+}
+
+/// Compute the centroid of a planar polygon with given vertices, by
+/// splitting it into triangles from the first vertex and averaging the
+/// centres of the triangles weighted by their area. Unlike
+/// `compute_centre_of_vertices`, this is the centre of area of the polygon.
+pub fn polygon_centroid(vertices: &[&GridVertex]) -> Vector3 {
+    assert!(vertices.len() >= 3, "Expected at least 3 points in polygon");
+
+    let origin = vertices[0].pos();
+    let mut centroid = Vector3{x: 0.0, y: 0.0, z: 0.0};
+    let mut total_area = 0.0;
+    for edge in vertices[1..].windows(2) {
+        let (b, c) = (edge[0].pos(), edge[1].pos());
+        let area = 0.5 * (b - origin).cross(&(c - origin)).length();
+        let mut triangle_centre = &(origin + b) + c;
+        triangle_centre.scale_in_place(area / 3.0);
+        centroid += triangle_centre;
+        total_area += area;
+    }
+    if total_area == 0.0 {
+        // degenerate polygon, so the area weighting is meaningless
+        return compute_centre_of_vertices(vertices);
+    }
+    centroid.scale_in_place(1.0 / total_area);
+    centroid
+}
+
+/// Compute the centre of a cell with the given vertices. Quadrilaterals
+/// and polygons are split into triangles, and the centres of the triangles
+/// are averaged weighted by their area, which is the centroid of the cell
+/// even when it is skewed. The other shapes use the average of the
+/// vertices, which is exact for triangles.
+pub fn compute_cell_centroid_weighted(vertices: &[&GridVertex], shape: &CellShape) -> Vector3 {
+    match shape {
+        CellShape::Quadrilateral | CellShape::Polygon(_) => polygon_centroid(vertices),
+        _ => compute_centre_of_vertices(vertices),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertices(positions: &[[Real; 3]]) -> Vec<GridVertex> {
+        positions.iter()
+            .enumerate()
+            .map(|(id, pos)| GridVertex::new(Vector3{x: pos[0], y: pos[1], z: pos[2]}, id))
+            .collect()
+    }
+
+    #[test]
+    fn unit_tetrahedron_volume() {
+        let vertices = vertices(&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+        let vertices: Vec<&GridVertex> = vertices.iter().collect();
+
+        assert!((tetrahedron_volume(&vertices) - 1.0 / 6.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn unit_cube_volume() {
+        let vertices = vertices(&[
+            [0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [1.0, 1.0, 1.0], [0.0, 1.0, 1.0],
+        ]);
+        let vertices: Vec<&GridVertex> = vertices.iter().collect();
+        let faces = vec![
+            vec![0, 3, 2, 1], vec![4, 5, 6, 7],
+            vec![0, 1, 5, 4], vec![1, 2, 6, 5], vec![2, 3, 7, 6], vec![3, 0, 4, 7],
+        ];
+
+        assert!((polyhedron_volume(&vertices, &faces) - 1.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn unit_square_area() {
+        let vertices = vertices(&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]);
+        let vertices: Vec<&GridVertex> = vertices.iter().collect();
+
+        assert_eq!(polygon_area(&vertices), 1.0);
+        assert_eq!(polygon_area(&vertices), quad_area(&vertices));
+    }
+
+    #[test]
+    fn unit_square_area_3d() {
+        // the unit square in the x-z plane, offset in y
+        let vertices = vertices(&[[0.0, 2.0, 0.0], [1.0, 2.0, 0.0], [1.0, 2.0, 1.0], [0.0, 2.0, 1.0]]);
+        let vertices: Vec<&GridVertex> = vertices.iter().collect();
+
+        assert!((polygon_area(&vertices) - 1.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn triangle_area_matches() {
+        let vertices = vertices(&[[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.5, 1.5, 0.0]]);
+        let vertices: Vec<&GridVertex> = vertices.iter().collect();
+
+        assert_eq!(polygon_area(&vertices), triangle_area(&vertices));
+    }
+
+    #[test]
+    fn signed_area_winding() {
+        let square = vertices(&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]);
+        let counter_clockwise: Vec<&GridVertex> = square.iter().collect();
+        let clockwise: Vec<&GridVertex> = square.iter().rev().collect();
+
+        assert_eq!(quad_signed_area(&counter_clockwise), 1.0);
+        assert_eq!(quad_signed_area(&clockwise), -1.0);
+        assert_eq!(triangle_signed_area(&counter_clockwise[..3]), 0.5);
+        assert_eq!(triangle_signed_area(&clockwise[..3]), -0.5);
+    }
+
+    #[test]
+    fn unit_square_centroid() {
+        let vertices = vertices(&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]);
+        let vertices: Vec<&GridVertex> = vertices.iter().collect();
+
+        assert_eq!(polygon_centroid(&vertices), Vector3{x: 0.5, y: 0.5, z: 0.0});
+    }
+
+    #[test]
+    fn triangle_centroid() {
+        let vertices = vertices(&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.5, 1.0, 0.0]]);
+        let vertices: Vec<&GridVertex> = vertices.iter().collect();
+
+        assert_eq!(polygon_centroid(&vertices), Vector3{x: 0.5, y: 1.0 / 3.0, z: 0.0});
+    }
+
+    #[test]
+    fn trapezium_centroid() {
+        // the centroid of a trapezium isn't the average of its vertices
+        let vertices = vertices(&[[0.0, 0.0, 0.0], [3.0, 0.0, 0.0], [2.0, 1.0, 0.0], [1.0, 1.0, 0.0]]);
+        let vertices: Vec<&GridVertex> = vertices.iter().collect();
+        let centroid = polygon_centroid(&vertices);
+
+        assert!((centroid.x - 1.5).abs() < 1e-14);
+        assert!((centroid.y - 5.0 / 12.0).abs() < 1e-14);
+    }
+
+    #[test]
+    fn skewed_quad_centroid() {
+        let vertices = vertices(&[[0.0, 0.0, 0.0], [4.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]);
+        let vertices: Vec<&GridVertex> = vertices.iter().collect();
+        let centroid = compute_cell_centroid_weighted(&vertices, &CellShape::Quadrilateral);
+
+        // the triangles (0, 1, 2) and (0, 2, 3) have areas 2 and 0.5
+        let expected = Vector3{x: (2.0 * 5.0 / 3.0 + 0.5 * 1.0 / 3.0) / 2.5, y: (2.0 / 3.0 + 0.5 * 2.0 / 3.0) / 2.5, z: 0.0};
+        assert!((centroid.x - expected.x).abs() < 1e-14);
+        assert!((centroid.y - expected.y).abs() < 1e-14);
+        assert_ne!(centroid, compute_centre_of_vertices(&vertices));
+    }
+
+    #[test]
+    fn triangle_centroid_weighted() {
+        let vertices = vertices(&[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.5, 1.0, 0.0]]);
+        let vertices: Vec<&GridVertex> = vertices.iter().collect();
+
+        assert_eq!(
+            compute_cell_centroid_weighted(&vertices, &CellShape::Triangle),
+            compute_centre_of_vertices(&vertices)
+        );
+    }
+}
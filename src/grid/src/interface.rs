@@ -1,16 +1,19 @@
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
 use crate::cell::GridCell;
+use crate::geom_calc::polygon_area;
 use crate::vertex::GridVertex;
 use common::vector3::Vector3;
 use common::number::Real;
-use crate::geom_calc::compute_centre_of_vertices;
-use crate::{Interface, Id};
+use crate::{Interface, Id, Vertex};
 
 /// Allowable interface shapes
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum InterfaceShape {
     Line,
+    Triangle,
+    Quadrilateral,
 }
 
 impl InterfaceShape {
@@ -19,6 +22,8 @@ impl InterfaceShape {
         match n_vertices {
             0 | 1 => panic!("Not enough vertices to form an interface: {n_vertices}"),
             2 => InterfaceShape::Line,
+            3 => InterfaceShape::Triangle,
+            4 => InterfaceShape::Quadrilateral,
             _ => panic!("Unsupported number of vertices in interface: {n_vertices}"),
         }
     }
@@ -28,19 +33,34 @@ impl InterfaceShape {
         match &self {
             InterfaceShape::Line => vertices[0].vector_to(vertices[1])
                                                .length(),
+            InterfaceShape::Triangle | InterfaceShape::Quadrilateral => polygon_area(vertices),
         }
     }
 
     pub fn from_su2_element_type(elem_type: usize) -> InterfaceShape {
         match elem_type {
             3 => InterfaceShape::Line,
+            5 => InterfaceShape::Triangle,
+            9 => InterfaceShape::Quadrilateral,
             _ => panic!("Invalid or unsupported su2 interface shape"),
         }
     }
 
+    /// Convert the Gmsh element type of a boundary element to interface shape
+    pub fn from_gmsh_element_type(elem_type: usize) -> InterfaceShape {
+        match elem_type {
+            1 => InterfaceShape::Line,
+            2 => InterfaceShape::Triangle,
+            3 => InterfaceShape::Quadrilateral,
+            _ => panic!("Invalid or unsupported gmsh interface shape"),
+        }
+    }
+
     pub fn to_su2_element_type(&self) -> usize {
         match &self {
             InterfaceShape::Line => 3,
+            InterfaceShape::Triangle => 5,
+            InterfaceShape::Quadrilateral => 9,
         }
     }
 }
@@ -52,15 +72,17 @@ pub enum Direction {
     Inwards, Outwards,
 }
 
-/// A geometric interface
+/// A geometric interface. The geometric properties are computed
+/// from the vertex positions the first time they are needed.
 #[derive(Debug, Clone)]
 pub struct GridInterface {
     vertex_ids: Vec<usize>,
-    area: Real,
-    n: Vector3,
-    t1: Vector3,
-    t2: Vector3,
-    centre: Vector3,
+    vertex_positions: Vec<Vector3>,
+    area: OnceLock<Real>,
+    n: OnceLock<Vector3>,
+    t1: OnceLock<Vector3>,
+    t2: OnceLock<Vector3>,
+    centre: OnceLock<Vector3>,
     shape: InterfaceShape,
     left_cell: Option<usize>,
     right_cell: Option<usize>,
@@ -75,63 +97,94 @@ impl GridInterface {
     ///
     /// * `id`: The id of the interface
     pub fn new_from_vertices(vertices: &[&GridVertex], id: usize) -> GridInterface {
-        let t1: Vector3;
-        let t2: Vector3;
-        let n: Vector3;
-        let area: Real;
-
-        // get the id's of the vertices
-        let mut vertex_ids = Vec::with_capacity(vertices.len());
-        for vertex in vertices.iter() {
-            vertex_ids.push(vertex.id());
-        }
+        // get the id's and positions of the vertices
+        let vertex_ids: Vec<usize> = vertices.iter().map(|vertex| vertex.id()).collect();
+        let vertex_positions = vertices.iter().map(|vertex| *vertex.pos()).collect();
 
-        // shape specific setup
         let shape = InterfaceShape::from_number_of_vertices(vertex_ids.len() as u8);
-        match shape {
-            InterfaceShape::Line => {
-                let v0v1 = vertices[0].vector_to(vertices[1]);
-                t1 = v0v1.normalised();
-                t2 = Vector3{x: 0.0, y: 0.0, z: 1.0};
-                n = t1.cross(&t2).normalised();
-                area = v0v1.length(); // per unit depth
-            }
-        }
 
         // the cells attached to this interface are none by default
         let left_cell = None;
         let right_cell = None;
 
-        // compute geometric centre of the cell
-        let centre = compute_centre_of_vertices(vertices);
-
-        GridInterface{vertex_ids, area, n, t1, t2, shape, left_cell, right_cell, centre, id}
+        GridInterface{
+            vertex_ids,
+            vertex_positions,
+            area: OnceLock::new(),
+            n: OnceLock::new(),
+            t1: OnceLock::new(),
+            t2: OnceLock::new(),
+            centre: OnceLock::new(),
+            shape,
+            left_cell,
+            right_cell,
+            id,
+        }
     }
 
     /// Access the area of the interface
     pub fn area(&self) -> Real {
-        self.area
+        *self.area.get_or_init(|| match self.shape {
+            // per unit depth
+            InterfaceShape::Line => (&self.vertex_positions[1] - &self.vertex_positions[0]).length(),
+            InterfaceShape::Triangle | InterfaceShape::Quadrilateral => self.area_vector().length(),
+        })
+    }
+
+    /// The normal of a polygonal interface, scaled by its area: half
+    /// the sum of the cross products of consecutive vertices
+    fn area_vector(&self) -> Vector3 {
+        let n = self.vertex_positions.len();
+        let mut area_vector = Vector3{x: 0.0, y: 0.0, z: 0.0};
+        for i in 0 .. n {
+            area_vector += self.vertex_positions[i].cross(&self.vertex_positions[(i + 1) % n]);
+        }
+        area_vector.scale_in_place(0.5);
+        area_vector
     }
 
     /// Access the interface normal
     pub fn norm(&self) -> Vector3 {
-        self.n
+        *self.n.get_or_init(|| match self.shape {
+            InterfaceShape::Line => self.t1().cross(&self.t2()).normalised(),
+            InterfaceShape::Triangle | InterfaceShape::Quadrilateral => self.area_vector().normalised(),
+        })
     }
 
     /// Access the first interface tangent
     pub fn t1(&self) -> Vector3 {
-        self.t1
+        *self.t1.get_or_init(|| match self.shape {
+            InterfaceShape::Line | InterfaceShape::Triangle | InterfaceShape::Quadrilateral => {
+                (&self.vertex_positions[1] - &self.vertex_positions[0]).normalised()
+            }
+        })
     }
 
     /// Access the second interface tangent
     pub fn t2(&self) -> Vector3 {
-        self.t2
+        *self.t2.get_or_init(|| match self.shape {
+            InterfaceShape::Line => Vector3{x: 0.0, y: 0.0, z: 1.0},
+            InterfaceShape::Triangle | InterfaceShape::Quadrilateral => self.norm().cross(&self.t1()),
+        })
+    }
+
+    /// Access the centre of the interface
+    pub fn centre(&self) -> Vector3 {
+        *self.centre.get_or_init(|| {
+            let mut centre = Vector3{x: 0.0, y: 0.0, z: 0.0};
+            for pos in self.vertex_positions.iter() {
+                centre += *pos;
+            }
+            centre.scale_in_place(1./self.vertex_positions.len() as Real);
+            centre
+        })
     }
 
     /// The dimensionality of the interface
     pub fn dimensions(&self) -> u8 {
         match &self.shape {
             InterfaceShape::Line => 2,
+            InterfaceShape::Triangle | InterfaceShape::Quadrilateral => 3,
         }
     }
 
@@ -145,24 +198,79 @@ impl GridInterface {
         }
     }
 
+    /// Change the id's of the vertices, where the vertex with id `i`
+    /// becomes the vertex with id `new_ids[i]`
+    pub(crate) fn renumber_vertices(&mut self, new_ids: &[usize]) {
+        for vertex_id in self.vertex_ids.iter_mut() {
+            *vertex_id = new_ids[*vertex_id];
+        }
+    }
+
+    /// Reverse the order of the vertices, which negates the normal, and
+    /// swap the cells on either side to match. The tangents are recomputed
+    /// from the new order of the vertices, so they still form a right
+    /// handed frame with the normal.
+    pub fn flip_normal(&mut self) {
+        self.vertex_ids.reverse();
+        self.vertex_positions.reverse();
+        self.n = OnceLock::new();
+        self.t1 = OnceLock::new();
+        self.t2 = OnceLock::new();
+        std::mem::swap(&mut self.left_cell, &mut self.right_cell);
+    }
+
+    /// Update the positions of the vertices from `vertices`, indexed by
+    /// vertex id, e.g. after the grid has been transformed. The area,
+    /// normal, tangents and centre are recomputed when next needed.
+    pub fn recompute_geometry(&mut self, vertices: &[GridVertex]) {
+        self.vertex_positions = self.vertex_ids.iter().map(|id| *vertices[*id].pos()).collect();
+        self.area = OnceLock::new();
+        self.n = OnceLock::new();
+        self.t1 = OnceLock::new();
+        self.t2 = OnceLock::new();
+        self.centre = OnceLock::new();
+    }
+
+    /// The cell the normal points into, if one is attached
+    pub fn left_cell(&self) -> Option<usize> {
+        self.left_cell
+    }
+
+    /// The cell the normal points out of, if one is attached
+    pub fn right_cell(&self) -> Option<usize> {
+        self.right_cell
+    }
+
+    /// The number of cells attached to the interface
+    pub(crate) fn number_of_attached_cells(&self) -> usize {
+        self.left_cell.is_some() as usize + self.right_cell.is_some() as usize
+    }
+
     /// Compute if an interface is pointing towards or away from
     /// a point in space
     pub fn compute_direction(&self, point: &Vector3) -> Direction {
+        self.try_compute_direction(point)
+            .expect("The point is on the interface")
+    }
+
+    /// Compute if an interface is pointing towards or away from
+    /// a point in space, or `None` if the point is on the interface
+    pub fn try_compute_direction(&self, point: &Vector3) -> Option<Direction> {
         // vector from centre of interface to the point
-        let dir = point - &self.centre;
+        let dir = point - &self.centre();
 
         // the sign of the dot product of dir with the interface
         // normal vector will tell us if the vectors are pointing
         // in the same direction or not
-        let dot = dir.dot(&self.n);
+        let dot = dir.dot(&self.norm());
 
         if dot.abs() < 1e-14 {
-            panic!("The point is on the interface");
+            return None;
         }
 
         match dot > 0.0 {
-            true => Direction::Inwards,
-            false => Direction::Outwards,
+            true => Some(Direction::Inwards),
+            false => Some(Direction::Outwards),
         }
     }
 
@@ -227,11 +335,11 @@ impl PartialEq for GridInterface {
     fn eq(&self, other: &GridInterface) -> bool {
         let id = self.id == other.id;
         let vertex_ids = self.vertex_ids == other.vertex_ids;
-        let area = (self.area - other.area).abs() < 1e-14;
-        let n = self.n == other.n;
-        let t1 = self.t1 == other.t1;
-        let t2 = self.t2 == other.t2;
-        let centre = self.centre == other.centre;
+        let area = (self.area() - other.area()).abs() < 1e-14;
+        let n = self.norm() == other.norm();
+        let t1 = self.t1() == other.t1();
+        let t2 = self.t2() == other.t2();
+        let centre = self.centre() == other.centre();
         let shape = self.shape == other.shape;
         let left_cell = self.left_cell == other.left_cell;
         let right_cell = self.right_cell == other.right_cell;
@@ -258,15 +366,47 @@ impl Ord for GridInterface {
 /// each one is unique
 #[derive(Debug)]
 pub struct InterfaceCollection {
-    interfaces: HashMap<usize, GridInterface>,
-    id_to_hash: HashMap<usize, usize>,
+    storage: InterfaceStorage,
+}
+
+/// How the interfaces in a collection are stored
+#[derive(Debug)]
+enum InterfaceStorage {
+    /// Interfaces stored by the hash of their vertices
+    Map {
+        interfaces: HashMap<usize, GridInterface>,
+        id_to_hash: HashMap<usize, usize>,
+    },
+
+    /// Interfaces stored contiguously, in order of their id's. Since the
+    /// id of each interface is the order it was added in, the id is also
+    /// the index into `interfaces`. This avoids storing each interface in
+    /// a hash map, which matters for meshes with millions of interfaces.
+    #[cfg(feature = "flat_interfaces")]
+    Flat {
+        interfaces: Vec<GridInterface>,
+        hash_to_id: HashMap<usize, usize>,
+    },
 }
 
 impl InterfaceCollection {
     pub fn with_capacity(capacity: usize) -> InterfaceCollection {
         InterfaceCollection { 
-            interfaces: HashMap::with_capacity(capacity),
-            id_to_hash: HashMap::with_capacity(capacity)
+            storage: InterfaceStorage::Map {
+                interfaces: HashMap::with_capacity(capacity),
+                id_to_hash: HashMap::with_capacity(capacity),
+            }
+        }
+    }
+
+    /// Store the interfaces in a flat vector, rather than a hash map
+    #[cfg(feature = "flat_interfaces")]
+    pub fn new_flat(capacity: usize) -> InterfaceCollection {
+        InterfaceCollection {
+            storage: InterfaceStorage::Flat {
+                interfaces: Vec::with_capacity(capacity),
+                hash_to_id: HashMap::with_capacity(capacity),
+            }
         }
     }
 
@@ -275,51 +415,202 @@ impl InterfaceCollection {
     pub fn add_or_retrieve(&mut self, vertices: &[&GridVertex]) -> usize {
         let vertex_ids: Vec<usize> = vertices.iter().map(|vertex| vertex.id()).collect();
         let hash = hash(&vertex_ids);
-        if !self.interfaces.contains_key(&hash) {
-            let interface = GridInterface::new_from_vertices(vertices, self.interfaces.len());
-            self.id_to_hash.insert(interface.id(), hash);
-            self.interfaces.insert(hash, interface);
+        match &mut self.storage {
+            InterfaceStorage::Map { interfaces, id_to_hash } => {
+                if !interfaces.contains_key(&hash) {
+                    let interface = GridInterface::new_from_vertices(vertices, interfaces.len());
+                    id_to_hash.insert(interface.id(), hash);
+                    interfaces.insert(hash, interface);
+                }
+                check_for_collision(&interfaces[&hash], &vertex_ids);
+                interfaces[&hash].id()
+            }
+            #[cfg(feature = "flat_interfaces")]
+            InterfaceStorage::Flat { interfaces, hash_to_id } => {
+                let id = *hash_to_id.entry(hash).or_insert_with(|| {
+                    let id = interfaces.len();
+                    interfaces.push(GridInterface::new_from_vertices(vertices, id));
+                    id
+                });
+                check_for_collision(&interfaces[id], &vertex_ids);
+                id
+            }
         }
-        self.interfaces[&hash].id()
     }
 
     pub fn find_interface(&self, vertices: &[&GridVertex]) -> usize {
         let vertex_ids: Vec<usize> = vertices.iter().map(|vertex| vertex.id()).collect();
         let hash = hash(&vertex_ids);
-        self.interfaces[&hash].id() 
+        let interface = match &self.storage {
+            InterfaceStorage::Map { interfaces, .. } => &interfaces[&hash],
+            #[cfg(feature = "flat_interfaces")]
+            InterfaceStorage::Flat { interfaces, hash_to_id } => &interfaces[hash_to_id[&hash]],
+        };
+        check_for_collision(interface, &vertex_ids);
+        interface.id()
     }
 
     pub fn interface_with_id(&self, id: usize) -> &GridInterface {
-        let hash = self.id_to_hash[&id];
-        &self.interfaces[&hash]
+        match &self.storage {
+            InterfaceStorage::Map { interfaces, id_to_hash } => &interfaces[&id_to_hash[&id]],
+            #[cfg(feature = "flat_interfaces")]
+            InterfaceStorage::Flat { interfaces, .. } => &interfaces[id],
+        }
+    }
+
+    /// The number of unique interfaces in the collection
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            InterfaceStorage::Map { interfaces, .. } => interfaces.len(),
+            #[cfg(feature = "flat_interfaces")]
+            InterfaceStorage::Flat { interfaces, .. } => interfaces.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check if an interface with the given vertices is in the
+    /// collection, without adding it
+    pub fn contains_vertices(&self, vertex_ids: &[usize]) -> bool {
+        let hash = hash(vertex_ids);
+        let interface = match &self.storage {
+            InterfaceStorage::Map { interfaces, .. } => interfaces.get(&hash),
+            #[cfg(feature = "flat_interfaces")]
+            InterfaceStorage::Flat { interfaces, hash_to_id } => {
+                hash_to_id.get(&hash).map(|id| &interfaces[*id])
+            }
+        };
+        interface.is_some_and(|interface| has_vertex_ids(interface, vertex_ids))
+    }
+
+    /// Iterate over the interfaces in order of their id
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { collection: self, id: 0 }
     }
 
     /// return the interfaces as owned values
     pub fn interfaces(&self) -> Vec<GridInterface> {
-        let mut ifaces: Vec<GridInterface> = self.interfaces.values().cloned().collect();
-        ifaces.sort();
-        ifaces
+        match &self.storage {
+            InterfaceStorage::Map { interfaces, .. } => {
+                let mut ifaces: Vec<GridInterface> = interfaces.values().cloned().collect();
+                ifaces.sort();
+                ifaces
+            }
+            // already in order of id
+            #[cfg(feature = "flat_interfaces")]
+            InterfaceStorage::Flat { interfaces, .. } => interfaces.clone(),
+        }
+    }
+
+    /// The interfaces in order of id, without copying them
+    pub fn into_interfaces(self) -> Vec<GridInterface> {
+        match self.storage {
+            InterfaceStorage::Map { interfaces, .. } => {
+                let mut ifaces: Vec<GridInterface> = interfaces.into_values().collect();
+                ifaces.sort();
+                ifaces
+            }
+            #[cfg(feature = "flat_interfaces")]
+            InterfaceStorage::Flat { interfaces, .. } => interfaces,
+        }
+    }
+
+    /// The total area of the interfaces on the boundary `tag`, which
+    /// is the length per unit depth in 2D. A boundary which isn't in
+    /// `boundaries` has no length.
+    pub fn total_length_of_tag(&self, tag: &str, boundaries: &HashMap<String, Vec<usize>>) -> Real {
+        boundaries
+            .get(tag)
+            .map_or(0.0, |ids| ids.iter().map(|id| self.interface_with_id(*id).area()).sum())
     }
 
     /// attach a cell to the interface with given id
     pub fn attach_cell_to_interface(&mut self, cell: &GridCell, id: usize) {
-        let hash = self.id_to_hash[&id];
-        self.interfaces.get_mut(&hash).unwrap().attach_cell(cell);
+        match &mut self.storage {
+            InterfaceStorage::Map { interfaces, id_to_hash } => {
+                let hash = id_to_hash[&id];
+                interfaces.get_mut(&hash).unwrap().attach_cell(cell);
+            }
+            #[cfg(feature = "flat_interfaces")]
+            InterfaceStorage::Flat { interfaces, .. } => interfaces[id].attach_cell(cell),
+        }
+    }
+}
+
+/// Iterator over the interfaces in an [`InterfaceCollection`], in order of id
+pub struct Iter<'a> {
+    collection: &'a InterfaceCollection,
+    id: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a GridInterface;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the id's are assigned in the order the interfaces were added,
+        // so they are contiguous from zero
+        if self.id >= self.collection.len() {
+            return None;
+        }
+        let interface = self.collection.interface_with_id(self.id);
+        self.id += 1;
+        Some(interface)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.collection.len() - self.id;
+        (remaining, Some(remaining))
     }
 }
 
+impl ExactSizeIterator for Iter<'_> {}
+
+impl<'a> IntoIterator for &'a InterfaceCollection {
+    type Item = &'a GridInterface;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Prime used as the base of the polynomial in [`hash`]
+const HASH_PRIME: usize = 1_000_000_007;
+
+/// Hash the vertex id's of an interface, independent of their order.
+///
+/// The id's are sorted, then combined as the polynomial
+/// `(id_0 + 1) * P^(n-1) + (id_1 + 1) * P^(n-2) + ... + (id_(n-1) + 1)`
+/// with wrapping arithmetic, where `P` is [`HASH_PRIME`]. Each id is offset
+/// by one so that vertex 0 still contributes to the hash. Concatenating the
+/// id's as strings made {10, 11} and {0, 111} hash to the same value, and
+/// overflowed for large id's. Collisions are still possible, but they are
+/// detected by [`check_for_collision`] rather than silently merging two
+/// interfaces.
 fn hash(vertex_ids: &[usize]) -> usize {
-    // the idea is to sort the vertex id's from highest to lowest
-    // then concatenate them together to make one large integer
     let mut id_vec = vertex_ids.to_vec();
-    id_vec.sort();
-    id_vec.reverse();
+    id_vec.sort_unstable();
+    id_vec.iter().fold(0, |hash: usize, id| {
+        hash.wrapping_mul(HASH_PRIME).wrapping_add(id + 1)
+    })
+}
+
+/// Whether `interface` has exactly the vertices in `vertex_ids`
+fn has_vertex_ids(interface: &GridInterface, vertex_ids: &[usize]) -> bool {
+    interface.vertex_ids().len() == vertex_ids.len() && interface.equal_to_vertex_ids(vertex_ids)
+}
 
-    let mut hash = "".to_string();
-    for id in id_vec {
-        hash += &id.to_string();
+/// Panic if the interface found from the hash of `vertex_ids` has
+/// different vertices, since two interfaces have the same hash
+fn check_for_collision(interface: &GridInterface, vertex_ids: &[usize]) {
+    if !has_vertex_ids(interface, vertex_ids) {
+        panic!(
+            "Interface hash collision between vertices {:?} and {:?}",
+            interface.vertex_ids(), vertex_ids
+        );
     }
-    hash.parse().unwrap()
 }
 
 #[cfg(test)]
@@ -329,9 +620,71 @@ mod tests {
     #[test]
     fn hash_test() {
         let vertices = &[0, 1, 11];
-        let vertices_hash = hash(vertices);
 
-        assert_eq!(vertices_hash, 1110);
+        assert_eq!(hash(vertices), hash(&[11, 0, 1]));
+        assert_ne!(hash(vertices), hash(&[0, 1]));
+    }
+
+    #[test]
+    fn hash_no_string_collision() {
+        // concatenating the sorted id's gives "1110" for both of these
+        assert_ne!(hash(&[10, 11]), hash(&[0, 111]));
+        assert_ne!(hash(&[1, 1, 1]), hash(&[1, 11]));
+
+        let vertices = [
+            GridVertex::new(Vector3{x: 0.0, y: 0.0, z: 0.0}, 10),
+            GridVertex::new(Vector3{x: 1.0, y: 0.0, z: 0.0}, 11),
+            GridVertex::new(Vector3{x: 0.0, y: 1.0, z: 0.0}, 0),
+            GridVertex::new(Vector3{x: 1.0, y: 1.0, z: 0.0}, 111),
+        ];
+        let mut collection = InterfaceCollection::with_capacity(2);
+        assert_eq!(collection.add_or_retrieve(&[&vertices[0], &vertices[1]]), 0);
+        assert_eq!(collection.add_or_retrieve(&[&vertices[2], &vertices[3]]), 1);
+        assert_eq!(collection.len(), 2);
+    }
+
+    #[test]
+    fn hash_large_ids() {
+        // the string hash overflowed for interfaces with large vertex id's
+        let ids = [usize::MAX - 3, usize::MAX - 2, usize::MAX - 1, 123_456_789];
+
+        assert_eq!(hash(&ids), hash(&[ids[3], ids[2], ids[1], ids[0]]));
+    }
+
+    #[test]
+    fn flip_normal() {
+        let vertices = [
+            GridVertex::new(Vector3{x: 0.0, y: 0.0, z: 0.0}, 0),
+            GridVertex::new(Vector3{x: 1.0, y: 0.0, z: 0.0}, 1),
+            GridVertex::new(Vector3{x: 0.0, y: 1.0, z: 0.0}, 2),
+        ];
+        let mut line = GridInterface::new_from_vertices(&[&vertices[0], &vertices[1]], 0);
+        let mut triangle = GridInterface::new_from_vertices(&[&vertices[0], &vertices[1], &vertices[2]], 1);
+        for interface in [&mut line, &mut triangle] {
+            let norm = interface.norm();
+            interface.flip_normal();
+            let flipped = interface.norm();
+            assert_eq!(flipped, Vector3{x: -norm.x, y: -norm.y, z: -norm.z});
+            assert!(interface.t1().cross(&interface.t2()).dist_to(&flipped) < 1e-14);
+        }
+        assert_eq!(line.vertex_ids(), &vec![1, 0]);
+        assert_eq!(triangle.vertex_ids(), &vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn recompute_geometry() {
+        let mut vertices = vec![
+            GridVertex::new(Vector3{x: 0.0, y: 0.0, z: 0.0}, 0),
+            GridVertex::new(Vector3{x: 1.0, y: 0.0, z: 0.0}, 1),
+        ];
+        let mut interface = GridInterface::new_from_vertices(&[&vertices[0], &vertices[1]], 0);
+        assert_eq!(interface.area(), 1.0);
+
+        vertices[1] = GridVertex::new(Vector3{x: 0.0, y: 2.0, z: 0.0}, 1);
+        interface.recompute_geometry(&vertices);
+        assert_eq!(interface.area(), 2.0);
+        assert_eq!(interface.centre(), Vector3{x: 0.0, y: 1.0, z: 0.0});
+        assert_eq!(interface.norm(), Vector3{x: 1.0, y: 0.0, z: 0.0});
     }
 
     #[test]
@@ -479,6 +832,40 @@ mod tests {
         assert_eq!(interface.dimensions(), 2);
     }
 
+    #[test]
+    fn quad_geometry() {
+        let vertices = [
+            GridVertex::new(Vector3{x: 0.0, y: 0.0, z: 1.0}, 0),
+            GridVertex::new(Vector3{x: 2.0, y: 0.0, z: 1.0}, 1),
+            GridVertex::new(Vector3{x: 2.0, y: 1.0, z: 1.0}, 2),
+            GridVertex::new(Vector3{x: 0.0, y: 1.0, z: 1.0}, 3),
+        ];
+        let interface = GridInterface::new_from_vertices(&[&vertices[0], &vertices[1], &vertices[2], &vertices[3]], 0);
+
+        assert_eq!(interface.shape(), &InterfaceShape::Quadrilateral);
+        assert_eq!(interface.dimensions(), 3);
+        assert_eq!(interface.area(), 2.0);
+        assert_eq!(interface.norm(), Vector3{x: 0.0, y: 0.0, z: 1.0});
+        assert_eq!(interface.t1(), Vector3{x: 1.0, y: 0.0, z: 0.0});
+        assert_eq!(interface.t2(), Vector3{x: 0.0, y: 1.0, z: 0.0});
+        assert_eq!(interface.compute_direction(&Vector3{x: 1.0, y: 0.5, z: 0.0}), Direction::Outwards);
+    }
+
+    #[test]
+    fn triangle_geometry() {
+        let vertices = [
+            GridVertex::new(Vector3{x: 0.0, y: 0.0, z: 0.0}, 0),
+            GridVertex::new(Vector3{x: 0.0, y: 1.0, z: 0.0}, 1),
+            GridVertex::new(Vector3{x: 0.0, y: 0.0, z: 1.0}, 2),
+        ];
+        let interface = GridInterface::new_from_vertices(&[&vertices[0], &vertices[1], &vertices[2]], 0);
+
+        assert_eq!(interface.shape(), &InterfaceShape::Triangle);
+        assert_eq!(interface.area(), 0.5);
+        assert_eq!(interface.norm(), Vector3{x: 1.0, y: 0.0, z: 0.0});
+        assert_eq!(interface.t1().cross(&interface.t2()), interface.norm());
+    }
+
     #[test]
     fn compute_direction_outwards() {
         let vertices = vec![
@@ -513,4 +900,93 @@ mod tests {
 
         assert_eq!(interface.vertex_ids(), &vec![3, 1]);
     }
+
+    fn check_collection(mut collection: InterfaceCollection) {
+        let vertices = [
+            GridVertex::new(Vector3{x: 0.0, y: 0.0, z: 0.0}, 0),
+            GridVertex::new(Vector3{x: 1.0, y: 0.0, z: 0.0}, 1),
+            GridVertex::new(Vector3{x: 1.0, y: 1.0, z: 0.0}, 2),
+        ];
+        assert!(collection.is_empty());
+        assert_eq!(collection.add_or_retrieve(&[&vertices[0], &vertices[1]]), 0);
+        assert_eq!(collection.add_or_retrieve(&[&vertices[1], &vertices[2]]), 1);
+        assert_eq!(collection.add_or_retrieve(&[&vertices[2], &vertices[1]]), 1);
+        assert_eq!(collection.add_or_retrieve(&[&vertices[2], &vertices[0]]), 2);
+
+        assert_eq!(collection.find_interface(&[&vertices[0], &vertices[2]]), 2);
+        assert_eq!(collection.interface_with_id(1).vertex_ids(), &vec![1, 2]);
+        let ids: Vec<usize> = collection.interfaces().iter().map(|iface| iface.id()).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+
+        assert_eq!(collection.len(), 3);
+        assert!(!collection.is_empty());
+        assert!(collection.contains_vertices(&[0, 2]));
+        assert!(!collection.contains_vertices(&[0, 3]));
+        assert_eq!(collection.len(), 3);
+        let ids: Vec<usize> = (&collection).into_iter().map(|iface| iface.id()).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+        assert_eq!(collection.iter().len(), 3);
+    }
+
+    #[test]
+    fn interface_collection() {
+        check_collection(InterfaceCollection::with_capacity(3));
+    }
+
+    #[cfg(feature = "flat_interfaces")]
+    #[test]
+    fn flat_interface_collection() {
+        check_collection(InterfaceCollection::new_flat(3));
+    }
+
+    #[test]
+    fn gmsh_element_types() {
+        let expected = [
+            (1, InterfaceShape::Line),
+            (2, InterfaceShape::Triangle),
+            (3, InterfaceShape::Quadrilateral),
+        ];
+        for (gmsh_type, shape) in expected {
+            assert_eq!(InterfaceShape::from_gmsh_element_type(gmsh_type), shape);
+            let su2_type = shape.to_su2_element_type();
+            assert_eq!(InterfaceShape::from_su2_element_type(su2_type), shape);
+        }
+    }
+
+    #[test]
+    fn total_length_of_tag() {
+        let vertices = [
+            GridVertex::new(Vector3{x: 0.0, y: 0.0, z: 0.0}, 0),
+            GridVertex::new(Vector3{x: 3.0, y: 4.0, z: 0.0}, 1),
+            GridVertex::new(Vector3{x: 3.0, y: 5.0, z: 0.0}, 2),
+        ];
+        let mut collection = InterfaceCollection::with_capacity(2);
+        collection.add_or_retrieve(&[&vertices[0], &vertices[1]]);
+        collection.add_or_retrieve(&[&vertices[1], &vertices[2]]);
+        let boundaries = HashMap::from([
+            ("wall".to_string(), vec![0, 1]),
+            ("inflow".to_string(), vec![1]),
+        ]);
+
+        assert_eq!(collection.total_length_of_tag("wall", &boundaries), 6.0);
+        assert_eq!(collection.total_length_of_tag("inflow", &boundaries), 1.0);
+        assert_eq!(collection.total_length_of_tag("outflow", &boundaries), 0.0);
+    }
+
+    #[test]
+    fn geometry_computed_lazily() {
+        let vertices = [
+            GridVertex::new(Vector3{x: 0.0, y: 0.0, z: 0.0}, 0),
+            GridVertex::new(Vector3{x: 3.0, y: 4.0, z: 0.0}, 1),
+        ];
+        let interface = GridInterface::new_from_vertices(&[&vertices[0], &vertices[1]], 0);
+        assert!(interface.area.get().is_none());
+        assert!(interface.n.get().is_none());
+
+        assert_eq!(interface.norm(), Vector3{x: 0.8, y: -0.6, z: 0.0});
+        assert_eq!(interface.n.get(), Some(&Vector3{x: 0.8, y: -0.6, z: 0.0}));
+        assert!(interface.t1.get().is_some());
+        assert!(interface.area.get().is_none());
+        assert_eq!(interface.area(), 5.0);
+    }
 }
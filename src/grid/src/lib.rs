@@ -16,9 +16,17 @@ pub mod cell;
 /// Hanles unstructured grids
 pub mod block;
 
-mod su2;
+/// Errors reading and writing grids
+pub mod error;
 
-mod geom_calc;
+/// Index two dimensional structured grids by (i, j)
+pub mod structured;
+
+/// Read and write su2 files
+pub mod su2;
+
+/// Geometric calculations for cells and interfaces
+pub mod geom_calc;
 
 pub trait Cell: Id {
     fn shape(&self) -> &CellShape;
@@ -50,4 +58,30 @@ pub trait Block<V, I, C>
     fn boundaries(&self) -> &HashMap<String, Vec<usize>>;
     fn dimensions(&self) -> u8;
     fn id(&self) -> usize;
+
+    fn n_cells(&self) -> usize {
+        self.cells().len()
+    }
+
+    fn n_interfaces(&self) -> usize {
+        self.interfaces().len()
+    }
+
+    /// The id's of the cells with a face on the boundary with the given
+    /// tag, in order of id. This is empty if there's no such boundary.
+    fn boundary_cell_ids(&self, tag: &str) -> Vec<usize> {
+        let boundary_interfaces = match self.boundaries().get(tag) {
+            Some(interfaces) => interfaces,
+            None => return Vec::new(),
+        };
+        self.cells()
+            .iter()
+            .filter(|cell| {
+                cell.interface_ids()
+                    .iter()
+                    .any(|interface| boundary_interfaces.contains(interface))
+            })
+            .map(|cell| cell.id())
+            .collect()
+    }
 }
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use common::number::Real;
+
+use crate::{Block, Cell, Id, Interface};
+use crate::block::GridBlock;
+use crate::cell::{CellShape, GridCell};
+use crate::error::GridError;
+use crate::interface::GridInterface;
+use crate::vertex::GridVertex;
+
+/// A view of a two dimensional block of `ni` by `nj` quadrilateral cells,
+/// which can be indexed by (i, j). The vertices and cells of the block
+/// must be numbered with i varying fastest, as written by structured
+/// grid generators. The interfaces can be numbered in any order.
+#[derive(Debug)]
+pub struct StructuredBlock2D<'a> {
+    block: &'a GridBlock,
+    ni: usize,
+    nj: usize,
+
+    /// The id of the interface on the west side of each cell, and
+    /// the east side of the last cells, at index j * (ni + 1) + i
+    i_faces: Vec<usize>,
+
+    /// The id of the interface on the south side of each cell, and
+    /// the north side of the last cells, at index j * ni + i
+    j_faces: Vec<usize>,
+}
+
+impl<'a> StructuredBlock2D<'a> {
+    /// Index `block` as `ni` by `nj` cells, or an error if the
+    /// block doesn't have the layout of a structured grid
+    pub fn from_grid_block(block: &'a GridBlock, ni: usize, nj: usize) -> Result<StructuredBlock2D<'a>, GridError> {
+        let invalid = |reason: String| Err(GridError::InvalidMesh(reason));
+        if block.dimensions() != 2 {
+            return invalid(format!("A {}D block can't be indexed by (i, j)", block.dimensions()));
+        }
+        if block.vertices().len() != (ni + 1) * (nj + 1) || block.cells().len() != ni * nj {
+            return invalid(format!(
+                "{} vertices and {} cells can't be arranged as {} x {} cells",
+                block.vertices().len(), block.cells().len(), ni, nj
+            ));
+        }
+
+        let vertex = |i: usize, j: usize| j * (ni + 1) + i;
+        for (index, cell) in block.cells().iter().enumerate() {
+            let (i, j) = (index % ni, index / ni);
+            let mut expected = [vertex(i, j), vertex(i + 1, j), vertex(i + 1, j + 1), vertex(i, j + 1)];
+            let mut vertex_ids = cell.vertex_ids().clone();
+            expected.sort();
+            vertex_ids.sort();
+            if cell.shape() != &CellShape::Quadrilateral || vertex_ids != expected {
+                return invalid(format!("Cell {} isn't at ({}, {})", index, i, j));
+            }
+        }
+
+        let mut edges = HashMap::with_capacity(block.interfaces().len());
+        for interface in block.interfaces().iter() {
+            let vertex_ids = interface.vertex_ids();
+            let edge = (vertex_ids[0].min(vertex_ids[1]), vertex_ids[0].max(vertex_ids[1]));
+            edges.insert(edge, interface.id());
+        }
+        // the cells are all in place, so every edge of them is an interface
+        let edges = &edges;
+        let i_faces = (0 .. nj)
+            .flat_map(|j| (0 ..= ni).map(move |i| edges[&(vertex(i, j), vertex(i, j + 1))]))
+            .collect();
+        let j_faces = (0 ..= nj)
+            .flat_map(|j| (0 .. ni).map(move |i| edges[&(vertex(i, j), vertex(i + 1, j))]))
+            .collect();
+
+        Ok(StructuredBlock2D{block, ni, nj, i_faces, j_faces})
+    }
+
+    pub fn block(&self) -> &GridBlock {
+        self.block
+    }
+
+    /// The number of cells in the i direction
+    pub fn ni(&self) -> usize {
+        self.ni
+    }
+
+    /// The number of cells in the j direction
+    pub fn nj(&self) -> usize {
+        self.nj
+    }
+
+    /// The cell at (i, j), for i < ni and j < nj
+    pub fn cell_at(&self, i: usize, j: usize) -> &GridCell {
+        assert!(i < self.ni && j < self.nj, "Cell ({}, {}) is outside {} x {} cells", i, j, self.ni, self.nj);
+        &self.block.cells()[j * self.ni + i]
+    }
+
+    /// The vertex at (i, j), for i <= ni and j <= nj
+    pub fn vertex_at(&self, i: usize, j: usize) -> &GridVertex {
+        assert!(i <= self.ni && j <= self.nj, "Vertex ({}, {}) is outside {} x {} cells", i, j, self.ni, self.nj);
+        &self.block.vertices()[j * (self.ni + 1) + i]
+    }
+
+    /// The interface of constant i between vertices (i, j) and
+    /// (i, j + 1), for i <= ni and j < nj
+    pub fn face_i_at(&self, i: usize, j: usize) -> &GridInterface {
+        assert!(i <= self.ni && j < self.nj, "i face ({}, {}) is outside {} x {} cells", i, j, self.ni, self.nj);
+        &self.block.interfaces()[self.i_faces[j * (self.ni + 1) + i]]
+    }
+
+    /// The interface of constant j between vertices (i, j) and
+    /// (i + 1, j), for i < ni and j <= nj
+    pub fn face_j_at(&self, i: usize, j: usize) -> &GridInterface {
+        assert!(i < self.ni && j <= self.nj, "j face ({}, {}) is outside {} x {} cells", i, j, self.ni, self.nj);
+        &self.block.interfaces()[self.j_faces[j * self.ni + i]]
+    }
+}
+
+/// The contents of an su2 file for a `width` by `height` rectangle with its
+/// lower left corner at the origin, divided into `ni` by `nj` quadrilateral
+/// cells. The vertices and cells are numbered with i varying fastest, so the
+/// block read from it can be indexed with [`StructuredBlock2D`]. The sides
+/// are tagged "south", "east", "north" and "west".
+pub fn rectangle_su2(ni: usize, nj: usize, width: Real, height: Real) -> String {
+    let vertex = |i: usize, j: usize| j * (ni + 1) + i;
+    let mut su2 = format!("NDIME= 2\nNPOIN= {}\n", (ni + 1) * (nj + 1));
+    for j in 0 ..= nj {
+        for i in 0 ..= ni {
+            let x = width * i as Real / ni as Real;
+            let y = height * j as Real / nj as Real;
+            writeln!(su2, "{} {}", x, y).unwrap();
+        }
+    }
+    writeln!(su2, "NELEM= {}", ni * nj).unwrap();
+    for j in 0 .. nj {
+        for i in 0 .. ni {
+            writeln!(su2, "9 {} {} {} {}", vertex(i, j), vertex(i + 1, j), vertex(i + 1, j + 1), vertex(i, j + 1)).unwrap();
+        }
+    }
+    let sides: [(&str, Vec<(usize, usize)>); 4] = [
+        ("south", (0 .. ni).map(|i| (vertex(i, 0), vertex(i + 1, 0))).collect()),
+        ("east", (0 .. nj).map(|j| (vertex(ni, j), vertex(ni, j + 1))).collect()),
+        ("north", (0 .. ni).map(|i| (vertex(i + 1, nj), vertex(i, nj))).collect()),
+        ("west", (0 .. nj).map(|j| (vertex(0, j + 1), vertex(0, j))).collect()),
+    ];
+    writeln!(su2, "NMARK= {}", sides.len()).unwrap();
+    for (tag, edges) in sides.iter() {
+        writeln!(su2, "MARKER_TAG={}\nMARKER_ELEMS= {}", tag, edges.len()).unwrap();
+        for (a, b) in edges.iter() {
+            writeln!(su2, "3 {} {}", a, b).unwrap();
+        }
+    }
+    su2
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use common::vector3::Vector3;
+
+    use super::*;
+    use crate::Vertex;
+    use crate::block::BlockCollection;
+
+    fn read_square() -> GridBlock {
+        let mut block_collection = BlockCollection::new();
+        block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+        block_collection.get_block(0).clone()
+    }
+
+    #[test]
+    fn index_square() {
+        let block = read_square();
+        let structured = StructuredBlock2D::from_grid_block(&block, 3, 3).unwrap();
+
+        assert_eq!(structured.cell_at(0, 0).id(), 0);
+        assert_eq!(structured.cell_at(2, 1).id(), 5);
+        assert_eq!(structured.cell_at(1, 2).centre(), &Vector3{x: 1.5, y: 2.5, z: 0.0});
+        assert_eq!(structured.vertex_at(3, 0).pos(), &Vector3{x: 3.0, y: 0.0, z: 0.0});
+        assert_eq!(structured.vertex_at(1, 2).pos(), &Vector3{x: 1.0, y: 2.0, z: 0.0});
+    }
+
+    #[test]
+    fn faces_of_square() {
+        let block = read_square();
+        let structured = StructuredBlock2D::from_grid_block(&block, 3, 3).unwrap();
+
+        // the west side of the grid is the inflow
+        let inflow: Vec<usize> = (0 .. 3).map(|j| structured.face_i_at(0, j).id()).collect();
+        assert_eq!(inflow, vec![3, 12, 19]);
+        assert_eq!(structured.face_i_at(2, 1).centre(), Vector3{x: 2.0, y: 1.5, z: 0.0});
+        assert_eq!(structured.face_j_at(1, 3).centre(), Vector3{x: 1.5, y: 3.0, z: 0.0});
+        for j in 0 .. 3 {
+            for i in 0 .. 3 {
+                let faces = structured.cell_at(i, j).interface_ids();
+                for face in [structured.face_i_at(i, j), structured.face_i_at(i + 1, j),
+                             structured.face_j_at(i, j), structured.face_j_at(i, j + 1)] {
+                    assert!(faces.contains(&face.id()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rectangle() {
+        let su2 = rectangle_su2(4, 2, 2.0, 1.0);
+        let block = crate::su2::read_su2(su2.as_bytes(), 0).unwrap();
+        let structured = StructuredBlock2D::from_grid_block(&block, 4, 2).unwrap();
+
+        assert_eq!(structured.vertex_at(4, 2).pos(), &Vector3{x: 2.0, y: 1.0, z: 0.0});
+        assert_eq!(structured.cell_at(1, 1).centre(), &Vector3{x: 0.75, y: 0.75, z: 0.0});
+        assert_eq!(block.boundaries()["south"].len(), 4);
+        assert_eq!(block.boundaries()["east"].len(), 2);
+        assert!(block.boundaries()["west"].contains(&structured.face_i_at(0, 1).id()));
+    }
+
+    #[test]
+    fn wrong_size() {
+        let block = read_square();
+
+        assert!(matches!(StructuredBlock2D::from_grid_block(&block, 9, 1), Err(GridError::InvalidMesh(_))));
+        assert!(matches!(StructuredBlock2D::from_grid_block(&block, 2, 2), Err(GridError::InvalidMesh(_))));
+    }
+
+    #[test]
+    #[should_panic]
+    fn cell_out_of_range() {
+        let block = read_square();
+        let structured = StructuredBlock2D::from_grid_block(&block, 3, 3).unwrap();
+
+        structured.cell_at(3, 0);
+    }
+}
@@ -1,5 +1,5 @@
 use std::path::Path;
-use std::io::{Lines, BufReader, BufRead, BufWriter, Write};
+use std::io::{BufRead, BufWriter, Seek, SeekFrom, Write};
 use std::fs::File;
 use std::collections::HashMap;
 
@@ -7,14 +7,17 @@ use super::block::GridBlock;
 use crate::interface::InterfaceCollection;
 use crate::{vertex::GridVertex, interface::GridInterface, cell::{GridCell, CellShape}};
 use crate::{Vertex, Interface, Cell, Block};
+use crate::error::GridError;
 use common::vector3::Vector3;
-use common::DynamicResult;
 
-/// Read a GridBlock from and su2 file
-pub fn read_su2(file_path: &Path, id: usize) -> DynamicResult<GridBlock> {
-    // open the file
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
+/// The most items to reserve space for based on the counts in the
+/// file, so a corrupt count can't exhaust the memory before the
+/// file is found to be too short
+const MAX_RESERVE: usize = 1 << 20;
+
+/// Read a GridBlock from the contents of an su2 file
+pub fn read_su2<R: BufRead>(reader: R, id: usize) -> Result<GridBlock, GridError> {
+    let mut lines = Su2Lines::new(reader);
 
     // we are going to iterate line by line, until we reach the end of the file.
     // If we hit a section heading, we will read that section of data. 
@@ -22,96 +25,246 @@ pub fn read_su2(file_path: &Path, id: usize) -> DynamicResult<GridBlock> {
     // (this is consistent with the su2 specification)
     let mut dimensions: Option<usize> = None;
     let mut n_cells: Option<usize> = None;
-    let mut vertices: Vec<GridVertex> = vec![];
-    let mut cell_connectivity: Vec<Vec<Vec<usize>>> = vec![]; 
+    let mut point_lines: Vec<Su2Line> = vec![];
     let mut cell_vertices: Vec<Vec<usize>> = vec![];
+    let mut cell_shapes: Vec<CellShape> = vec![];
     let mut boundary_faces: HashMap<String, Vec<Vec<usize>>> = HashMap::new();
-    let mut boundaries: HashMap<String, Vec<usize>> = HashMap::new();
-
-    let mut line_iter = reader.lines();
-    while let Some(line) = line_iter.next() {
-        // clean up the line before we begin
-        let line = line?;
-        let line = line.trim();
 
+    while let Some(line) = lines.next_line()? {
         // the number of spatial dimensions
-        if line.starts_with("NDIME=") {
-            dimensions = Some(parse_key_value_pair(line));
+        if line.text.starts_with("NDIME=") {
+            dimensions = Some(parse_dimensions(&line)?);
         }
         
-        // the position of each vertex
-        else if line.starts_with("NPOIN=") {
-            let dim = dimensions
-                .expect("Number of dimension should be set before vertex coordinates");
-            let n_points = parse_key_value_pair::<usize>(line);
-            vertices.reserve(n_points);
-            for point_i in 0 .. n_points {
-                let point_line = next_line(&mut line_iter);
-                let coords = parse_vector_from_line_with_dim(&point_line, dim);
-                let vertex_pos = Vector3::new_from_vec(coords);
-                vertices.push(GridVertex::new(vertex_pos, point_i));                                        
+        // the position of each vertex. These are parsed once the whole
+        // file has been read, since NDIME may come after NPOIN
+        else if line.text.starts_with("NPOIN=") {
+            let n_points = line.parse_key_value_pair::<usize>()?;
+            point_lines.reserve(n_points.min(MAX_RESERVE));
+            for _ in 0 .. n_points {
+                point_lines.push(lines.expect_line()?);
             }
         }
 
-        // the interfaces and cells
+        // the cells
         // this part works soley in id's, rather than references. 
         // This let's us read this before the definition of points 
         // if needed.
-        else if line.starts_with("NELEM=") {
-            let n_elem = parse_key_value_pair::<usize>(line);
+        else if line.text.starts_with("NELEM=") {
+            let n_elem = line.parse_key_value_pair::<usize>()?;
             n_cells = Some(n_elem);
-            cell_connectivity.reserve(n_elem);
-            cell_vertices.reserve(n_elem);
+            cell_vertices.reserve(n_elem.min(MAX_RESERVE));
+            cell_shapes.reserve(n_elem.min(MAX_RESERVE));
             for _ in 0 .. n_elem {
-                let cell_line = next_line(&mut line_iter);
-                let cell_definition = parse_vector_from_line::<usize>(&cell_line);
-                let shape = CellShape::from_su2_element_type(cell_definition[0]);
-                let this_cell_vertices = &cell_definition[1..];
-                cell_connectivity.push(shape.interfaces(this_cell_vertices));
-                cell_vertices.push(this_cell_vertices.to_vec());
+                let (shape, this_cell_vertices) = parse_element(&lines.expect_line()?)?;
+                cell_vertices.push(this_cell_vertices);
+                cell_shapes.push(shape);
             }
         }
 
         // boundary conditions
-        else if line.starts_with("NMARK=") {
-            let n_boundaries = parse_key_value_pair(line);
+        else if line.text.starts_with("NMARK=") {
+            let n_boundaries = line.parse_key_value_pair()?;
             for _ in 0 .. n_boundaries {
-                let (tag, bndry_faces) = read_boundary(&mut line_iter);
+                let (tag, bndry_faces) = read_boundary(&mut lines)?;
                 boundary_faces.insert(tag, bndry_faces);
             }
         }
     }
-    // now that we've read the file, we can build the interfaces and cells
-    let n_cells = n_cells.expect("Could not find connectivity");
+
+    // now that we've read the file, we can build the vertices
+    let dimensions = dimensions.ok_or_else(missing_dimensions)?;
+    let mut vertices: Vec<GridVertex> = Vec::with_capacity(point_lines.len());
+    for (point_i, point_line) in point_lines.iter().enumerate() {
+        vertices.push(parse_vertex(point_line, dimensions, point_i)?);
+    }
+
+    // and the interfaces and cells
+    let n_cells = n_cells.ok_or_else(missing_connectivity)?;
+    #[cfg(not(feature = "flat_interfaces"))]
     let mut interfaces = InterfaceCollection::with_capacity(n_cells);
+    #[cfg(feature = "flat_interfaces")]
+    let mut interfaces = InterfaceCollection::new_flat(n_cells);
     let mut cells: Vec<GridCell> = Vec::with_capacity(n_cells);
-    for (i, cell_interfaces) in cell_connectivity.iter().enumerate() {
-        let mut this_cell_interface_ids: Vec<usize> = vec![];
-        for interface in cell_interfaces.iter() {
+    for (i, (shape, this_cell_vertices)) in cell_shapes.iter().zip(cell_vertices.iter()).enumerate() {
+        cells.push(build_cell(*shape, this_cell_vertices, &vertices, &mut interfaces, i)?);
+    }
+
+    // now we can find the interfaces on the boundaries
+    let boundaries = find_boundary_interfaces(boundary_faces, &interfaces, &vertices)?;
+    Ok(GridBlock::new(vertices, interfaces.interfaces(), cells, boundaries, dimensions as u8, id))
+}
+
+/// Read a GridBlock from the contents of an su2 file in two passes, to
+/// use less memory on large grids than [`read_su2`]. The first pass finds
+/// where each section starts and how many items it has, without keeping
+/// the lines. The second pass returns to each section, and builds the
+/// vertices and cells straight from the lines, into vectors allocated to
+/// the exact size.
+pub fn read_su2_streaming<R: BufRead + Seek>(reader: R, id: usize) -> Result<GridBlock, GridError> {
+    let mut lines = Su2Lines::new(reader);
+
+    // the first pass. The boundaries are small compared to the rest
+    // of the grid, so they are read straight away
+    let mut dimensions: Option<usize> = None;
+    let mut points: Option<(Su2Position, usize)> = None;
+    let mut elements: Option<(Su2Position, usize)> = None;
+    let mut n_cell_faces = 0;
+    let mut boundary_faces: HashMap<String, Vec<Vec<usize>>> = HashMap::new();
+    while let Some(line) = lines.next_line()? {
+        if line.text.starts_with("NDIME=") {
+            dimensions = Some(parse_dimensions(&line)?);
+        }
+        else if line.text.starts_with("NPOIN=") {
+            let n_points = line.parse_key_value_pair::<usize>()?;
+            points = Some((lines.position()?, n_points));
+            lines.skip_lines(n_points)?;
+        }
+        else if line.text.starts_with("NELEM=") {
+            let n_elem = line.parse_key_value_pair::<usize>()?;
+            elements = Some((lines.position()?, n_elem));
+            for _ in 0 .. n_elem {
+                let (shape, _) = parse_element(&lines.expect_line()?)?;
+                n_cell_faces += shape.number_of_interfaces();
+            }
+        }
+        else if line.text.starts_with("NMARK=") {
+            let n_boundaries = line.parse_key_value_pair()?;
+            for _ in 0 .. n_boundaries {
+                let (tag, bndry_faces) = read_boundary(&mut lines)?;
+                boundary_faces.insert(tag, bndry_faces);
+            }
+        }
+    }
+
+    // the second pass. The first pass made sure there are as many lines
+    // as each count says, so it's safe to allocate the exact size
+    let dimensions = dimensions.ok_or_else(missing_dimensions)?;
+    let mut vertices: Vec<GridVertex> = Vec::new();
+    if let Some((position, n_points)) = points {
+        lines.seek(position)?;
+        vertices.reserve_exact(n_points);
+        for point_i in 0 .. n_points {
+            vertices.push(parse_vertex(&lines.expect_line()?, dimensions, point_i)?);
+        }
+    }
+
+    // every interface is either shared by two cells, or on a boundary, so
+    // this is the number of interfaces if every boundary face is tagged.
+    // Reserving enough space up front saves growing the collection while
+    // it holds most of the interfaces.
+    let n_boundary_faces: usize = boundary_faces.values().map(|faces| faces.len()).sum();
+    let n_interfaces = (n_cell_faces + n_boundary_faces) / 2;
+    let (position, n_cells) = elements.ok_or_else(missing_connectivity)?;
+    lines.seek(position)?;
+    #[cfg(not(feature = "flat_interfaces"))]
+    let mut interfaces = InterfaceCollection::with_capacity(n_interfaces);
+    #[cfg(feature = "flat_interfaces")]
+    let mut interfaces = InterfaceCollection::new_flat(n_interfaces);
+    let mut cells: Vec<GridCell> = Vec::with_capacity(n_cells);
+    for i in 0 .. n_cells {
+        let (shape, cell_vertices) = parse_element(&lines.expect_line()?)?;
+        cells.push(build_cell(shape, &cell_vertices, &vertices, &mut interfaces, i)?);
+    }
+
+    let boundaries = find_boundary_interfaces(boundary_faces, &interfaces, &vertices)?;
+    Ok(GridBlock::new(vertices, interfaces.into_interfaces(), cells, boundaries, dimensions as u8, id))
+}
+
+fn parse_dimensions(line: &Su2Line) -> Result<usize, GridError> {
+    let dim = line.parse_key_value_pair()?;
+    if !(2 ..= 3).contains(&dim) {
+        return Err(line.error(format!("expected 2 or 3 dimensions, found {}", dim)));
+    }
+    Ok(dim)
+}
+
+fn missing_dimensions() -> GridError {
+    GridError::InvalidMesh("Could not find number of dimensions (NDIME)".to_string())
+}
+
+fn missing_connectivity() -> GridError {
+    GridError::InvalidMesh("Could not find connectivity (NELEM)".to_string())
+}
+
+fn parse_vertex(line: &Su2Line, dimensions: usize, id: usize) -> Result<GridVertex, GridError> {
+    let coords = line.parse_vector_with_dim(dimensions)?;
+    Ok(GridVertex::new(Vector3::new_from_vec(coords), id))
+}
+
+/// The shape and vertex id's of an element
+fn parse_element(line: &Su2Line) -> Result<(CellShape, Vec<usize>), GridError> {
+    let cell_definition = line.parse_vector::<usize>()?;
+    let (shape, cell_vertices) = match cell_definition.split_first() {
+        // polygons are the only elements without a fixed number of vertices
+        Some((7, cell_vertices)) if cell_vertices.len() > 4 => {
+            let n_vertices = u8::try_from(cell_vertices.len()).map_err(
+                |_| line.error("too many vertices in polygon")
+            )?;
+            (CellShape::Polygon(n_vertices), cell_vertices)
+        }
+        Some((elem_type, cell_vertices)) => {
+            let shape = CellShape::try_from_su2_element_type(*elem_type).ok_or_else(
+                || line.error(format!("unsupported element type {}", elem_type))
+            )?;
+            (shape, cell_vertices)
+        }
+        None => return Err(line.error("empty element definition")),
+    };
+    if cell_vertices.len() != shape.number_of_vertices() {
+        return Err(line.error(format!(
+            "expected {} vertices, found {}", shape.number_of_vertices(), cell_vertices.len()
+        )));
+    }
+    Ok((shape, cell_vertices.to_vec()))
+}
+
+/// Build a cell from the id's of its vertices, adding its
+/// interfaces to `interfaces` if they aren't there already
+fn build_cell(shape: CellShape, cell_vertices: &[usize], vertices: &[GridVertex],
+              interfaces: &mut InterfaceCollection, id: usize) -> Result<GridCell, GridError> {
+    if let Some(vertex_id) = cell_vertices.iter().find(|vertex_id| **vertex_id >= vertices.len()) {
+        return Err(GridError::InvalidMesh(
+            format!("Vertex {} is used, but there are only {} vertices", vertex_id, vertices.len())
+        ));
+    }
+    let interface_ids: Vec<usize> = shape.interfaces(cell_vertices)
+        .iter()
+        .map(|interface| {
             let interface_vertices: Vec<&GridVertex> = interface
                 .iter()
                 .map(|vertex_id| &vertices[*vertex_id])
                 .collect();
-            let interface_id = interfaces.add_or_retrieve(&interface_vertices);
-            this_cell_interface_ids.push(interface_id);
-        }
-
-        let this_cell_interfaces: Vec<&GridInterface> = this_cell_interface_ids
-            .iter()
-            .map(|id| interfaces.interface_with_id(*id))
-            .collect();
-        let this_cell_vertices: Vec<&GridVertex> = cell_vertices[i]
-            .iter()
-            .map(|id| &vertices[*id])
-            .collect();
-        cells.push(GridCell::new(&this_cell_interfaces, &this_cell_vertices, i));
-    }
+            interfaces.add_or_retrieve(&interface_vertices)
+        })
+        .collect();
+    let cell_interfaces: Vec<&GridInterface> = interface_ids
+        .iter()
+        .map(|id| interfaces.interface_with_id(*id))
+        .collect();
+    let cell_vertices: Vec<&GridVertex> = cell_vertices
+        .iter()
+        .map(|id| &vertices[*id])
+        .collect();
+    GridCell::try_new_with_shape(shape, &cell_interfaces, &cell_vertices, id)
+}
 
-    // now we can find the interfaces on the boundaries
+/// The id's of the interfaces on each boundary, from the
+/// element type and vertex id's of each boundary face
+fn find_boundary_interfaces(boundary_faces: HashMap<String, Vec<Vec<usize>>>, interfaces: &InterfaceCollection,
+                            vertices: &[GridVertex]) -> Result<HashMap<String, Vec<usize>>, GridError> {
+    let mut boundaries: HashMap<String, Vec<usize>> = HashMap::new();
     for (tag, faces_on_boundary) in boundary_faces {
         let mut interfaces_on_boundary = Vec::new();
         for vertex_ids_in_face in faces_on_boundary {
-            let vertices_in_face: Vec<&GridVertex> = vertex_ids_in_face[1..]
+            let vertex_ids_in_face = &vertex_ids_in_face[1..];
+            if !interfaces.contains_vertices(vertex_ids_in_face) {
+                return Err(GridError::InvalidMesh(format!(
+                    "Boundary '{}' has a face with vertices {:?}, which isn't part of any cell",
+                    tag, vertex_ids_in_face
+                )));
+            }
+            let vertices_in_face: Vec<&GridVertex> = vertex_ids_in_face
                 .iter()
                 .map(|id| &vertices[*id])
                 .collect();
@@ -120,106 +273,207 @@ pub fn read_su2(file_path: &Path, id: usize) -> DynamicResult<GridBlock> {
         }
         boundaries.insert(tag, interfaces_on_boundary);
     }
-    Ok(GridBlock::new(vertices, interfaces.interfaces(), cells, boundaries, dimensions.unwrap() as u8, id))
+    Ok(boundaries)
 }
 
 /// Write a [`Block`] trait object to a su2 file
-pub fn write_su2<V, I, C, B>(file_path: &Path, block: &B)
+pub fn write_su2<V, I, C, B>(file_path: &Path, block: &B) -> Result<(), GridError>
     where B: Block<V, I, C>, C: Cell, I: Interface + Clone, V: Vertex
 {
-    let file = File::create(file_path).unwrap(); 
+    let file = File::create(file_path).map_err(|err| with_path(err, file_path))?;
     let mut buffer = BufWriter::new(file);
 
     // the number of dimensions
-    writeln!(buffer, "NDIME={}", block.dimensions()).unwrap();
+    writeln!(buffer, "NDIME={}", block.dimensions())?;
 
     // the position of the vertices
-    writeln!(buffer, "NPOIN={}", block.vertices().len()).unwrap();
+    writeln!(buffer, "NPOIN={}", block.vertices().len())?;
     for vertex in block.vertices().iter() {
-        write!(buffer, "{}", vertex.pos().x).unwrap();
-        write!(buffer, " {}", vertex.pos().y).unwrap();
+        write!(buffer, "{}", vertex.pos().x)?;
+        write!(buffer, " {}", vertex.pos().y)?;
         if block.dimensions() == 3 {
-            write!(buffer, " {}", vertex.pos().z).unwrap();
+            write!(buffer, " {}", vertex.pos().z)?;
         }
-        writeln!(buffer).unwrap();
+        writeln!(buffer)?;
     }
 
     // the connectivity
-    writeln!(buffer, "NELEM={}", block.cells().len()).unwrap();
+    writeln!(buffer, "NELEM={}", block.cells().len())?;
     for cell in block.cells().iter() {
         let element_type = cell.shape().to_su2_element_type();
-        write!(buffer, "{}", element_type).unwrap();
+        write!(buffer, "{}", element_type)?;
         for vertex_id in cell.vertex_ids().iter() {
-            write!(buffer, " {}", vertex_id).unwrap();
+            write!(buffer, " {}", vertex_id)?;
         }
-        writeln!(buffer).unwrap();
+        writeln!(buffer)?;
     }
 
-    // boundaries
+    // boundaries, in order of tag so the file is the same every time.
+    // In 3D the boundary faces are triangles and quadrilaterals.
     let interfaces = block.interfaces();
-    writeln!(buffer, "NMARK={}", block.boundaries().len()).unwrap();
-    for (tag, bndry_interfaces) in block.boundaries().iter() {
-        writeln!(buffer, "MARKER_TAG={}", tag).unwrap();
-        writeln!(buffer, "MARKER_ELEMS={}", bndry_interfaces.len()).unwrap();
+    writeln!(buffer, "NMARK={}", block.boundaries().len())?;
+    let mut boundaries: Vec<(&String, &Vec<usize>)> = block.boundaries().iter().collect();
+    boundaries.sort_by_key(|(tag, _)| *tag);
+    for (tag, bndry_interfaces) in boundaries {
+        writeln!(buffer, "MARKER_TAG={}", tag)?;
+        writeln!(buffer, "MARKER_ELEMS={}", bndry_interfaces.len())?;
         for interface in bndry_interfaces.iter() {
             let iface = &interfaces[*interface];
             let shape = iface.shape().to_su2_element_type();
-            write!(buffer, "{}", shape).unwrap();
+            write!(buffer, "{}", shape)?;
             for vertex_id in iface.vertex_ids().iter() {
-                write!(buffer, " {}", vertex_id).unwrap();
+                write!(buffer, " {}", vertex_id)?;
             }
-            writeln!(buffer).unwrap();
+            writeln!(buffer)?;
         }
     }
+    buffer.flush()?;
+    Ok(())
 }
 
-fn parse_key_value_pair<T>(pair: &str) -> T
-    where T: std::str::FromStr, <T as std::str::FromStr>::Err: std::fmt::Debug
-{
-    pair.split('=')
-        .last().unwrap()
-        .trim()
-        .parse().unwrap()
+/// Add the file name to an I/O error, so it is clear which file failed
+pub(crate) fn with_path(err: std::io::Error, file_path: &Path) -> std::io::Error {
+    std::io::Error::new(err.kind(), format!("{}: {}", file_path.display(), err))
 }
 
-fn parse_vector_from_line_with_dim<T>(line: &str, dim: usize) -> Vec<T> 
-    where T: std::str::FromStr, <T as std::str::FromStr>::Err: std::fmt::Debug
-{
-    line.split(' ')
-        .filter(|token| !token.is_empty()) // remove empty tokens
-        .take(dim) // take only the first dim tokens
-        .map(|token| token.parse().unwrap()) // convert tokens to T
-        .collect() // collect into a vector
+/// A trimmed line of an su2 file, and its line number (counting from one)
+struct Su2Line {
+    number: usize,
+    text: String,
 }
 
-fn parse_vector_from_line<T>(line: &str) -> Vec<T> 
-    where T: std::str::FromStr, <T as std::str::FromStr>::Err: std::fmt::Debug
-{
-    line.split(' ')
-        .filter(|token| !token.is_empty())
-        .map(|token| token.parse().unwrap())
-        .collect()
-}
-
-fn read_boundary(line_iter: &mut Lines<BufReader<File>>) -> (String, Vec<Vec<usize>>) {
-    let bndry_line = next_line(line_iter);
-    assert!(bndry_line.starts_with("MARKER_TAG"));
-    let tag = bndry_line.split_once('=').unwrap().1.to_string();
-    let bndry_line = next_line(line_iter);
-    assert!(bndry_line.starts_with("MARKER_ELEMS"));
-    let number_interfaces = parse_key_value_pair::<usize>(&bndry_line);
-    let mut bndry_interfaces: Vec<Vec<usize>> = Vec::with_capacity(number_interfaces);
-    for _ in 0 .. number_interfaces {
-        let bndry_line = next_line(line_iter);
-        bndry_interfaces.push(parse_vector_from_line(&bndry_line));
+impl Su2Line {
+    fn error(&self, reason: impl ToString) -> GridError {
+        GridError::parse_error(self.number, &self.text, reason)
     }
-    (tag, bndry_interfaces)
+
+    fn parse_key_value_pair<T>(&self) -> Result<T, GridError>
+        where T: std::str::FromStr, <T as std::str::FromStr>::Err: std::fmt::Display
+    {
+        let (_, value) = self.text.split_once('=')
+            .ok_or_else(|| self.error("expected key=value"))?;
+        value.trim()
+            .parse()
+            .map_err(|err| self.error(err))
+    }
+
+    fn parse_vector_with_dim<T>(&self, dim: usize) -> Result<Vec<T>, GridError>
+        where T: std::str::FromStr, <T as std::str::FromStr>::Err: std::fmt::Display
+    {
+        let values = self.text.split(' ')
+            .filter(|token| !token.is_empty()) // remove empty tokens
+            .take(dim) // take only the first dim tokens
+            .map(|token| token.parse().map_err(|err| self.error(err))) // convert tokens to T
+            .collect::<Result<Vec<T>, GridError>>()?;
+        if values.len() < dim {
+            return Err(self.error(format!("expected {} values, found {}", dim, values.len())));
+        }
+        Ok(values)
+    }
+
+    fn parse_vector<T>(&self) -> Result<Vec<T>, GridError>
+        where T: std::str::FromStr, <T as std::str::FromStr>::Err: std::fmt::Display
+    {
+        self.text.split(' ')
+            .filter(|token| !token.is_empty())
+            .map(|token| token.parse().map_err(|err| self.error(err)))
+            .collect()
+    }
+}
+
+/// The lines of an su2 file, keeping track of the line number
+struct Su2Lines<R> {
+    reader: R,
+    buffer: String,
+    line_number: usize,
 }
 
-fn next_line(line_iter: &mut Lines<BufReader<File>>) -> String {
-    line_iter.next()
-        .unwrap()
-        .unwrap()
-        .trim()
-        .to_string()
+/// A place in an su2 file to return to with [`Su2Lines::seek`]
+#[derive(Debug, Clone, Copy)]
+struct Su2Position {
+    offset: u64,
+    line_number: usize,
+}
+
+impl<R: BufRead> Su2Lines<R> {
+    fn new(reader: R) -> Su2Lines<R> {
+        Su2Lines { reader, buffer: String::new(), line_number: 0 }
+    }
+
+    /// The next line, or `None` at the end of the file. Comments
+    /// (anything after a `%`) are removed, and lines which only
+    /// contain a comment are skipped.
+    fn next_line(&mut self) -> Result<Option<Su2Line>, GridError> {
+        loop {
+            self.buffer.clear();
+            if self.reader.read_line(&mut self.buffer)? == 0 {
+                return Ok(None);
+            }
+            self.line_number += 1;
+            let (text, comment) = match self.buffer.split_once('%') {
+                Some((text, comment)) => (text.trim(), Some(comment)),
+                None => (self.buffer.trim(), None),
+            };
+            if text.is_empty() && comment.is_some() {
+                continue;
+            }
+            return Ok(Some(Su2Line { number: self.line_number, text: text.to_string() }));
+        }
+    }
+
+    /// The next line, which must exist
+    fn expect_line(&mut self) -> Result<Su2Line, GridError> {
+        self.next_line()?.ok_or_else(|| GridError::InvalidMesh(
+            format!("Unexpected end of file after line {}", self.line_number)
+        ))
+    }
+
+    /// Skip over `n` lines, which must exist
+    fn skip_lines(&mut self, n: usize) -> Result<(), GridError> {
+        for _ in 0 .. n {
+            self.expect_line()?;
+        }
+        Ok(())
+    }
+
+}
+
+impl<R: BufRead + Seek> Su2Lines<R> {
+    /// The position of the next line
+    fn position(&mut self) -> Result<Su2Position, GridError> {
+        Ok(Su2Position { offset: self.reader.stream_position()?, line_number: self.line_number })
+    }
+
+    /// Go back to a position found with [`Su2Lines::position`]
+    fn seek(&mut self, position: Su2Position) -> Result<(), GridError> {
+        self.reader.seek(SeekFrom::Start(position.offset))?;
+        self.line_number = position.line_number;
+        Ok(())
+    }
+}
+
+fn read_boundary<R: BufRead>(lines: &mut Su2Lines<R>) -> Result<(String, Vec<Vec<usize>>), GridError> {
+    let bndry_line = lines.expect_line()?;
+    if !bndry_line.text.starts_with("MARKER_TAG") {
+        return Err(bndry_line.error("expected MARKER_TAG"));
+    }
+    let tag = match bndry_line.text.split_once('=') {
+        Some((_, tag)) => tag.to_string(),
+        None => return Err(bndry_line.error("expected key=value")),
+    };
+    let bndry_line = lines.expect_line()?;
+    if !bndry_line.text.starts_with("MARKER_ELEMS") {
+        return Err(bndry_line.error("expected MARKER_ELEMS"));
+    }
+    let number_interfaces = bndry_line.parse_key_value_pair::<usize>()?;
+    let mut bndry_interfaces: Vec<Vec<usize>> = Vec::with_capacity(number_interfaces.min(MAX_RESERVE));
+    for _ in 0 .. number_interfaces {
+        let bndry_line = lines.expect_line()?;
+        let face = bndry_line.parse_vector::<usize>()?;
+        if face.len() < 2 {
+            return Err(bndry_line.error("expected an element type and vertices"));
+        }
+        bndry_interfaces.push(face);
+    }
+    Ok((tag, bndry_interfaces))
 }
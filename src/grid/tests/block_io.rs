@@ -1,9 +1,16 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::fs::File;
+use std::io::{BufReader, Cursor};
 use std::collections::HashMap;
 
 use common::vector3::Vector3;
 use grid::{vertex::GridVertex, interface::GridInterface, cell::GridCell, block::*};
-use grid::Block;
+use grid::{Block, Cell, Id, Interface, Vertex};
+use grid::cell::CellShape;
+use grid::interface::InterfaceShape;
+use grid::error::GridError;
+use grid::structured::rectangle_su2;
+use grid::su2::{read_su2, read_su2_streaming};
 
 fn create_block_elements() -> (Vec<GridVertex>, Vec<GridInterface>, Vec<GridCell>, HashMap<String, Vec<usize>>) {
     let vertices = vec![
@@ -115,3 +122,592 @@ fn write_su2_file() {
 
     assert_eq!(ref_block.vertices(), read_block.vertices());
 }
+
+#[test]
+fn read_su2_cube() {
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("./tests/data/cube.su2")).unwrap();
+    let block = block_collection.get_block(0);
+
+    assert_eq!(block.dimensions(), 3);
+    assert_eq!(block.vertices().len(), 27);
+    assert_eq!(block.vertices()[26].pos(), &Vector3{x: 1.0, y: 1.0, z: 1.0});
+    assert_eq!(block.cells().len(), 8);
+    for cell in block.cells().iter() {
+        assert_eq!(cell.shape(), &CellShape::Hexahedron);
+        assert!((cell.volume() - 0.125).abs() < 1e-15);
+    }
+    // three planes of four faces in each direction
+    assert_eq!(block.interfaces().len(), 36);
+    assert!(block.interfaces().iter().all(|interface| interface.shape() == &InterfaceShape::Quadrilateral));
+    assert_eq!(block.boundary_tags(), vec!["bottom", "east", "north", "south", "top", "west"]);
+    for tag in block.boundary_tags() {
+        assert_eq!(block.boundary_interfaces(tag).unwrap().len(), 4);
+    }
+    assert_eq!(block.cells_adjacent_to_boundary("west"), Some(vec![0, 2, 4, 6]));
+    assert_eq!(block.validate(), Ok(()));
+}
+
+#[test]
+fn write_su2_cube() {
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("./tests/data/cube.su2")).unwrap();
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("cube_test.su2");
+    write_block(block_collection.get_block(0), &path).unwrap();
+    block_collection.add_block(&path).unwrap();
+
+    let (written, read) = (block_collection.get_block(0), block_collection.get_block(1));
+    assert_eq!(read.dimensions(), 3);
+    assert_eq!(read.vertices(), written.vertices());
+    assert_eq!(read.cells(), written.cells());
+    assert_eq!(read.boundaries(), written.boundaries());
+    assert_eq!(read.boundary_tags(), vec!["bottom", "east", "north", "south", "top", "west"]);
+}
+
+#[test]
+fn write_su2_triangle_and_quad_boundaries() {
+    // a tetrahedron and a pyramid, with a triangle and a quadrilateral boundary
+    let contents = "\
+NDIME= 3
+NPOIN= 9
+0 0 0
+1 0 0
+0 1 0
+0 0 1
+0 0 2
+1 0 2
+1 1 2
+0 1 2
+0.5 0.5 3
+NELEM= 2
+10 0 1 2 3
+14 4 5 6 7 8
+NMARK= 2
+MARKER_TAG=tet_base
+MARKER_ELEMS= 1
+5 0 2 1
+MARKER_TAG=pyramid_base
+MARKER_ELEMS= 1
+9 4 7 6 5
+";
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&write_temp_file("mixed_boundaries.su2", contents)).unwrap();
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("mixed_boundaries_test.su2");
+    write_block(block_collection.get_block(0), &path).unwrap();
+    block_collection.add_block(&path).unwrap();
+
+    let (written, read) = (block_collection.get_block(0), block_collection.get_block(1));
+    assert_eq!(read.vertices(), written.vertices());
+    assert_eq!(read.cells(), written.cells());
+    assert_eq!(read.boundaries(), written.boundaries());
+    assert_eq!(read.boundary_tags(), vec!["pyramid_base", "tet_base"]);
+    assert_eq!(read.boundary_interfaces("tet_base").unwrap()[0].shape(), &InterfaceShape::Triangle);
+    assert_eq!(read.boundary_interfaces("pyramid_base").unwrap()[0].shape(), &InterfaceShape::Quadrilateral);
+}
+
+#[test]
+fn su2_3d_elements() {
+    // a tetrahedron, a pyramid and a prism, which don't share any vertices
+    let contents = "\
+NDIME= 3
+NPOIN= 15
+0 0 0
+1 0 0
+0 1 0
+0 0 1
+0 0 2
+1 0 2
+1 1 2
+0 1 2
+0.5 0.5 3
+0 0 4
+1 0 4
+0 1 4
+0 0 5
+1 0 5
+0 1 5
+NELEM= 3
+10 0 1 2 3
+14 4 5 6 7 8
+13 9 10 11 12 13 14
+NMARK= 0
+";
+    let path = write_temp_file("3d_elements.su2", contents);
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&path).unwrap();
+    let block = block_collection.get_block(0);
+
+    let shapes: Vec<CellShape> = block.cells().iter().map(|cell| *cell.shape()).collect();
+    assert_eq!(shapes, vec![CellShape::Tetrahedron, CellShape::Pyramid, CellShape::Prism]);
+    let volumes = [1.0 / 6.0, 1.0 / 3.0, 0.5];
+    for (cell, volume) in block.cells().iter().zip(volumes) {
+        assert!((cell.volume() - volume).abs() < 1e-15);
+    }
+    assert_eq!(block.interfaces().len(), 4 + 5 + 5);
+    let triangles = block.interfaces()
+        .iter()
+        .filter(|interface| interface.shape() == &InterfaceShape::Triangle)
+        .count();
+    assert_eq!(triangles, 4 + 4 + 2);
+}
+
+#[test]
+fn add_blocks_parallel() {
+    let paths = vec![PathBuf::from("./tests/data/square.su2"); 8];
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&paths[0]).unwrap();
+    block_collection.add_blocks_parallel(&paths).unwrap();
+
+    assert_eq!(block_collection.blocks().len(), 9);
+    for (i, block) in block_collection.blocks().iter().enumerate() {
+        assert_eq!(block.id(), i);
+        assert_eq!(block.cells().len(), 9);
+        assert_eq!(block.vertices(), block_collection.get_block(0).vertices());
+    }
+}
+
+#[test]
+fn add_blocks_parallel_missing_file() {
+    let paths = vec![
+        PathBuf::from("./tests/data/square.su2"),
+        PathBuf::from("./tests/data/missing.su2"),
+    ];
+    let mut block_collection = BlockCollection::new();
+    let result = block_collection.add_blocks_parallel(&paths);
+
+    assert!(result.unwrap_err().to_string().contains("missing.su2"));
+    assert!(block_collection.blocks().is_empty());
+}
+
+#[test]
+fn write_blocks_as_su2() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("write_blocks_as_su2");
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+    block_collection.add_block(&PathBuf::from("./tests/data/cube.su2")).unwrap();
+
+    block_collection.write_blocks_as(&dir, GridFileType::Su2).unwrap();
+
+    let mut read_back = BlockCollection::new();
+    read_back.add_block(&dir.join("block_0000.su2")).unwrap();
+    read_back.add_block(&dir.join("block_0001.su2")).unwrap();
+    for (read, written) in read_back.blocks().iter().zip(block_collection.blocks()) {
+        assert_eq!(read.cells().len(), written.cells().len());
+        assert_eq!(read.boundary_tags(), written.boundary_tags());
+    }
+}
+
+fn write_temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn unknown_file_type() {
+    let mut block_collection = BlockCollection::new();
+    let result = block_collection.add_block(&PathBuf::from("./tests/data/square.txt"));
+
+    assert!(matches!(result, Err(GridError::UnknownFileType(_))));
+}
+
+#[test]
+fn su2_parse_error() {
+    let path = write_temp_file("parse_error.su2", "NDIME=2\nNPOIN=1\n0.0 zero\n");
+    let mut block_collection = BlockCollection::new();
+    let result = block_collection.add_block(&path);
+
+    match result {
+        Err(GridError::ParseError { line_number, line, .. }) => {
+            assert_eq!(line_number, 3);
+            assert_eq!(line, "0.0 zero");
+        }
+        other => panic!("Expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn su2_missing_connectivity() {
+    let path = write_temp_file("no_connectivity.su2", "NDIME=2\nNPOIN=1\n0.0 0.0\n");
+    let mut block_collection = BlockCollection::new();
+    let result = block_collection.add_block(&path);
+
+    assert!(matches!(result, Err(GridError::InvalidMesh(_))));
+}
+
+#[test]
+fn su2_hexagon() {
+    let mut su2 = String::from("NDIME= 2\nNPOIN= 6\n");
+    for i in 0 .. 6 {
+        let angle = i as f64 * std::f64::consts::PI / 3.0;
+        su2.push_str(&format!("{} {}\n", angle.cos(), angle.sin()));
+    }
+    su2.push_str("NELEM= 1\n7 0 1 2 3 4 5\nNMARK= 1\nMARKER_TAG= wall\nMARKER_ELEMS= 6\n");
+    for i in 0 .. 6 {
+        su2.push_str(&format!("3 {} {}\n", i, (i + 1) % 6));
+    }
+    let path = write_temp_file("hexagon.su2", &su2);
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&path).unwrap();
+    let block = block_collection.get_block(0);
+
+    assert_eq!(block.cells()[0].shape(), &CellShape::Polygon(6));
+    assert_eq!(block.interfaces().len(), 6);
+    assert!((block.cells()[0].volume() - 1.5 * 3f64.sqrt()).abs() < 1e-14);
+    assert_eq!(block.validate(), Ok(()));
+
+    // write it back out, and read it again
+    let copy = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("hexagon_copy.su2");
+    write_block(block, &copy).unwrap();
+    block_collection.add_block(&copy).unwrap();
+    assert_eq!(block_collection.get_block(1).cells()[0].shape(), &CellShape::Polygon(6));
+}
+
+fn open(path: &Path) -> BufReader<File> {
+    BufReader::new(File::open(path).unwrap())
+}
+
+#[test]
+fn su2_streaming() {
+    let paths = [
+        PathBuf::from("./tests/data/square.su2"),
+        PathBuf::from("./tests/data/square_with_comments.su2"),
+        PathBuf::from("./tests/data/cube.su2"),
+        write_temp_file("streaming_structured.su2", &rectangle_su2(4, 4, 1.0, 1.0)),
+    ];
+    for path in paths.iter() {
+        let block = read_su2_streaming(open(path), 3).unwrap();
+        let reference = read_su2(open(path), 3).unwrap();
+        assert_eq!(block.vertices(), reference.vertices());
+        assert_eq!(block.interfaces(), reference.interfaces());
+        assert_eq!(block.cells(), reference.cells());
+        assert_eq!(block.boundaries(), reference.boundaries());
+        assert_eq!(block.dimensions(), reference.dimensions());
+        assert_eq!(block.id(), 3);
+    }
+}
+
+#[test]
+fn su2_streaming_errors() {
+    // the bad coordinate is parsed after coming back to the points
+    let path = write_temp_file("streaming_bad_point.su2", "NPOIN= 2\n0 0\n0 x\nNDIME= 2\nNELEM= 0\n");
+    assert!(matches!(read_su2_streaming(open(&path), 0), Err(GridError::ParseError { line_number: 3, .. })));
+    assert!(matches!(read_su2(open(&path), 0), Err(GridError::ParseError { line_number: 3, .. })));
+
+    let path = write_temp_file("streaming_no_connectivity.su2", "NDIME=2\nNPOIN=1\n0.0 0.0\n");
+    assert!(matches!(read_su2_streaming(open(&path), 0), Err(GridError::InvalidMesh(_))));
+
+    let path = write_temp_file("streaming_short.su2", "NDIME=2\nNPOIN=1000000\n0.0 0.0\n");
+    assert!(matches!(read_su2_streaming(open(&path), 0), Err(GridError::InvalidMesh(_))));
+}
+
+/// The square grid, with the sections in the given order
+fn square_su2(sections: &[&str]) -> String {
+    let square = std::fs::read_to_string("./tests/data/square.su2").unwrap();
+    let section_start = |name: &str| square.find(name).unwrap();
+    let mut starts: Vec<usize> = ["NDIME=", "NPOIN=", "NELEM=", "NMARK="]
+        .iter()
+        .map(|name| section_start(name))
+        .collect();
+    starts.push(square.len());
+    sections.iter()
+        .map(|name| {
+            let start = section_start(name);
+            let end = starts.iter().copied().filter(|pos| *pos > start).min().unwrap();
+            &square[start..end]
+        })
+        .collect()
+}
+
+#[test]
+fn su2_dimensions_after_points() {
+    let path = write_temp_file("dimensions_last.su2", &square_su2(&["NPOIN=", "NELEM=", "NMARK=", "NDIME="]));
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&path).unwrap();
+    block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+
+    let block = block_collection.get_block(0);
+    let reference = block_collection.get_block(1);
+    assert_eq!(block.dimensions(), 2);
+    assert_eq!(block.vertices(), reference.vertices());
+    assert_eq!(block.cells(), reference.cells());
+}
+
+#[test]
+fn su2_missing_dimensions() {
+    let path = write_temp_file("no_dimensions.su2", &square_su2(&["NPOIN=", "NELEM=", "NMARK="]));
+    let mut block_collection = BlockCollection::new();
+    let result = block_collection.add_block(&path);
+
+    assert!(matches!(result, Err(GridError::InvalidMesh(_))));
+}
+
+#[test]
+fn su2_wrong_vertex_count() {
+    let contents = square_su2(&["NDIME=", "NPOIN=", "NELEM=", "NMARK="]).replace("NPOIN= 16", "NPOIN= 17");
+    let path = write_temp_file("wrong_vertex_count.su2", &contents);
+    let mut block_collection = BlockCollection::new();
+    let result = block_collection.add_block(&path);
+
+    match result {
+        Err(GridError::ParseError { line_number, line, .. }) => {
+            assert_eq!(line_number, 19);
+            assert_eq!(line, "NELEM= 9");
+        }
+        other => panic!("Expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn su2_vertex_out_of_range() {
+    let contents = square_su2(&["NDIME=", "NPOIN=", "NELEM=", "NMARK="]).replace("9 10 11 15 14", "9 10 11 16 14");
+    let path = write_temp_file("vertex_out_of_range.su2", &contents);
+    let mut block_collection = BlockCollection::new();
+    let result = block_collection.add_block(&path);
+
+    assert!(matches!(result, Err(GridError::InvalidMesh(_))));
+}
+
+#[test]
+fn su2_unsupported_element() {
+    let contents = square_su2(&["NDIME=", "NPOIN=", "NELEM=", "NMARK="]).replace("9 0 1 5 4", "12 0 1 5 4");
+    let path = write_temp_file("unsupported_element.su2", &contents);
+    let mut block_collection = BlockCollection::new();
+    let result = block_collection.add_block(&path);
+
+    assert!(matches!(result, Err(GridError::ParseError { line_number: 20, .. })));
+}
+
+#[test]
+fn su2_with_comments() {
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("./tests/data/square_with_comments.su2")).unwrap();
+    block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+
+    let block = block_collection.get_block(0);
+    let reference = block_collection.get_block(1);
+    assert_eq!(block.vertices(), reference.vertices());
+    assert_eq!(block.interfaces(), reference.interfaces());
+    assert_eq!(block.cells(), reference.cells());
+    assert_eq!(block.boundaries(), reference.boundaries());
+    assert_eq!(block.dimensions(), reference.dimensions());
+}
+
+#[test]
+fn su2_degenerate_cell() {
+    // found by fuzzing: the centre of this cell is on one of its interfaces
+    let contents = square_su2(&["NDIME=", "NPOIN=", "NELEM=", "NMARK="]).replace("9 10 11 15 14", "9 10 13 15 14");
+    let path = write_temp_file("degenerate_cell.su2", &contents);
+    let mut block_collection = BlockCollection::new();
+    let result = block_collection.add_block(&path);
+
+    assert!(matches!(result, Err(GridError::InvalidMesh(_))));
+}
+
+#[test]
+fn su2_invalid_dimensions() {
+    let contents = square_su2(&["NDIME=", "NPOIN=", "NELEM=", "NMARK="]).replace("NDIME= 2", "NDIME= 7");
+    let path = write_temp_file("invalid_dimensions.su2", &contents);
+    let mut block_collection = BlockCollection::new();
+    let result = block_collection.add_block(&path);
+
+    assert!(matches!(result, Err(GridError::ParseError { line_number: 1, .. })));
+}
+
+/// An su2 file with a single unit square cell, with its lower left corner at (x, 0)
+fn unit_square_su2(x: f64, left_tag: &str, right_tag: &str) -> String {
+    format!("\
+NDIME= 2
+NPOIN= 4
+{x} 0
+{} 0
+{} 1
+{x} 1
+NELEM= 1
+9 0 1 2 3
+NMARK= 3
+MARKER_TAG={left_tag}
+MARKER_ELEMS= 1
+3 3 0
+MARKER_TAG={right_tag}
+MARKER_ELEMS= 1
+3 1 2
+MARKER_TAG=wall
+MARKER_ELEMS= 2
+3 0 1
+3 2 3
+", x + 1.0, x + 1.0)
+}
+
+#[test]
+fn remove_orphan_vertices() {
+    // vertices 1 and 5 aren't used by the cell or the boundaries
+    let contents = "\
+NDIME= 2
+NPOIN= 6
+0 0
+9 9
+1 0
+1 1
+0 1
+8 8
+NELEM= 1
+9 0 2 3 4
+NMARK= 1
+MARKER_TAG=wall
+MARKER_ELEMS= 4
+3 0 2
+3 2 3
+3 3 4
+3 4 0
+";
+    let path = write_temp_file("orphan_vertices.su2", contents);
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&path).unwrap();
+    let mut block = block_collection.get_block(0).clone();
+
+    assert_eq!(block.validate(), Err(vec![GridValidationError::OrphanVertices(vec![1, 5])]));
+    assert_eq!(block.remove_orphan_vertices(), 2);
+    assert_eq!(block.validate(), Ok(()));
+    assert_eq!(block.vertices().len(), 4);
+    for (i, vertex) in block.vertices().iter().enumerate() {
+        assert_eq!(vertex.id(), i);
+    }
+    assert_eq!(block.vertices()[1].pos(), &Vector3{x: 1.0, y: 0.0, z: 0.0});
+    assert_eq!(block.cells()[0].vertex_ids(), &vec![0, 1, 2, 3]);
+    let wall_vertices: Vec<&Vec<usize>> = block.boundary_interfaces("wall")
+        .unwrap()
+        .iter()
+        .map(|interface| interface.vertex_ids())
+        .collect();
+    assert_eq!(wall_vertices, vec![&vec![0, 1], &vec![1, 2], &vec![2, 3], &vec![3, 0]]);
+    assert_eq!(block.remove_orphan_vertices(), 0);
+}
+
+#[test]
+fn merge_two_blocks() {
+    let left = write_temp_file("merge_left.su2", &unit_square_su2(0.0, "inflow", "connection"));
+    let right = write_temp_file("merge_right.su2", &unit_square_su2(1.0, "connection", "outflow"));
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_blocks_parallel(&[left, right]).unwrap();
+    let block = merge_blocks(block_collection.blocks()).unwrap();
+
+    assert_eq!(block.id(), 0);
+    assert_eq!(block.dimensions(), 2);
+    // the two vertices on the connection are shared
+    assert_eq!(block.vertices().len(), 6);
+    assert_eq!(block.cells().len(), 2);
+    assert_eq!(block.interfaces().len(), 7);
+    assert_eq!(block.boundary_tags(), vec!["inflow", "outflow", "wall"]);
+    assert_eq!(block.boundary_interfaces("wall").unwrap().len(), 4);
+    assert_eq!(block.cells_adjacent_to_boundary("outflow"), Some(vec![1]));
+    assert_eq!(block.validate(), Ok(()));
+}
+
+#[test]
+fn merge_single_block() {
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+    let block = merge_blocks(block_collection.blocks()).unwrap();
+    let original = block_collection.get_block(0);
+
+    assert_eq!(block.vertices(), original.vertices());
+    assert_eq!(block.cells(), original.cells());
+    assert_eq!(block.interfaces(), original.interfaces());
+    assert_eq!(block.boundaries(), original.boundaries());
+}
+
+#[test]
+fn merge_different_dimensions() {
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+    block_collection.add_block(&PathBuf::from("./tests/data/cube.su2")).unwrap();
+
+    assert!(matches!(merge_blocks(block_collection.blocks()), Err(GridError::InvalidMesh(_))));
+    assert!(matches!(merge_blocks(&[]), Err(GridError::InvalidMesh(_))));
+}
+
+#[test]
+fn dual_mesh_square() {
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("./tests/data/square.su2")).unwrap();
+    let block = block_collection.get_block(0);
+    let dual = block.compute_dual_mesh().unwrap();
+
+    // one cell per vertex, and one vertex per cell, boundary interface and boundary vertex
+    assert_eq!(dual.cells().len(), 16);
+    assert_eq!(dual.vertices().len(), 9 + 12 + 12);
+    let volume = |block: &GridBlock| -> f64 { block.cells().iter().map(|cell| cell.volume()).sum() };
+    assert!((volume(&dual) - volume(block)).abs() < 1e-14);
+    assert_eq!(dual.boundary_tags(), block.boundary_tags());
+    for tag in block.boundary_tags() {
+        assert_eq!(dual.boundary_interfaces(tag).unwrap().len(), 6);
+    }
+    assert_eq!(dual.check_cell_orientation(), Ok(()));
+    assert_eq!(dual.validate(), Ok(()));
+}
+
+#[test]
+fn dual_of_dual_structured() {
+    let n = 5;
+    let path = write_temp_file("structured.su2", &rectangle_su2(n, n, 1.0, 1.0));
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&path).unwrap();
+    let block = block_collection.get_block(0);
+    let dual_of_dual = block.compute_dual_mesh().unwrap().compute_dual_mesh().unwrap();
+
+    assert_eq!(dual_of_dual.validate(), Ok(()));
+    // the boundary vertices close off the cells next to the boundary
+    // differently, so only the cells away from the boundary are recovered
+    let h = 1.0 / n as f64;
+    let interior = |pos: &Vector3| pos.x > h && pos.x < 1.0 - h && pos.y > h && pos.y < 1.0 - h;
+    let positions = |block: &GridBlock, cell: &GridCell| -> Vec<Vector3> {
+        cell.vertex_ids().iter().map(|id| *block.vertices()[*id].pos()).collect()
+    };
+    let mut n_interior = 0;
+    for cell in block.cells().iter().filter(|cell| interior(cell.centre())) {
+        let dual_cell = dual_of_dual.cells()
+            .iter()
+            .find(|dual_cell| dual_cell.centre().dist_to(cell.centre()) < 1e-12)
+            .unwrap();
+        assert!((dual_cell.volume() - cell.volume()).abs() < 1e-14);
+        let dual_positions = positions(&dual_of_dual, dual_cell);
+        for pos in positions(block, cell) {
+            assert!(dual_positions.iter().any(|dual_pos| dual_pos.dist_to(&pos) < 1e-12));
+        }
+        n_interior += 1;
+    }
+    assert_eq!(n_interior, (n - 2) * (n - 2));
+}
+
+#[test]
+fn dual_mesh_3d() {
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block(&PathBuf::from("./tests/data/cube.su2")).unwrap();
+
+    assert!(matches!(block_collection.get_block(0).compute_dual_mesh(), Err(GridError::InvalidMesh(_))));
+}
+
+#[test]
+fn add_block_from_reader() {
+    let contents = std::fs::read("./tests/data/square.su2").unwrap();
+    let mut block_collection = BlockCollection::new();
+    block_collection.add_block_from_reader(Cursor::new(contents.clone()), GridFileType::Su2, 2).unwrap();
+    block_collection.add_block_from_reader(Cursor::new(unit_square_su2(0.0, "a", "b").into_bytes()), GridFileType::Su2, 0).unwrap();
+
+    let from_file = read_su2(open(Path::new("./tests/data/square.su2")), 2).unwrap();
+    let block = block_collection.find_block(2).unwrap();
+    assert_eq!(block.vertices().len(), from_file.vertices().len());
+    assert_eq!(block.interfaces().len(), from_file.interfaces().len());
+    assert_eq!(block.cells().len(), from_file.cells().len());
+    assert_eq!(block.boundaries(), from_file.boundaries());
+    assert_eq!(block_collection.find_block(0).unwrap().cells().len(), 1);
+
+    let result = block_collection.add_block_from_reader(Cursor::new(contents), GridFileType::Su2, 2);
+    assert!(matches!(result, Err(GridError::DuplicateBlockId(2))));
+
+    let result = block_collection.add_block_from_reader(Cursor::new(b"NDIME= 2\nNPOIN= 1\n".to_vec()), GridFileType::Su2, 3);
+    assert!(result.is_err());
+    assert!(block_collection.find_block(3).is_none());
+}